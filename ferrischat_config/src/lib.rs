@@ -10,6 +10,14 @@ pub static GLOBAL_CONFIG: OnceCell<AppConfig> = OnceCell::new();
 pub struct AppConfig {
     pub database: DatabaseConfig,
     pub redis: RedisConfig,
+    #[serde(default)]
+    pub invites: InviteConfig,
+    #[serde(default)]
+    pub messages: MessageConfig,
+    #[serde(default)]
+    pub attachments: AttachmentConfig,
+    #[serde(default)]
+    pub unfurl: UnfurlConfig,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -28,6 +36,81 @@ pub struct RedisConfig {
     pub password: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub struct InviteConfig {
+    /// Length of randomly generated invite codes.
+    pub code_length: usize,
+}
+
+impl Default for InviteConfig {
+    fn default() -> Self {
+        Self { code_length: 10 }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MessageConfig {
+    /// Maximum number of messages that can be pinned in a single channel.
+    pub max_pins: usize,
+    /// Maximum number of messages that can be deleted with a single bulk delete.
+    pub max_bulk_delete: usize,
+    /// Maximum number of messages a single user can have waiting to be sent.
+    pub max_scheduled_messages: usize,
+}
+
+impl Default for MessageConfig {
+    fn default() -> Self {
+        Self {
+            max_pins: 50,
+            max_bulk_delete: 100,
+            max_scheduled_messages: 100,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AttachmentConfig {
+    /// Maximum size of a single uploaded file, in bytes.
+    pub max_size: usize,
+    /// Maximum number of files that can be attached to one message.
+    pub max_per_message: usize,
+}
+
+impl Default for AttachmentConfig {
+    fn default() -> Self {
+        Self {
+            max_size: 25 * 1024 * 1024,
+            max_per_message: 10,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UnfurlConfig {
+    /// Whether links in messages get previews at all.
+    pub enabled: bool,
+    /// How long fetching a page can take, in milliseconds.
+    pub timeout: u64,
+    /// Most bytes of a page that are read.
+    pub max_size: usize,
+    /// Most links previewed in a single message.
+    pub max_urls_per_message: usize,
+    /// How long previews are cached for, in seconds.
+    pub cache_ttl: u64,
+}
+
+impl Default for UnfurlConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            timeout: 5000,
+            max_size: 1024 * 1024,
+            max_urls_per_message: 5,
+            cache_ttl: 3600,
+        }
+    }
+}
+
 impl Display for RedisConfig {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.write_str("redis://")?;
@@ -0,0 +1,61 @@
+use super::{image_key, is_valid_image_hash, ImageKind, IMAGE_SIZES};
+use crate::storage::get_storage;
+use crate::WebServerError;
+use axum::extract::{Path, Query};
+use bytes::Bytes;
+use ferrischat_common::request_json::GetImageParams;
+use ferrischat_common::types::ErrorJson;
+use http::header::{CACHE_CONTROL, CONTENT_TYPE};
+use http::{HeaderMap, HeaderValue};
+
+/// GET `/v0/avatars/{user_id}/{hash}.png`
+///
+/// Avatars are public. `size` picks one of the stored sizes, defaulting to the largest.
+pub async fn get_avatar(
+    Path((user_id, file)): Path<(u128, String)>,
+    Query(params): Query<GetImageParams>,
+) -> Result<(HeaderMap, Bytes), WebServerError> {
+    get_image(ImageKind::UserAvatar, user_id, &file, params.size).await
+}
+
+/// GET `/v0/icons/{guild_id}/{hash}.png`
+///
+/// Icons are public. `size` picks one of the stored sizes, defaulting to the largest.
+pub async fn get_icon(
+    Path((guild_id, file)): Path<(u128, String)>,
+    Query(params): Query<GetImageParams>,
+) -> Result<(HeaderMap, Bytes), WebServerError> {
+    get_image(ImageKind::GuildIcon, guild_id, &file, params.size).await
+}
+
+async fn get_image(
+    kind: ImageKind,
+    owner_id: u128,
+    file: &str,
+    size: Option<u32>,
+) -> Result<(HeaderMap, Bytes), WebServerError> {
+    let hash = file
+        .strip_suffix(".png")
+        .filter(|hash| is_valid_image_hash(hash))
+        .ok_or_else(|| ErrorJson::new_404(format!("Unknown image {}", file)))?;
+
+    let size = size.unwrap_or(IMAGE_SIZES[IMAGE_SIZES.len() - 1]);
+    if !IMAGE_SIZES.contains(&size) {
+        return Err(ErrorJson::new_400(format!("size must be one of {:?}", IMAGE_SIZES)).into());
+    }
+
+    let data = get_storage()?
+        .get(&image_key(kind, owner_id, hash, size))
+        .await?
+        .ok_or_else(|| ErrorJson::new_404(format!("Unknown image {}", file)))?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("image/png"));
+    // the hash changes whenever the image does
+    headers.insert(
+        CACHE_CONTROL,
+        HeaderValue::from_static("public, max-age=31536000, immutable"),
+    );
+
+    Ok((headers, data))
+}
@@ -0,0 +1,19 @@
+mod get_image;
+mod process;
+mod upload;
+
+pub use get_image::*;
+pub use process::*;
+pub use upload::*;
+
+use axum::routing::get;
+use axum::Router;
+
+pub fn generate_avatars_routes() -> axum::Router {
+    debug!("generating routes for avatars");
+    Router::new()
+        // GET    /avatars/:user_id/:hash.png
+        .route(expand_version!("avatars/:user_id/:file"), get(get_avatar))
+        // GET    /icons/:guild_id/:hash.png
+        .route(expand_version!("icons/:guild_id/:file"), get(get_icon))
+}
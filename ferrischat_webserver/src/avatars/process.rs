@@ -0,0 +1,93 @@
+use image::imageops::FilterType;
+use image::io::{Limits, Reader};
+use image::{ImageFormat, ImageOutputFormat};
+use std::io::Cursor;
+
+/// Sizes every avatar and icon is stored at, in pixels. The largest is served by default.
+pub const IMAGE_SIZES: [u32; 5] = [32, 64, 128, 256, 512];
+/// Largest upload accepted, in bytes, after base64 decoding.
+pub const MAX_IMAGE_SIZE: usize = 8 * 1024 * 1024;
+/// Largest width or height an upload can have, in pixels.
+pub const MAX_IMAGE_DIMENSION: u32 = 4096;
+
+/// An uploaded image, normalized and resized to each of [`IMAGE_SIZES`].
+pub struct ProcessedImage {
+    /// Hex digest of the largest size, which is what avatar fields hold.
+    pub hash: String,
+    /// PNG data for each of [`IMAGE_SIZES`], in the same order.
+    pub sizes: Vec<(u32, Vec<u8>)>,
+}
+
+/// Decodes an image sent as base64, optionally as a `data:` URI.
+pub fn decode_image_upload(upload: &str) -> Result<Vec<u8>, String> {
+    let encoded = match upload.strip_prefix("data:") {
+        Some(uri) => {
+            uri.split_once(";base64,")
+                .ok_or_else(|| "data URIs must be base64 encoded".to_string())?
+                .1
+        }
+        None => upload,
+    };
+    if encoded.len() / 4 * 3 > MAX_IMAGE_SIZE {
+        return Err(format!("images must be at most {} bytes", MAX_IMAGE_SIZE));
+    }
+    base64::decode(encoded.trim()).map_err(|e| format!("invalid base64 image: {}", e))
+}
+
+/// Validates an image, crops it square and re-encodes it as PNG at each of [`IMAGE_SIZES`].
+///
+/// Re-encoding drops any metadata the original carried. Animated images keep their first frame.
+/// This is CPU heavy, so run it with [`tokio::task::spawn_blocking`].
+pub fn process_image(data: &[u8]) -> Result<ProcessedImage, String> {
+    let mut reader = Reader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|e| format!("failed to read image: {}", e))?;
+    if !matches!(
+        reader.format(),
+        Some(ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::Gif | ImageFormat::WebP)
+    ) {
+        return Err("images must be PNG, JPEG, GIF or WebP".to_string());
+    }
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+    limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+    reader.limits(limits);
+
+    let image = reader
+        .decode()
+        .map_err(|e| format!("failed to decode image: {}", e))?;
+
+    let side = image.width().min(image.height());
+    let image = image.crop_imm(
+        (image.width() - side) / 2,
+        (image.height() - side) / 2,
+        side,
+        side,
+    );
+
+    let mut sizes = Vec::with_capacity(IMAGE_SIZES.len());
+    for size in IMAGE_SIZES {
+        let mut png = Vec::new();
+        image
+            .resize_exact(size, size, FilterType::Lanczos3)
+            .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+            .map_err(|e| format!("failed to encode image: {}", e))?;
+        sizes.push((size, png));
+    }
+
+    let largest = &sizes[sizes.len() - 1].1;
+    let hash = ring::digest::digest(&ring::digest::SHA256, largest)
+        .as_ref()
+        .iter()
+        .take(16)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+
+    Ok(ProcessedImage { hash, sizes })
+}
+
+/// Whether `hash` could have come from [`process_image`].
+pub fn is_valid_image_hash(hash: &str) -> bool {
+    hash.len() == 32 && hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
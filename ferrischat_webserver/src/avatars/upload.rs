@@ -0,0 +1,86 @@
+use super::{decode_image_upload, process_image, IMAGE_SIZES};
+use crate::storage::get_storage;
+use crate::WebServerError;
+use ferrischat_common::types::ErrorJson;
+
+/// What an uploaded image belongs to.
+#[derive(Copy, Clone)]
+pub enum ImageKind {
+    UserAvatar,
+    GuildIcon,
+}
+
+impl ImageKind {
+    fn prefix(self) -> &'static str {
+        match self {
+            Self::UserAvatar => "avatars",
+            Self::GuildIcon => "icons",
+        }
+    }
+}
+
+/// Storage key for one size of an avatar or icon.
+pub fn image_key(kind: ImageKind, owner_id: u128, hash: &str, size: u32) -> String {
+    format!("{}/{}/{}/{}.png", kind.prefix(), owner_id, hash, size)
+}
+
+/// Processes a base64 upload and stores every size of it, returning its hash.
+///
+/// An empty upload means the image is being removed, and gives `None`.
+pub async fn upload_image(
+    kind: ImageKind,
+    owner_id: u128,
+    upload: String,
+) -> Result<Option<String>, WebServerError> {
+    if upload.is_empty() {
+        return Ok(None);
+    }
+
+    let data = decode_image_upload(&upload).map_err(ErrorJson::new_400)?;
+    let image = tokio::task::spawn_blocking(move || process_image(&data))
+        .await
+        .map_err(|e| ErrorJson::new_500(format!("image processing panicked: {}", e), true, None))?
+        .map_err(ErrorJson::new_400)?;
+
+    let storage = get_storage()?;
+    for (size, png) in image.sizes {
+        storage
+            .put(&image_key(kind, owner_id, &image.hash, size), png.into())
+            .await?;
+    }
+
+    Ok(Some(image.hash))
+}
+
+/// Deletes every size of an image that's no longer used.
+///
+/// Failures are only logged, as the image is already unreachable.
+pub async fn delete_image_files(kind: ImageKind, owner_id: u128, hash: &str) {
+    let storage = match get_storage() {
+        Ok(s) => s,
+        Err(_) => {
+            error!("storage is missing: image {} was left behind", hash);
+            return;
+        }
+    };
+
+    for size in IMAGE_SIZES {
+        if let Err(e) = storage.delete(&image_key(kind, owner_id, hash, size)).await {
+            error!(%owner_id, %hash, "failed to delete image file: {}", e);
+        }
+    }
+}
+
+/// Deletes the files of an image that was just replaced, unless it was replaced with itself.
+pub async fn delete_replaced_image(
+    kind: ImageKind,
+    owner_id: u128,
+    old: Option<String>,
+    new: &Option<String>,
+) {
+    if let Some(old) = old {
+        if new.as_ref() != Some(&old) {
+            delete_image_files(kind, owner_id, &old).await;
+        }
+    }
+}
@@ -3,12 +3,14 @@ use crate::{Json, WebServerError};
 use axum::extract::Path;
 use axum::Json as JsonInput;
 use ferrischat_common::request_json::ChannelCreateJson;
-use ferrischat_common::types::{Channel, ModelType};
+use ferrischat_common::types::{Channel, ChannelType, ErrorJson, ModelType};
 use ferrischat_common::ws::WsOutboundEvent;
 use ferrischat_macros::get_db_or_fail;
 use ferrischat_snowflake_generator::generate_snowflake;
 
 /// POST `/v0/guilds/{guild_id/channels`
+///
+/// New channels are placed after every other channel in the guild.
 pub async fn create_channel(
     _: crate::Authorization,
     channel_info: JsonInput<ChannelCreateJson>,
@@ -16,7 +18,19 @@ pub async fn create_channel(
 ) -> Result<Json<Channel>, WebServerError> {
     let db = get_db_or_fail!();
 
-    let ChannelCreateJson { name } = channel_info.0;
+    let ChannelCreateJson {
+        name,
+        channel_type,
+        parent_id,
+    } = channel_info.0;
+    let channel_type = channel_type.unwrap_or(ChannelType::Text);
+    // DMs and threads have their own endpoints
+    if !matches!(channel_type, ChannelType::Text | ChannelType::Category) {
+        return Err(ErrorJson::new_400(
+            "only text channels and categories can be created here".to_string(),
+        )
+        .into());
+    }
 
     let node_id = get_node_id!();
     let channel_id = generate_snowflake::<0>(ModelType::Channel as u8, node_id);
@@ -24,19 +38,66 @@ pub async fn create_channel(
 
     let bigint_guild_id = u128_to_bigdecimal!(guild_id);
 
-    sqlx::query!(
-        "INSERT INTO channels VALUES ($1, $2, $3)",
+    let bigint_parent_id = match parent_id {
+        Some(parent_id) => {
+            if channel_type == ChannelType::Category {
+                return Err(ErrorJson::new_400(
+                    "categories can't be placed in other categories".to_string(),
+                )
+                .into());
+            }
+            let bigint_parent_id = u128_to_bigdecimal!(parent_id);
+            if !sqlx::query!(
+                r#"SELECT EXISTS(SELECT * FROM channels WHERE id = $1 AND guild_id = $2 AND channel_type = $3) AS "exists!""#,
+                bigint_parent_id,
+                bigint_guild_id,
+                ChannelType::Category as i16
+            )
+            .fetch_one(db)
+            .await?
+            .exists
+            {
+                return Err(ErrorJson::new_400(format!(
+                    "channel {} is not a category in this guild",
+                    parent_id
+                ))
+                .into());
+            }
+            Some(bigint_parent_id)
+        }
+        None => None,
+    };
+
+    let position = sqlx::query!(
+        r#"
+INSERT INTO channels (id, name, guild_id, channel_type, parent_id, position)
+SELECT $1, $2, $3, $4, $5, coalesce(max(position) + 1, 0)
+FROM channels
+WHERE guild_id = $3
+RETURNING position
+"#,
         bigint_channel_id,
         name,
-        bigint_guild_id
+        bigint_guild_id,
+        channel_type as i16,
+        bigint_parent_id
     )
-    .execute(db)
-    .await?;
+    .fetch_one(db)
+    .await?
+    .position;
 
     let channel_obj = Channel {
         id: channel_id,
         name,
-        guild_id,
+        guild_id: Some(guild_id),
+        channel_type,
+        parent_id,
+        position,
+        topic: None,
+        slowmode: 0,
+        nsfw: false,
+        announcement: false,
+        message_ttl: None,
     };
 
     let event = WsOutboundEvent::ChannelCreate {
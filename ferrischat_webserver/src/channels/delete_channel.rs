@@ -1,7 +1,8 @@
+use crate::storage::delete_attachment_files;
 use crate::ws::fire_event;
 use crate::WebServerError;
 use axum::extract::Path;
-use ferrischat_common::types::{Channel, ErrorJson};
+use ferrischat_common::types::{Channel, ChannelType, ErrorJson};
 use ferrischat_common::ws::WsOutboundEvent;
 
 /// DELETE `/v0/channels/{channel_id}`
@@ -12,22 +13,93 @@ pub async fn delete_channel(
     let db = get_db_or_fail!();
     let bigint_channel_id = u128_to_bigdecimal!(channel_id);
 
+    let mut tx = db.begin().await?;
+
+    // the rows go with their messages, but the files have to be removed by hand afterwards
+    let attachments = sqlx::query!(
+        r#"
+SELECT a.id
+FROM attachments a
+    INNER JOIN messages m ON m.id = a.message_id
+    INNER JOIN channels c ON c.id = m.channel_id
+WHERE c.id = $1
+   OR (c.parent_id = $1 AND c.channel_type = $2)
+"#,
+        bigint_channel_id,
+        ChannelType::Thread as i16
+    )
+    .fetch_all(&mut tx)
+    .await?;
+
+    // threads go down with the channel they were created in
+    let threads = sqlx::query!(
+        "DELETE FROM channels WHERE parent_id = $1 AND channel_type = $2 RETURNING *",
+        bigint_channel_id,
+        ChannelType::Thread as i16
+    )
+    .fetch_all(&mut tx)
+    .await?;
+
+    // DMs go away once everyone has left them instead
     let channel = sqlx::query!(
-        "DELETE FROM channels WHERE id = $1 RETURNING *",
+        "DELETE FROM channels WHERE id = $1 AND guild_id IS NOT NULL RETURNING *",
         bigint_channel_id,
     )
-    .fetch_optional(db)
+    .fetch_optional(&mut tx)
     .await?
     .ok_or_else(|| ErrorJson::new_404(format!("Unknown channel with ID {}", channel_id)))?;
-    let channel = Channel {
+
+    tx.commit().await?;
+
+    let mut attachment_ids = Vec::with_capacity(attachments.len());
+    for x in attachments {
+        attachment_ids.push(bigdecimal_to_u128!(x.id));
+    }
+    delete_attachment_files(attachment_ids).await;
+
+    let mut deleted = Vec::with_capacity(threads.len() + 1);
+    for thread in threads {
+        deleted.push(Channel {
+            id: bigdecimal_to_u128!(thread.id),
+            guild_id: match thread.guild_id {
+                Some(guild_id) => Some(bigdecimal_to_u128!(guild_id)),
+                None => None,
+            },
+            name: thread.name,
+            channel_type: ChannelType::Thread,
+            parent_id: Some(channel_id),
+            position: thread.position,
+            topic: thread.topic,
+            slowmode: thread.slowmode,
+            nsfw: thread.nsfw,
+            announcement: thread.announcement,
+            message_ttl: thread.message_ttl,
+        });
+    }
+    deleted.push(Channel {
         id: bigdecimal_to_u128!(channel.id),
-        guild_id: bigdecimal_to_u128!(channel.guild_id),
+        guild_id: match channel.guild_id {
+            Some(guild_id) => Some(bigdecimal_to_u128!(guild_id)),
+            None => None,
+        },
         name: channel.name,
-    };
-
-    let event = WsOutboundEvent::ChannelDelete { channel };
+        channel_type: ChannelType::from_i16(channel.channel_type).unwrap_or(ChannelType::Text),
+        parent_id: match channel.parent_id {
+            Some(parent_id) => Some(bigdecimal_to_u128!(parent_id)),
+            None => None,
+        },
+        position: channel.position,
+        topic: channel.topic,
+        slowmode: channel.slowmode,
+        nsfw: channel.nsfw,
+        announcement: channel.announcement,
+        message_ttl: channel.message_ttl,
+    });
 
-    fire_event(&event).await?;
+    for channel in deleted {
+        let event = WsOutboundEvent::ChannelDelete { channel };
+        fire_event(&event).await?;
+    }
 
     Ok(http::StatusCode::NO_CONTENT)
 }
@@ -0,0 +1,74 @@
+use super::{check_dm_recipient, load_dm_channel, MAX_GROUP_DM_RECIPIENTS};
+use crate::ws::fire_event;
+use crate::WebServerError;
+use axum::extract::Path;
+use ferrischat_common::types::{DMChannel, ErrorJson};
+use ferrischat_common::ws::WsOutboundEvent;
+
+/// PUT `/v0/channels/{channel_id}/recipients/{user_id}`
+///
+/// Any recipient of a group DM can add more users to it.
+pub async fn add_dm_recipient(
+    Path((channel_id, user_id)): Path<(u128, u128)>,
+    auth: crate::Authorization,
+) -> Result<crate::Json<DMChannel>, WebServerError> {
+    let db = get_db_or_fail!();
+
+    check_dm_recipient(db, channel_id, auth.0).await?;
+    let old = load_dm_channel(db, channel_id)
+        .await?
+        .ok_or_else(|| ErrorJson::new_404(format!("Unknown channel with ID {}", channel_id)))?;
+
+    if !old.group {
+        return Err(ErrorJson::new_400("users can only be added to group DMs".to_string()).into());
+    }
+    if old.recipients.iter().any(|u| u.id == user_id) {
+        return Ok(crate::Json {
+            obj: old,
+            code: 200,
+        });
+    }
+    if old.recipients.len() >= MAX_GROUP_DM_RECIPIENTS {
+        return Err(ErrorJson::new_400(format!(
+            "group DMs can have at most {} users",
+            MAX_GROUP_DM_RECIPIENTS
+        ))
+        .into());
+    }
+
+    let bigint_user_id = u128_to_bigdecimal!(user_id);
+    if !sqlx::query!(
+        r#"SELECT EXISTS(SELECT * FROM users WHERE id = $1) AS "exists!""#,
+        bigint_user_id
+    )
+    .fetch_one(db)
+    .await?
+    .exists
+    {
+        return Err(ErrorJson::new_404(format!("Unknown user with ID {}", user_id)).into());
+    }
+
+    sqlx::query!(
+        "INSERT INTO channel_recipients VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        u128_to_bigdecimal!(channel_id),
+        bigint_user_id
+    )
+    .execute(db)
+    .await?;
+
+    let new = load_dm_channel(db, channel_id)
+        .await?
+        .ok_or_else(|| ErrorJson::new_404(format!("Unknown channel with ID {}", channel_id)))?;
+
+    let event = WsOutboundEvent::DmChannelUpdate {
+        old,
+        new: new.clone(),
+    };
+
+    fire_event(&event).await?;
+
+    Ok(crate::Json {
+        obj: new,
+        code: 200,
+    })
+}
@@ -1,49 +1,163 @@
-use axum::extract::{Path, Json};
-use ferrischat_macros::get_db_or_fail;
-use ferrischat_common::types::{ModelType, DMChannel};
+use super::{load_dm_channel, MAX_GROUP_DM_RECIPIENTS};
+use crate::ws::fire_event;
+use crate::WebServerError;
+use axum::extract::Json;
 use ferrischat_common::request_json::DMChannelCreateJson;
-
+use ferrischat_common::types::{ChannelType, DMChannel, ErrorJson, ModelType};
+use ferrischat_common::ws::WsOutboundEvent;
+use ferrischat_snowflake_generator::generate_snowflake;
 
 /// POST `/v0/users/me/channels`
+///
+/// If a 1:1 DM with this user already exists, it's returned instead of making a new one.
 pub async fn create_dm_channel(
     auth: crate::Authorization,
-    Json(DMChannelCreateJson { group, name }): Json<DMChannelCreateJson>,
-    Path(users): Path<String>,
-) -> Result<Json<DMChannel>, WebServerError> {
+    Json(DMChannelCreateJson {
+        recipients,
+        group,
+        name,
+    }): Json<DMChannelCreateJson>,
+) -> Result<crate::Json<DMChannel>, WebServerError> {
     let db = get_db_or_fail!();
+    let bigint_user_id = u128_to_bigdecimal!(auth.0);
+
+    let mut dm_key = None;
+    let mut recipients = recipients;
+    recipients.retain(|id| *id != auth.0);
+    recipients.sort_unstable();
+    recipients.dedup();
+
+    if group {
+        if recipients.is_empty() || recipients.len() >= MAX_GROUP_DM_RECIPIENTS {
+            return Err(ErrorJson::new_400(format!(
+                "group DMs must have between 2 and {} users, including you",
+                MAX_GROUP_DM_RECIPIENTS
+            ))
+            .into());
+        }
+        if name.as_ref().map_or(false, |n| n.chars().count() > 100) {
+            return Err(ErrorJson::new_400(
+                "names must be at most 100 characters long".to_string(),
+            )
+            .into());
+        }
+    } else {
+        if recipients.len() != 1 {
+            return Err(ErrorJson::new_400(
+                "Direct DM messages can not contain more than 1 other user. \
+                 Consider making this a group DM to include more people"
+                    .to_string(),
+            )
+            .into());
+        }
+        if name.is_some() {
+            return Err(ErrorJson::new_400("only group DMs can have a name".to_string()).into());
+        }
+
+        let key = dm_channel_key(auth.0, recipients[0]);
+        if let Some(channel) = load_dm_channel_by_key(db, &key).await? {
+            return Ok(crate::Json {
+                obj: channel,
+                code: 200,
+            });
+        }
+        dm_key = Some(key);
+    }
+
+    let bigint_recipients = recipients
+        .iter()
+        .map(|id| u128_to_bigdecimal!(*id))
+        .chain(std::iter::once(bigint_user_id.clone()))
+        .collect::<Vec<_>>();
+
+    let found = sqlx::query!(
+        r#"SELECT count(*) AS "count!" FROM users WHERE id = ANY($1)"#,
+        &bigint_recipients[..]
+    )
+    .fetch_one(db)
+    .await?
+    .count;
+    if usize::try_from(found).unwrap_or(0) != bigint_recipients.len() {
+        return Err(ErrorJson::new_404("one or more recipients don't exist".to_string()).into());
+    }
 
     let node_id = get_node_id!();
-    let channel_id = generate_snowflake::<0>(ModelType::DMChannel as u8, node_id);
+    let channel_id = generate_snowflake::<0>(ModelType::Channel as u8, node_id);
     let bigint_channel_id = u128_to_bigdecimal!(channel_id);
 
-    let users = users::split(',').filter_map(str::parse::<u128>).collect();
-    
-    if !group && users.len() > 1 {
-        return Err(ErrorJson::new_400("Direct DM messages can not contain more than 1 other user. \
-         Consider making this a group DM to include more people".to_string()).into());
-    }
+    let (channel_type, owner_id) = if group {
+        (ChannelType::GroupDm, Some(bigint_user_id))
+    } else {
+        (ChannelType::Dm, None)
+    };
 
-    users.push(auth.0);
+    let mut tx = db.begin().await?;
+
+    // the key is unique, so if someone else just opened the same DM, theirs is used instead
+    let inserted = sqlx::query!(
+        "INSERT INTO channels (id, name, channel_type, owner_id, dm_key) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (dm_key) DO NOTHING RETURNING id",
+        bigint_channel_id,
+        name.unwrap_or_default(),
+        channel_type as i16,
+        owner_id,
+        dm_key
+    )
+    .fetch_optional(&mut tx)
+    .await?
+    .is_some();
+    if !inserted {
+        tx.rollback().await?;
+        if let Some(ref key) = dm_key {
+            if let Some(channel) = load_dm_channel_by_key(db, key).await? {
+                return Ok(crate::Json {
+                    obj: channel,
+                    code: 200,
+                });
+            }
+        }
+        return Err(ErrorJson::new_404("DM channel just disappeared".to_string()).into());
+    }
 
     sqlx::query!(
-        "INSERT INTO dmchannels VALUES ($1, $2, $3, $4)",
+        "INSERT INTO channel_recipients SELECT $1, unnest($2::NUMERIC[])",
         bigint_channel_id,
-        users,
-        group,
-        name,
+        &bigint_recipients[..]
     )
-    .execute(db)
+    .execute(&mut tx)
     .await?;
 
-    let dm_channel_obj = DMChannel {
-        id: bigint_channel_id,
-        name,
-        users,
-        group
+    tx.commit().await?;
+
+    let channel = load_dm_channel(db, channel_id)
+        .await?
+        .ok_or_else(|| ErrorJson::new_404("DM channel just disappeared".to_string()))?;
+
+    let event = WsOutboundEvent::DmChannelCreate {
+        channel: channel.clone(),
     };
 
+    fire_event(&event).await?;
+
     Ok(crate::Json {
-        obj: dm_channel_obj,
+        obj: channel,
         code: 201,
     })
-}
\ No newline at end of file
+}
+
+/// Identifies the 1:1 DM between two users, whichever of them opened it.
+fn dm_channel_key(a: u128, b: u128) -> String {
+    format!("{}:{}", a.min(b), a.max(b))
+}
+
+async fn load_dm_channel_by_key(
+    db: &sqlx::PgPool,
+    key: &str,
+) -> Result<Option<DMChannel>, WebServerError> {
+    let existing = sqlx::query!("SELECT id FROM channels WHERE dm_key = $1", key)
+        .fetch_optional(db)
+        .await?;
+    match existing {
+        Some(existing) => load_dm_channel(db, bigdecimal_to_u128!(existing.id)).await,
+        None => Ok(None),
+    }
+}
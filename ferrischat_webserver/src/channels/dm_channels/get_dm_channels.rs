@@ -0,0 +1,29 @@
+use super::load_dm_channel;
+use crate::WebServerError;
+use ferrischat_common::types::DMChannel;
+
+/// GET `/v0/users/me/channels`
+pub async fn get_dm_channels(
+    auth: crate::Authorization,
+) -> Result<crate::Json<Vec<DMChannel>>, WebServerError> {
+    let db = get_db_or_fail!();
+
+    let resp = sqlx::query!(
+        "SELECT channel_id FROM channel_recipients WHERE user_id = $1 ORDER BY channel_id",
+        u128_to_bigdecimal!(auth.0)
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut channels = Vec::with_capacity(resp.len());
+    for x in resp {
+        if let Some(channel) = load_dm_channel(db, bigdecimal_to_u128!(x.channel_id)).await? {
+            channels.push(channel);
+        }
+    }
+
+    Ok(crate::Json {
+        obj: channels,
+        code: 200,
+    })
+}
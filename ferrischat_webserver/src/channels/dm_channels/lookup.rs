@@ -0,0 +1,87 @@
+use crate::WebServerError;
+use ferrischat_common::types::{ChannelType, DMChannel, ErrorJson, Pronouns, User, UserFlags};
+
+/// Most users a group DM can have, including its owner.
+pub const MAX_GROUP_DM_RECIPIENTS: usize = 10;
+
+/// Fetches a DM channel along with its recipients.
+///
+/// Returns `None` if there's no DM channel with this ID: guild channels don't count.
+pub async fn load_dm_channel(
+    db: &sqlx::PgPool,
+    channel_id: u128,
+) -> Result<Option<DMChannel>, WebServerError> {
+    let bigint_channel_id = u128_to_bigdecimal!(channel_id);
+
+    let channel = match sqlx::query!(
+        "SELECT name, channel_type, owner_id FROM channels WHERE id = $1 AND guild_id IS NULL",
+        bigint_channel_id
+    )
+    .fetch_optional(db)
+    .await?
+    {
+        Some(channel) => channel,
+        None => return Ok(None),
+    };
+
+    let resp = sqlx::query!(
+        r#"
+SELECT u.*
+FROM channel_recipients r
+    INNER JOIN users u ON u.id = r.user_id
+WHERE r.channel_id = $1
+ORDER BY u.id
+"#,
+        bigint_channel_id
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut recipients = Vec::with_capacity(resp.len());
+    for u in resp {
+        recipients.push(User {
+            id: bigdecimal_to_u128!(u.id),
+            name: u.name,
+            avatar: u.avatar,
+            guilds: None,
+            flags: UserFlags::from_bits_truncate(u.flags),
+            discriminator: u.discriminator,
+            pronouns: u.pronouns.and_then(Pronouns::from_i16),
+        });
+    }
+
+    Ok(Some(DMChannel {
+        id: channel_id,
+        // unnamed DMs are stored with an empty name
+        name: Some(channel.name).filter(|n| !n.is_empty()),
+        group: channel.channel_type == ChannelType::GroupDm as i16,
+        owner_id: match channel.owner_id {
+            Some(owner_id) => Some(bigdecimal_to_u128!(owner_id)),
+            None => None,
+        },
+        recipients,
+    }))
+}
+
+/// Checks that a user is one of a DM channel's recipients.
+///
+/// Returns a 404 if they aren't, so DMs can't be probed for.
+pub async fn check_dm_recipient(
+    db: &sqlx::PgPool,
+    channel_id: u128,
+    user_id: u128,
+) -> Result<(), WebServerError> {
+    if sqlx::query!(
+        r#"SELECT EXISTS(SELECT * FROM channel_recipients WHERE channel_id = $1 AND user_id = $2) AS "exists!""#,
+        u128_to_bigdecimal!(channel_id),
+        u128_to_bigdecimal!(user_id)
+    )
+    .fetch_one(db)
+    .await?
+    .exists
+    {
+        Ok(())
+    } else {
+        Err(ErrorJson::new_404(format!("Unknown channel with ID {}", channel_id)).into())
+    }
+}
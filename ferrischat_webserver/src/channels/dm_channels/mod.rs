@@ -0,0 +1,36 @@
+mod add_recipient;
+mod create_dm_channel;
+mod get_dm_channels;
+mod lookup;
+mod remove_recipient;
+
+pub use add_recipient::*;
+pub use create_dm_channel::*;
+pub use get_dm_channels::*;
+pub use lookup::*;
+pub use remove_recipient::*;
+
+use axum::routing::{delete, get, put};
+use axum::Router;
+
+pub fn generate_dm_channels_routes() -> axum::Router {
+    debug!("generating routes for DM channels");
+    Router::new()
+        // POST   /users/me/channels
+        // GET    /users/me/channels
+        .route(
+            expand_version!("users/me/channels"),
+            get(get_dm_channels).post(create_dm_channel),
+        )
+        // DELETE /users/me/channels/:channel_id
+        .route(
+            expand_version!("users/me/channels/:channel_id"),
+            delete(leave_dm_channel),
+        )
+        // PUT    /channels/:channel_id/recipients/:user_id
+        // DELETE /channels/:channel_id/recipients/:user_id
+        .route(
+            expand_version!("channels/:channel_id/recipients/:user_id"),
+            put(add_dm_recipient).delete(remove_dm_recipient),
+        )
+}
@@ -0,0 +1,136 @@
+use super::{check_dm_recipient, load_dm_channel};
+use crate::storage::delete_attachment_files;
+use crate::ws::fire_event;
+use crate::WebServerError;
+use axum::extract::Path;
+use ferrischat_common::types::{DMChannel, ErrorJson};
+use ferrischat_common::ws::WsOutboundEvent;
+
+/// DELETE `/v0/channels/{channel_id}/recipients/{user_id}`
+///
+/// Only the owner of a group DM can remove other users from it.
+pub async fn remove_dm_recipient(
+    Path((channel_id, user_id)): Path<(u128, u128)>,
+    auth: crate::Authorization,
+) -> Result<http::StatusCode, WebServerError> {
+    let db = get_db_or_fail!();
+
+    check_dm_recipient(db, channel_id, auth.0).await?;
+    let channel = load_dm_channel(db, channel_id)
+        .await?
+        .ok_or_else(|| ErrorJson::new_404(format!("Unknown channel with ID {}", channel_id)))?;
+
+    if !channel.group {
+        return Err(
+            ErrorJson::new_400("users can only be removed from group DMs".to_string()).into(),
+        );
+    }
+    if user_id != auth.0 && channel.owner_id != Some(auth.0) {
+        return Err(ErrorJson::new_403(
+            "only the owner of a group DM can remove other users".to_string(),
+        )
+        .into());
+    }
+    if !channel.recipients.iter().any(|u| u.id == user_id) {
+        return Err(ErrorJson::new_404(format!("user {} is not in this channel", user_id)).into());
+    }
+
+    remove_recipient(db, channel, user_id).await?;
+
+    Ok(http::StatusCode::NO_CONTENT)
+}
+
+/// DELETE `/v0/users/me/channels/{channel_id}`
+pub async fn leave_dm_channel(
+    Path(channel_id): Path<u128>,
+    auth: crate::Authorization,
+) -> Result<http::StatusCode, WebServerError> {
+    let db = get_db_or_fail!();
+
+    check_dm_recipient(db, channel_id, auth.0).await?;
+    let channel = load_dm_channel(db, channel_id)
+        .await?
+        .ok_or_else(|| ErrorJson::new_404(format!("Unknown channel with ID {}", channel_id)))?;
+
+    if !channel.group {
+        return Err(ErrorJson::new_400("1:1 DMs can't be left".to_string()).into());
+    }
+
+    remove_recipient(db, channel, auth.0).await?;
+
+    Ok(http::StatusCode::NO_CONTENT)
+}
+
+/// Removes a user from a group DM.
+///
+/// If the owner leaves, ownership passes to whoever has the oldest account,
+/// and once the last user leaves the channel is deleted.
+async fn remove_recipient(
+    db: &sqlx::PgPool,
+    old: DMChannel,
+    user_id: u128,
+) -> Result<(), WebServerError> {
+    let bigint_channel_id = u128_to_bigdecimal!(old.id);
+
+    let mut tx = db.begin().await?;
+
+    sqlx::query!(
+        "DELETE FROM channel_recipients WHERE channel_id = $1 AND user_id = $2",
+        bigint_channel_id,
+        u128_to_bigdecimal!(user_id)
+    )
+    .execute(&mut tx)
+    .await?;
+
+    if old.recipients.len() <= 1 {
+        // the rows go with the channel, but the files have to be removed by hand afterwards
+        let attachments = sqlx::query!(
+            r#"
+SELECT a.id
+FROM attachments a
+    INNER JOIN messages m ON m.id = a.message_id
+WHERE m.channel_id = $1
+"#,
+            bigint_channel_id
+        )
+        .fetch_all(&mut tx)
+        .await?;
+
+        sqlx::query!("DELETE FROM channels WHERE id = $1", bigint_channel_id)
+            .execute(&mut tx)
+            .await?;
+        tx.commit().await?;
+
+        let mut attachment_ids = Vec::with_capacity(attachments.len());
+        for x in attachments {
+            attachment_ids.push(bigdecimal_to_u128!(x.id));
+        }
+        delete_attachment_files(attachment_ids).await;
+
+        let event = WsOutboundEvent::DmChannelDelete { channel: old };
+        return fire_event(&event).await;
+    }
+
+    if old.owner_id == Some(user_id) {
+        // user IDs are snowflakes, so the smallest one belongs to the oldest account
+        sqlx::query!(
+            r#"
+UPDATE channels
+SET owner_id = (SELECT min(user_id) FROM channel_recipients WHERE channel_id = $1)
+WHERE id = $1
+"#,
+            bigint_channel_id
+        )
+        .execute(&mut tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    let new = load_dm_channel(db, old.id)
+        .await?
+        .ok_or_else(|| ErrorJson::new_404(format!("Unknown channel with ID {}", old.id)))?;
+
+    let event = WsOutboundEvent::DmChannelUpdate { old, new };
+    fire_event(&event).await
+}
@@ -1,33 +1,127 @@
+use super::dm_channels::check_dm_recipient;
+use super::MAX_SLOWMODE;
+use crate::guilds::member_has_permissions;
+use crate::messages::validate_message_ttl;
 use crate::ws::fire_event;
 use crate::{Json, WebServerError};
 use axum::extract::Path;
+use ferrischat_common::perms::Permissions;
 use ferrischat_common::request_json::ChannelUpdateJson;
-use ferrischat_common::types::{Channel, ErrorJson};
+use ferrischat_common::types::{Channel, ChannelType, ErrorJson};
 use ferrischat_common::ws::WsOutboundEvent;
 
 /// PATCH `/v0/channels/{channel_id}`
 pub async fn edit_channel(
     Path(channel_id): Path<u128>,
     channel_info: axum::extract::Json<ChannelUpdateJson>,
-    _: crate::Authorization,
+    auth: crate::Authorization,
 ) -> Result<Json<Channel>, WebServerError> {
     let bigint_channel_id = u128_to_bigdecimal!(channel_id);
     let db = get_db_or_fail!();
-    let ChannelUpdateJson { name } = channel_info.0;
+    let ChannelUpdateJson {
+        name,
+        topic,
+        slowmode,
+        nsfw,
+        announcement,
+        message_ttl,
+    } = channel_info.0;
+
+    if topic.as_ref().map_or(false, |t| t.chars().count() > 1024) {
+        return Err(
+            ErrorJson::new_400("topic must be at most 1024 characters long".to_string()).into(),
+        );
+    }
+    if slowmode.map_or(false, |s| !(0..=MAX_SLOWMODE).contains(&s)) {
+        return Err(ErrorJson::new_400(format!(
+            "slowmode must be between 0 and {} seconds",
+            MAX_SLOWMODE
+        ))
+        .into());
+    }
+    if let Some(message_ttl) = message_ttl {
+        validate_message_ttl(message_ttl).map_err(ErrorJson::new_400)?;
+    }
 
     let c = sqlx::query!("SELECT * FROM channels WHERE id = $1", bigint_channel_id)
         .fetch_optional(db)
         .await?
         .ok_or_else(|| ErrorJson::new_404(format!("Unknown channel with ID {}", channel_id)))?;
+
+    let has_settings = topic.is_some()
+        || slowmode.is_some()
+        || nsfw.is_some()
+        || announcement.is_some()
+        || message_ttl.is_some();
+    match c.guild_id {
+        Some(ref guild_id) => {
+            if has_settings
+                && !member_has_permissions(
+                    db,
+                    guild_id,
+                    &u128_to_bigdecimal!(auth.0),
+                    Permissions::MANAGE_CHANNELS,
+                )
+                .await?
+            {
+                return Err(ErrorJson::new_403(
+                    "you need the manage channels permission to change channel settings"
+                        .to_string(),
+                )
+                .into());
+            }
+        }
+        // any recipient can rename a group DM, but that's all that can be changed about DMs
+        None => {
+            check_dm_recipient(db, channel_id, auth.0).await?;
+            if has_settings || c.channel_type != ChannelType::GroupDm as i16 {
+                return Err(ErrorJson::new_400(
+                    "only group DMs can be edited, and only their name".to_string(),
+                )
+                .into());
+            }
+        }
+    }
+
     let old = Channel {
         id: channel_id,
         name: c.name,
-        guild_id: bigdecimal_to_u128!(c.guild_id),
+        guild_id: match c.guild_id {
+            Some(guild_id) => Some(bigdecimal_to_u128!(guild_id)),
+            None => None,
+        },
+        channel_type: ChannelType::from_i16(c.channel_type).unwrap_or(ChannelType::Text),
+        parent_id: match c.parent_id {
+            Some(parent_id) => Some(bigdecimal_to_u128!(parent_id)),
+            None => None,
+        },
+        position: c.position,
+        topic: c.topic,
+        slowmode: c.slowmode,
+        nsfw: c.nsfw,
+        announcement: c.announcement,
+        message_ttl: c.message_ttl,
     };
 
+    // an empty topic removes it, and a message TTL of 0 turns expiry off
     let new_obj = sqlx::query!(
-        "UPDATE channels SET name = $1 WHERE id= $2 RETURNING *",
+        r#"
+UPDATE channels
+SET name         = coalesce($1, name),
+    topic        = CASE WHEN $2::TEXT IS NULL THEN topic ELSE NULLIF($2, '') END,
+    slowmode     = coalesce($3, slowmode),
+    nsfw         = coalesce($4, nsfw),
+    announcement = coalesce($5, announcement),
+    message_ttl  = CASE WHEN $6::INTEGER IS NULL THEN message_ttl ELSE NULLIF($6, 0) END
+WHERE id = $7
+RETURNING *
+"#,
         name,
+        topic,
+        slowmode,
+        nsfw,
+        announcement,
+        message_ttl,
         bigint_channel_id
     )
     .fetch_optional(db)
@@ -36,7 +130,21 @@ pub async fn edit_channel(
     let new = Channel {
         id: channel_id,
         name: new_obj.name,
-        guild_id: bigdecimal_to_u128!(new_obj.guild_id),
+        guild_id: match new_obj.guild_id {
+            Some(guild_id) => Some(bigdecimal_to_u128!(guild_id)),
+            None => None,
+        },
+        channel_type: ChannelType::from_i16(new_obj.channel_type).unwrap_or(ChannelType::Text),
+        parent_id: match new_obj.parent_id {
+            Some(parent_id) => Some(bigdecimal_to_u128!(parent_id)),
+            None => None,
+        },
+        position: new_obj.position,
+        topic: new_obj.topic,
+        slowmode: new_obj.slowmode,
+        nsfw: new_obj.nsfw,
+        announcement: new_obj.announcement,
+        message_ttl: new_obj.message_ttl,
     };
 
     let event = WsOutboundEvent::ChannelUpdate {
@@ -0,0 +1,143 @@
+use crate::ws::fire_event;
+use crate::WebServerError;
+use axum::extract::{Json, Path};
+use ferrischat_common::request_json::ChannelPositionJson;
+use ferrischat_common::types::{Channel, ChannelType, ErrorJson};
+use ferrischat_common::ws::WsOutboundEvent;
+use std::collections::{HashMap, HashSet};
+
+/// PATCH `/v0/guilds/{guild_id}/channels`
+///
+/// Moves any number of channels at once. Every entry sets both the position and the parent
+/// category of a channel, so leaving `parent_id` out moves the channel out of its category.
+/// Either every channel is moved or none are.
+pub async fn edit_guild_channels(
+    Path(guild_id): Path<u128>,
+    Json(positions): Json<Vec<ChannelPositionJson>>,
+    auth: crate::Authorization,
+) -> Result<crate::Json<Vec<Channel>>, WebServerError> {
+    let db = get_db_or_fail!();
+    let bigint_guild_id = u128_to_bigdecimal!(guild_id);
+
+    let owner_id = sqlx::query!("SELECT owner_id FROM guilds WHERE id = $1", bigint_guild_id)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ErrorJson::new_404(format!("Unknown guild with ID {}", guild_id)))?
+        .owner_id;
+    if bigdecimal_to_u128!(owner_id) != auth.0 {
+        return Err(
+            ErrorJson::new_403("only the guild owner can move channels".to_string()).into(),
+        );
+    }
+
+    let mut seen = HashSet::with_capacity(positions.len());
+    for ChannelPositionJson { id, position, .. } in &positions {
+        if !seen.insert(*id) {
+            return Err(
+                ErrorJson::new_400(format!("channel {} was given more than once", id)).into(),
+            );
+        }
+        if *position < 0 {
+            return Err(ErrorJson::new_400("positions must be >= 0".to_string()).into());
+        }
+    }
+
+    let mut tx = db.begin().await?;
+
+    let resp = sqlx::query!(
+        "SELECT * FROM channels WHERE guild_id = $1 AND channel_type <> 4 FOR UPDATE",
+        bigint_guild_id
+    )
+    .fetch_all(&mut tx)
+    .await?;
+
+    let mut channels = HashMap::with_capacity(resp.len());
+    for x in resp {
+        let id = bigdecimal_to_u128!(x.id);
+        channels.insert(
+            id,
+            Channel {
+                id,
+                name: x.name,
+                guild_id: Some(guild_id),
+                channel_type: ChannelType::from_i16(x.channel_type).unwrap_or(ChannelType::Text),
+                parent_id: match x.parent_id {
+                    Some(parent_id) => Some(bigdecimal_to_u128!(parent_id)),
+                    None => None,
+                },
+                position: x.position,
+                topic: x.topic,
+                slowmode: x.slowmode,
+                nsfw: x.nsfw,
+                announcement: x.announcement,
+                message_ttl: x.message_ttl,
+            },
+        );
+    }
+
+    let mut moved = Vec::new();
+    for ChannelPositionJson {
+        id,
+        position,
+        parent_id,
+    } in positions
+    {
+        let old = channels
+            .get(&id)
+            .ok_or_else(|| ErrorJson::new_400(format!("channel {} is not in this guild", id)))?;
+
+        if let Some(parent_id) = parent_id {
+            if old.channel_type == ChannelType::Category {
+                return Err(ErrorJson::new_400(
+                    "categories can't be placed in other categories".to_string(),
+                )
+                .into());
+            }
+            if channels.get(&parent_id).map(|c| c.channel_type) != Some(ChannelType::Category) {
+                return Err(ErrorJson::new_400(format!(
+                    "channel {} is not a category in this guild",
+                    parent_id
+                ))
+                .into());
+            }
+        }
+
+        if old.position == position && old.parent_id == parent_id {
+            continue;
+        }
+
+        sqlx::query!(
+            "UPDATE channels SET position = $1, parent_id = $2 WHERE id = $3",
+            position,
+            parent_id.map(|parent_id| u128_to_bigdecimal!(parent_id)),
+            u128_to_bigdecimal!(id)
+        )
+        .execute(&mut tx)
+        .await?;
+
+        let old = old.clone();
+        let new = Channel {
+            position,
+            parent_id,
+            ..old.clone()
+        };
+        moved.push((old, new));
+    }
+
+    tx.commit().await?;
+
+    for (old, new) in moved {
+        channels.insert(new.id, new.clone());
+
+        let event = WsOutboundEvent::ChannelUpdate { old, new };
+        fire_event(&event).await?;
+    }
+
+    let mut channels = channels.into_values().collect::<Vec<_>>();
+    channels.sort_unstable_by_key(|c| (c.position, c.id));
+
+    Ok(crate::Json {
+        obj: channels,
+        code: 200,
+    })
+}
@@ -0,0 +1,103 @@
+use crate::guilds::member_has_permissions;
+use crate::WebServerError;
+use axum::extract::{Json, Path};
+use ferrischat_common::perms::Permissions;
+use ferrischat_common::request_json::ChannelFollowJson;
+use ferrischat_common::types::{ChannelFollower, ChannelType, ErrorJson};
+
+/// POST `/v0/channels/{channel_id}/followers`
+///
+/// Makes a text channel follow an announcement channel, so messages crossposted from it get copied there.
+/// Following needs access to the announcement channel's guild,
+/// and the manage channels permission in the target channel's guild.
+pub async fn follow_channel(
+    Path(channel_id): Path<u128>,
+    auth: crate::Authorization,
+    Json(ChannelFollowJson { target_channel_id }): Json<ChannelFollowJson>,
+) -> Result<crate::Json<ChannelFollower>, WebServerError> {
+    let db = get_db_or_fail!();
+    let bigint_channel_id = u128_to_bigdecimal!(channel_id);
+    let bigint_target_channel_id = u128_to_bigdecimal!(target_channel_id);
+    let bigint_user_id = u128_to_bigdecimal!(auth.0);
+
+    if channel_id == target_channel_id {
+        return Err(ErrorJson::new_400("channels can't follow themselves".to_string()).into());
+    }
+
+    let source = sqlx::query!(
+        "SELECT guild_id, announcement FROM channels WHERE id = $1",
+        bigint_channel_id
+    )
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| ErrorJson::new_404(format!("Unknown channel with ID {}", channel_id)))?;
+    let source_guild_id = match source.guild_id {
+        Some(guild_id) if source.announcement => guild_id,
+        _ => {
+            return Err(ErrorJson::new_400(
+                "only announcement channels can be followed".to_string(),
+            )
+            .into())
+        }
+    };
+    if !sqlx::query!(
+        r#"SELECT EXISTS(SELECT * FROM members WHERE guild_id = $1 AND user_id = $2) AS "exists!""#,
+        source_guild_id,
+        bigint_user_id
+    )
+    .fetch_one(db)
+    .await?
+    .exists
+    {
+        return Err(ErrorJson::new_404(format!("Unknown channel with ID {}", channel_id)).into());
+    }
+
+    let target = sqlx::query!(
+        "SELECT guild_id, channel_type FROM channels WHERE id = $1",
+        bigint_target_channel_id
+    )
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| ErrorJson::new_404(format!("Unknown channel with ID {}", target_channel_id)))?;
+    let target_guild_id = match (target.guild_id, target.channel_type) {
+        (Some(guild_id), t) if t == ChannelType::Text as i16 => guild_id,
+        _ => {
+            return Err(ErrorJson::new_400(
+                "only guild text channels can follow announcement channels".to_string(),
+            )
+            .into())
+        }
+    };
+    if !member_has_permissions(
+        db,
+        &target_guild_id,
+        &bigint_user_id,
+        Permissions::MANAGE_CHANNELS,
+    )
+    .await?
+    {
+        return Err(ErrorJson::new_403(
+            "you need the manage channels permission in the target channel's guild".to_string(),
+        )
+        .into());
+    }
+
+    let created = sqlx::query!(
+        "INSERT INTO channel_followers VALUES ($1, $2, $3) ON CONFLICT DO NOTHING RETURNING source_channel_id",
+        bigint_channel_id,
+        bigint_target_channel_id,
+        bigint_user_id
+    )
+    .fetch_optional(db)
+    .await?
+    .is_some();
+
+    Ok(crate::Json {
+        obj: ChannelFollower {
+            source_channel_id: channel_id,
+            target_channel_id,
+            target_guild_id: bigdecimal_to_u128!(target_guild_id),
+        },
+        code: if created { 201 } else { 200 },
+    })
+}
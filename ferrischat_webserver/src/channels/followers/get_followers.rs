@@ -0,0 +1,67 @@
+use crate::guilds::member_has_permissions;
+use crate::WebServerError;
+use axum::extract::Path;
+use ferrischat_common::perms::Permissions;
+use ferrischat_common::types::{ChannelFollower, ErrorJson};
+
+/// GET `/v0/channels/{channel_id}/followers`
+///
+/// Lists the channels following an announcement channel.
+/// This needs the manage channels permission in the announcement channel's guild.
+pub async fn get_followers(
+    Path(channel_id): Path<u128>,
+    auth: crate::Authorization,
+) -> Result<crate::Json<Vec<ChannelFollower>>, WebServerError> {
+    let db = get_db_or_fail!();
+    let bigint_channel_id = u128_to_bigdecimal!(channel_id);
+
+    let guild_id = sqlx::query!(
+        "SELECT guild_id FROM channels WHERE id = $1",
+        bigint_channel_id
+    )
+    .fetch_optional(db)
+    .await?
+    .and_then(|c| c.guild_id)
+    .ok_or_else(|| ErrorJson::new_404(format!("Unknown channel with ID {}", channel_id)))?;
+
+    if !member_has_permissions(
+        db,
+        &guild_id,
+        &u128_to_bigdecimal!(auth.0),
+        Permissions::MANAGE_CHANNELS,
+    )
+    .await?
+    {
+        return Err(ErrorJson::new_403(
+            "you need the manage channels permission to see who follows this channel".to_string(),
+        )
+        .into());
+    }
+
+    let resp = sqlx::query!(
+        r#"
+SELECT f.target_channel_id,
+       c.guild_id AS "guild_id!"
+FROM channel_followers f
+    INNER JOIN channels c ON c.id = f.target_channel_id
+WHERE f.source_channel_id = $1
+"#,
+        bigint_channel_id
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut followers = Vec::with_capacity(resp.len());
+    for x in resp {
+        followers.push(ChannelFollower {
+            source_channel_id: channel_id,
+            target_channel_id: bigdecimal_to_u128!(x.target_channel_id),
+            target_guild_id: bigdecimal_to_u128!(x.guild_id),
+        });
+    }
+
+    Ok(crate::Json {
+        obj: followers,
+        code: 200,
+    })
+}
@@ -0,0 +1,26 @@
+mod follow_channel;
+mod get_followers;
+mod unfollow_channel;
+
+pub use follow_channel::*;
+pub use get_followers::*;
+pub use unfollow_channel::*;
+
+use axum::routing::{delete, post};
+use axum::Router;
+
+pub fn generate_followers_routes() -> axum::Router {
+    debug!("generating routes for channel followers");
+    Router::new()
+        // POST   /channels/:channel_id/followers
+        // GET    /channels/:channel_id/followers
+        .route(
+            expand_version!("channels/:channel_id/followers"),
+            post(follow_channel).get(get_followers),
+        )
+        // DELETE /channels/:channel_id/followers/:target_channel_id
+        .route(
+            expand_version!("channels/:channel_id/followers/:target_channel_id"),
+            delete(unfollow_channel),
+        )
+}
@@ -0,0 +1,73 @@
+use crate::guilds::member_has_permissions;
+use crate::WebServerError;
+use axum::extract::Path;
+use ferrischat_common::perms::Permissions;
+use ferrischat_common::types::ErrorJson;
+
+/// DELETE `/v0/channels/{channel_id}/followers/{target_channel_id}`
+///
+/// Either side can end a follow: this needs the manage channels permission
+/// in the guild of the announcement channel or of the following channel.
+pub async fn unfollow_channel(
+    Path((channel_id, target_channel_id)): Path<(u128, u128)>,
+    auth: crate::Authorization,
+) -> Result<http::StatusCode, WebServerError> {
+    let db = get_db_or_fail!();
+    let bigint_channel_id = u128_to_bigdecimal!(channel_id);
+    let bigint_target_channel_id = u128_to_bigdecimal!(target_channel_id);
+    let bigint_user_id = u128_to_bigdecimal!(auth.0);
+
+    let follow = sqlx::query!(
+        r#"
+SELECT s.guild_id AS "source_guild_id!",
+       t.guild_id AS "target_guild_id!"
+FROM channel_followers f
+    INNER JOIN channels s ON s.id = f.source_channel_id
+    INNER JOIN channels t ON t.id = f.target_channel_id
+WHERE f.source_channel_id = $1
+  AND f.target_channel_id = $2
+"#,
+        bigint_channel_id,
+        bigint_target_channel_id
+    )
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| {
+        ErrorJson::new_404(format!(
+            "channel {} doesn't follow {}",
+            target_channel_id, channel_id
+        ))
+    })?;
+
+    if !member_has_permissions(
+        db,
+        &follow.source_guild_id,
+        &bigint_user_id,
+        Permissions::MANAGE_CHANNELS,
+    )
+    .await?
+        && !member_has_permissions(
+            db,
+            &follow.target_guild_id,
+            &bigint_user_id,
+            Permissions::MANAGE_CHANNELS,
+        )
+        .await?
+    {
+        return Err(ErrorJson::new_403(
+            "you need the manage channels permission in either guild to remove this follow"
+                .to_string(),
+        )
+        .into());
+    }
+
+    sqlx::query!(
+        "DELETE FROM channel_followers WHERE source_channel_id = $1 AND target_channel_id = $2",
+        bigint_channel_id,
+        bigint_target_channel_id
+    )
+    .execute(db)
+    .await?;
+
+    Ok(http::StatusCode::NO_CONTENT)
+}
@@ -1,24 +1,46 @@
+use super::dm_channels::check_dm_recipient;
 use crate::WebServerError;
 use axum::extract::Path;
-use ferrischat_common::types::{Channel, ErrorJson};
+use ferrischat_common::types::{Channel, ChannelType, ErrorJson};
 
 /// GET `/v0/guilds/{guild_id/channels/{channel_id}`
 pub async fn get_channel(
     Path(channel_id): Path<u128>,
-    _: crate::Authorization,
+    auth: crate::Authorization,
 ) -> Result<crate::Json<Channel>, WebServerError> {
+    let db = get_db_or_fail!();
     let c = sqlx::query!(
         "SELECT * FROM channels WHERE id = $1",
         u128_to_bigdecimal!(channel_id)
     )
-    .fetch_optional(get_db_or_fail!())
+    .fetch_optional(db)
     .await?
     .ok_or_else(|| ErrorJson::new_404(format!("Unknown channel with ID {}", channel_id)))?;
+
+    // only recipients can see DMs
+    if c.guild_id.is_none() {
+        check_dm_recipient(db, channel_id, auth.0).await?;
+    }
+
     Ok(crate::Json {
         obj: Channel {
             id: channel_id,
             name: c.name,
-            guild_id: bigdecimal_to_u128!(c.guild_id),
+            guild_id: match c.guild_id {
+                Some(guild_id) => Some(bigdecimal_to_u128!(guild_id)),
+                None => None,
+            },
+            channel_type: ChannelType::from_i16(c.channel_type).unwrap_or(ChannelType::Text),
+            parent_id: match c.parent_id {
+                Some(parent_id) => Some(bigdecimal_to_u128!(parent_id)),
+                None => None,
+            },
+            position: c.position,
+            topic: c.topic,
+            slowmode: c.slowmode,
+            nsfw: c.nsfw,
+            announcement: c.announcement,
+            message_ttl: c.message_ttl,
         },
         code: 200,
     })
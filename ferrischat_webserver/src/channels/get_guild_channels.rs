@@ -0,0 +1,58 @@
+use crate::WebServerError;
+use axum::extract::Path;
+use ferrischat_common::types::{Channel, ChannelType, ErrorJson};
+
+/// GET `/v0/guilds/{guild_id}/channels`
+///
+/// Channels are sorted by their position.
+/// Threads aren't included: they're listed per channel, at `/v0/channels/{channel_id}/threads`.
+pub async fn get_guild_channels(
+    Path(guild_id): Path<u128>,
+    _: crate::Authorization,
+) -> Result<crate::Json<Vec<Channel>>, WebServerError> {
+    let db = get_db_or_fail!();
+    let bigint_guild_id = u128_to_bigdecimal!(guild_id);
+
+    if !sqlx::query!(
+        r#"SELECT EXISTS(SELECT * FROM guilds WHERE id = $1) AS "exists!""#,
+        bigint_guild_id
+    )
+    .fetch_one(db)
+    .await?
+    .exists
+    {
+        return Err(ErrorJson::new_404(format!("Unknown guild with ID {}", guild_id)).into());
+    }
+
+    let resp = sqlx::query!(
+        "SELECT * FROM channels WHERE guild_id = $1 AND channel_type <> 4 ORDER BY position, id",
+        bigint_guild_id
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut channels = Vec::with_capacity(resp.len());
+    for x in resp {
+        channels.push(Channel {
+            id: bigdecimal_to_u128!(x.id),
+            name: x.name,
+            guild_id: Some(guild_id),
+            channel_type: ChannelType::from_i16(x.channel_type).unwrap_or(ChannelType::Text),
+            parent_id: match x.parent_id {
+                Some(parent_id) => Some(bigdecimal_to_u128!(parent_id)),
+                None => None,
+            },
+            position: x.position,
+            topic: x.topic,
+            slowmode: x.slowmode,
+            nsfw: x.nsfw,
+            announcement: x.announcement,
+            message_ttl: x.message_ttl,
+        });
+    }
+
+    Ok(crate::Json {
+        obj: channels,
+        code: 200,
+    })
+}
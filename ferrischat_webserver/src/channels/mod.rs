@@ -1,14 +1,23 @@
 mod create_channel;
 mod delete_channel;
+pub mod dm_channels;
 mod edit_channel;
+mod edit_guild_channels;
+pub mod followers;
 mod get_channel;
+mod get_guild_channels;
+pub mod pins;
+mod slowmode;
 mod typing_end;
 mod typing_start;
 
 pub use create_channel::*;
 pub use delete_channel::*;
 pub use edit_channel::*;
+pub use edit_guild_channels::*;
 pub use get_channel::*;
+pub use get_guild_channels::*;
+pub use slowmode::*;
 pub use typing_end::*;
 pub use typing_start::*;
 
@@ -19,9 +28,13 @@ pub fn generate_channels_routes() -> axum::Router {
     debug!("generating routes for channels");
     Router::new()
         // POST   /guilds/:guild_id/channels
+        // GET    /guilds/:guild_id/channels
+        // PATCH  /guilds/:guild_id/channels
         .route(
             expand_version!("guilds/:guild_id/channels"),
-            post(create_channel),
+            post(create_channel)
+                .get(get_guild_channels)
+                .patch(edit_guild_channels),
         )
         // GET    /channels/:channel_id
         // PATCH  /channels/:channel_id
@@ -36,4 +49,10 @@ pub fn generate_channels_routes() -> axum::Router {
             expand_version!("channels/:channel_id/typing"),
             post(typing_start).delete(typing_end),
         )
+        // DM channels routes
+        .merge(dm_channels::generate_dm_channels_routes())
+        // followers routes
+        .merge(followers::generate_followers_routes())
+        // pins routes
+        .merge(pins::generate_pins_routes())
 }
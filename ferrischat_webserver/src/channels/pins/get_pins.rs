@@ -0,0 +1,108 @@
+use super::load_pins_channel;
+use crate::messages::attachments::load_attachments;
+use crate::messages::reactions::load_reactions;
+use crate::messages::{
+    build_message_reference, build_reply, load_embeds, load_reply_targets, mention_ids_from_row,
+};
+use crate::WebServerError;
+use axum::extract::Path;
+use ferrischat_common::types::{Message, User, UserFlags};
+
+/// GET `/v0/channels/{channel_id}/pins`
+///
+/// The most recently pinned messages come first.
+pub async fn get_pins(
+    Path(channel_id): Path<u128>,
+    auth: crate::Authorization,
+) -> Result<crate::Json<Vec<Message>>, WebServerError> {
+    let db = get_db_or_fail!();
+
+    let channel = load_pins_channel(db, channel_id, auth.0, false).await?;
+
+    let resp = sqlx::query!(
+        r#"
+SELECT m.*,
+       a.name AS author_name,
+       a.avatar AS avatar,
+       a.flags AS author_flags,
+       a.discriminator AS author_discriminator,
+       a.pronouns AS author_pronouns
+FROM pins p
+    INNER JOIN messages m ON m.id = p.message_id
+    CROSS JOIN LATERAL (
+        SELECT *
+        FROM users
+        WHERE id = m.author_id
+        ) AS a
+WHERE p.channel_id = $1
+  AND (m.expires_at IS NULL OR m.expires_at > extract(epoch FROM now()))
+ORDER BY p.pinned_at DESC
+"#,
+        u128_to_bigdecimal!(channel_id)
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut ids = Vec::with_capacity(resp.len());
+    let mut reply_ids = Vec::new();
+    for x in &resp {
+        ids.push(bigdecimal_to_u128!(x.id));
+        if let Some(ref reply_to_id) = x.reply_to_id {
+            reply_ids.push(bigdecimal_to_u128!(reply_to_id));
+        }
+    }
+    let mut reactions = load_reactions(db, &ids, auth.0).await?;
+    let mut attachments = load_attachments(db, &ids).await?;
+    let reply_targets = load_reply_targets(db, &reply_ids).await?;
+
+    let mut messages = Vec::with_capacity(resp.len());
+    for (x, id) in resp.into_iter().zip(ids) {
+        let author_id = bigdecimal_to_u128!(x.author_id);
+        messages.push(Message {
+            id,
+            content: x.content,
+            channel: channel.clone(),
+            channel_id,
+            author_id,
+            author: Some(User {
+                id: author_id,
+                name: x.author_name,
+                avatar: x.avatar,
+                guilds: None,
+                flags: UserFlags::from_bits_truncate(x.author_flags),
+                discriminator: x.author_discriminator,
+                pronouns: x
+                    .author_pronouns
+                    .and_then(ferrischat_common::types::Pronouns::from_i16),
+            }),
+            edited_at: x.edited_at,
+            expires_at: x.expires_at,
+            embeds: load_embeds(x.embeds)?,
+            mentions: mention_ids_from_row(x.mentions)?,
+            mention_roles: mention_ids_from_row(x.mention_roles)?,
+            mention_channels: mention_ids_from_row(x.mention_channels)?,
+            mention_everyone: x.mention_everyone,
+            reactions: reactions.remove(&id).unwrap_or_default(),
+            attachments: attachments.remove(&id).unwrap_or_default(),
+            crossposted_from: build_message_reference(
+                x.crosspost_guild_id,
+                x.crosspost_channel_id,
+                x.crosspost_message_id,
+            )?,
+            reply_to: match x.reply_to_id {
+                Some(reply_to_id) => Some(build_reply(
+                    &reply_targets,
+                    bigdecimal_to_u128!(reply_to_id),
+                    x.reply_mention_author,
+                )),
+                None => None,
+            },
+            nonce: None,
+        });
+    }
+
+    Ok(crate::Json {
+        obj: messages,
+        code: 200,
+    })
+}
@@ -0,0 +1,107 @@
+use crate::channels::dm_channels::check_dm_recipient;
+use crate::guilds::member_has_permissions;
+use crate::ws::fire_event;
+use crate::WebServerError;
+use ferrischat_common::perms::Permissions;
+use ferrischat_common::types::{Channel, ChannelType, ErrorJson};
+use ferrischat_common::ws::WsOutboundEvent;
+
+/// Maximum number of pins per channel, as set in the config.
+pub fn max_pins() -> usize {
+    ferrischat_config::GLOBAL_CONFIG
+        .get()
+        .map_or(50, |cfg| cfg.messages.max_pins)
+}
+
+/// Fetches a channel, checking the user can see it.
+///
+/// If `manage` is set, the user must also be allowed to pin and unpin messages in it:
+/// that needs the manage messages permission in guilds, while any recipient can manage pins in DMs.
+pub async fn load_pins_channel(
+    db: &sqlx::PgPool,
+    channel_id: u128,
+    user_id: u128,
+    manage: bool,
+) -> Result<Channel, WebServerError> {
+    let channel = sqlx::query!(
+        "SELECT * FROM channels WHERE id = $1",
+        u128_to_bigdecimal!(channel_id)
+    )
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| ErrorJson::new_404(format!("Unknown channel with ID {}", channel_id)))?;
+
+    match channel.guild_id {
+        Some(ref guild_id) => {
+            let bigint_user_id = u128_to_bigdecimal!(user_id);
+            if !sqlx::query!(
+                r#"SELECT EXISTS(SELECT * FROM members WHERE guild_id = $1 AND user_id = $2) AS "exists!""#,
+                guild_id,
+                bigint_user_id
+            )
+            .fetch_one(db)
+            .await?
+            .exists
+            {
+                return Err(ErrorJson::new_404(format!(
+                    "Unknown channel with ID {}",
+                    channel_id
+                ))
+                .into());
+            }
+            if manage
+                && !member_has_permissions(
+                    db,
+                    guild_id,
+                    &bigint_user_id,
+                    Permissions::MANAGE_MESSAGES,
+                )
+                .await?
+            {
+                return Err(ErrorJson::new_403(
+                    "you need the manage messages permission to pin messages".to_string(),
+                )
+                .into());
+            }
+        }
+        None => check_dm_recipient(db, channel_id, user_id).await?,
+    }
+
+    Ok(Channel {
+        id: channel_id,
+        name: channel.name,
+        guild_id: match channel.guild_id {
+            Some(guild_id) => Some(bigdecimal_to_u128!(guild_id)),
+            None => None,
+        },
+        channel_type: ChannelType::from_i16(channel.channel_type).unwrap_or(ChannelType::Text),
+        parent_id: match channel.parent_id {
+            Some(parent_id) => Some(bigdecimal_to_u128!(parent_id)),
+            None => None,
+        },
+        position: channel.position,
+        topic: channel.topic,
+        slowmode: channel.slowmode,
+        nsfw: channel.nsfw,
+        announcement: channel.announcement,
+        message_ttl: channel.message_ttl,
+    })
+}
+
+/// Fires `ChannelPinsUpdate` for a channel whose pins just changed.
+pub async fn fire_pins_update(db: &sqlx::PgPool, channel: Channel) -> Result<(), WebServerError> {
+    let last_pin_timestamp = sqlx::query!(
+        "SELECT max(pinned_at) AS last_pin_timestamp FROM pins WHERE channel_id = $1",
+        u128_to_bigdecimal!(channel.id)
+    )
+    .fetch_one(db)
+    .await?
+    .last_pin_timestamp;
+
+    let event = WsOutboundEvent::ChannelPinsUpdate {
+        channel,
+        last_pin_timestamp,
+    };
+
+    fire_event(&event).await
+}
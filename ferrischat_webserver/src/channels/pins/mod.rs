@@ -0,0 +1,25 @@
+mod get_pins;
+mod lookup;
+mod pin_message;
+mod unpin_message;
+
+pub use get_pins::*;
+pub use lookup::*;
+pub use pin_message::*;
+pub use unpin_message::*;
+
+use axum::routing::{get, put};
+use axum::Router;
+
+pub fn generate_pins_routes() -> axum::Router {
+    debug!("generating routes for pins");
+    Router::new()
+        // GET    /channels/:channel_id/pins
+        .route(expand_version!("channels/:channel_id/pins"), get(get_pins))
+        // PUT    /channels/:channel_id/pins/:message_id
+        // DELETE /channels/:channel_id/pins/:message_id
+        .route(
+            expand_version!("channels/:channel_id/pins/:message_id"),
+            put(pin_message).delete(unpin_message),
+        )
+}
@@ -0,0 +1,69 @@
+use super::{fire_pins_update, load_pins_channel, max_pins};
+use crate::WebServerError;
+use axum::extract::Path;
+use ferrischat_common::types::ErrorJson;
+
+/// PUT `/v0/channels/{channel_id}/pins/{message_id}`
+///
+/// Pinning a message that's already pinned does nothing.
+pub async fn pin_message(
+    Path((channel_id, message_id)): Path<(u128, u128)>,
+    auth: crate::Authorization,
+) -> Result<http::StatusCode, WebServerError> {
+    let db = get_db_or_fail!();
+    let bigint_channel_id = u128_to_bigdecimal!(channel_id);
+    let bigint_message_id = u128_to_bigdecimal!(message_id);
+
+    let channel = load_pins_channel(db, channel_id, auth.0, true).await?;
+
+    let mut tx = db.begin().await?;
+
+    // locking the channel keeps concurrent pins from going over the limit together
+    sqlx::query!(
+        "SELECT id FROM channels WHERE id = $1 FOR UPDATE",
+        bigint_channel_id
+    )
+    .fetch_one(&mut tx)
+    .await?;
+
+    let message = sqlx::query!(
+        r#"
+SELECT EXISTS(SELECT * FROM pins WHERE message_id = m.id) AS "pinned!",
+       (SELECT count(*) FROM pins WHERE channel_id = m.channel_id) AS "pin_count!"
+FROM messages m
+WHERE m.id = $1
+  AND m.channel_id = $2
+"#,
+        bigint_message_id,
+        bigint_channel_id
+    )
+    .fetch_optional(&mut tx)
+    .await?
+    .ok_or_else(|| ErrorJson::new_404(format!("Unknown message with ID {}", message_id)))?;
+
+    if message.pinned {
+        return Ok(http::StatusCode::NO_CONTENT);
+    }
+    if message.pin_count >= i64::try_from(max_pins()).unwrap_or(i64::MAX) {
+        return Err(ErrorJson::new_400(format!(
+            "channels can have at most {} pinned messages",
+            max_pins()
+        ))
+        .into());
+    }
+
+    sqlx::query!(
+        "INSERT INTO pins (message_id, channel_id, pinned_by) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+        bigint_message_id,
+        bigint_channel_id,
+        u128_to_bigdecimal!(auth.0)
+    )
+    .execute(&mut tx)
+    .await?;
+
+    tx.commit().await?;
+
+    fire_pins_update(db, channel).await?;
+
+    Ok(http::StatusCode::NO_CONTENT)
+}
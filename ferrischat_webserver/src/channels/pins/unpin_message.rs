@@ -0,0 +1,34 @@
+use super::{fire_pins_update, load_pins_channel};
+use crate::WebServerError;
+use axum::extract::Path;
+use ferrischat_common::types::ErrorJson;
+
+/// DELETE `/v0/channels/{channel_id}/pins/{message_id}`
+pub async fn unpin_message(
+    Path((channel_id, message_id)): Path<(u128, u128)>,
+    auth: crate::Authorization,
+) -> Result<http::StatusCode, WebServerError> {
+    let db = get_db_or_fail!();
+
+    let channel = load_pins_channel(db, channel_id, auth.0, true).await?;
+
+    if sqlx::query!(
+        "DELETE FROM pins WHERE message_id = $1 AND channel_id = $2 RETURNING message_id",
+        u128_to_bigdecimal!(message_id),
+        u128_to_bigdecimal!(channel_id)
+    )
+    .fetch_optional(db)
+    .await?
+    .is_none()
+    {
+        return Err(ErrorJson::new_404(format!(
+            "message {} is not pinned in {}",
+            message_id, channel_id
+        ))
+        .into());
+    }
+
+    fire_pins_update(db, channel).await?;
+
+    Ok(http::StatusCode::NO_CONTENT)
+}
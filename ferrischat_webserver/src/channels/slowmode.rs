@@ -0,0 +1,44 @@
+use crate::WebServerError;
+use ferrischat_redis::REDIS_MANAGER;
+
+/// Longest slowmode a channel can have, in seconds: 6 hours.
+pub const MAX_SLOWMODE: i32 = 21600;
+
+/// Starts a user's slowmode cooldown in a channel.
+///
+/// If the user is still cooling down from their last message,
+/// this returns [`WebServerError::RateLimited`] with the time left instead.
+pub async fn check_slowmode(
+    channel_id: u128,
+    user_id: u128,
+    slowmode: i32,
+) -> Result<(), WebServerError> {
+    let mut redis = REDIS_MANAGER
+        .get()
+        .ok_or(WebServerError::MissingRedis)?
+        .get()
+        .await?;
+
+    let key = format!("slowmode:{}:{}", channel_id, user_id);
+    // SET NX only succeeds if the last cooldown has expired
+    let started = ferrischat_redis::redis::cmd("SET")
+        .arg(&key)
+        .arg(1_u8)
+        .arg("EX")
+        .arg(slowmode)
+        .arg("NX")
+        .query_async::<_, Option<String>>(&mut redis)
+        .await?
+        .is_some();
+    if started {
+        return Ok(());
+    }
+
+    let retry_after = ferrischat_redis::redis::cmd("PTTL")
+        .arg(&key)
+        .query_async::<_, i64>(&mut redis)
+        .await?;
+    Err(WebServerError::RateLimited {
+        retry_after: u64::try_from(retry_after).unwrap_or(0),
+    })
+}
@@ -1,7 +1,8 @@
+use super::dm_channels::check_dm_recipient;
 use crate::ws::fire_event;
 use crate::WebServerError;
 use axum::extract::Path;
-use ferrischat_common::types::{Channel, ErrorJson, Pronouns, User, UserFlags};
+use ferrischat_common::types::{Channel, ChannelType, ErrorJson, Pronouns, User, UserFlags};
 use ferrischat_common::ws::WsOutboundEvent;
 
 /// POST `/v0/channels/{channel_id}/typing`
@@ -23,6 +24,11 @@ pub async fn typing_start(
         .await?
         .ok_or_else(|| ErrorJson::new_404(format!("Unknown channel with ID {}", channel_id)))?;
 
+    // only recipients can see DMs
+    if channel.guild_id.is_none() {
+        check_dm_recipient(db, channel_id, authorized_user).await?;
+    }
+
     let user_obj = User {
         id: authorized_user,
         name: user.name,
@@ -33,11 +39,25 @@ pub async fn typing_start(
         pronouns: user.pronouns.and_then(Pronouns::from_i16),
     };
 
-    let guild_id = bigdecimal_to_u128!(channel.guild_id);
+    let guild_id = match channel.guild_id {
+        Some(guild_id) => Some(bigdecimal_to_u128!(guild_id)),
+        None => None,
+    };
     let channel_obj = Channel {
         id: channel_id,
         name: channel.name,
         guild_id,
+        channel_type: ChannelType::from_i16(channel.channel_type).unwrap_or(ChannelType::Text),
+        parent_id: match channel.parent_id {
+            Some(parent_id) => Some(bigdecimal_to_u128!(parent_id)),
+            None => None,
+        },
+        position: channel.position,
+        topic: channel.topic,
+        slowmode: channel.slowmode,
+        nsfw: channel.nsfw,
+        announcement: channel.announcement,
+        message_ttl: channel.message_ttl,
     };
 
     let event = WsOutboundEvent::TypingStart {
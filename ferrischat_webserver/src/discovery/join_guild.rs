@@ -0,0 +1,97 @@
+use crate::ws::fire_event;
+use crate::WebServerError;
+use axum::extract::Path;
+use ferrischat_common::types::{ErrorJson, Member, User, UserFlags};
+use ferrischat_common::ws::WsOutboundEvent;
+
+/// POST `/v0/discovery/guilds/{guild_id}/join`
+///
+/// Joins a discoverable guild without needing an invite.
+pub async fn join_discoverable_guild(
+    Path(guild_id): Path<u128>,
+    crate::Authorization(user_id): crate::Authorization,
+) -> Result<crate::Json<Member>, WebServerError> {
+    let db = get_db_or_fail!();
+    let bigint_guild_id = u128_to_bigdecimal!(guild_id);
+    let bigint_user_id = u128_to_bigdecimal!(user_id);
+
+    let u = sqlx::query!("SELECT * FROM users WHERE id = $1", bigint_user_id)
+        .fetch_one(db)
+        .await?;
+    let flags = UserFlags::from_bits_truncate(u.flags);
+    if flags.contains(UserFlags::BOT_ACCOUNT) {
+        return Err(ErrorJson::new_401(
+            "Bots cannot join guilds! They must be invited by the guild owner.".to_string(),
+        )
+        .into());
+    }
+
+    // guilds that aren't discoverable are reported as unknown so they can't be probed for
+    if !sqlx::query!(
+        r#"SELECT EXISTS(SELECT * FROM guilds WHERE id = $1 AND discoverable) AS "exists!""#,
+        bigint_guild_id
+    )
+    .fetch_one(db)
+    .await?
+    .exists
+    {
+        return Err(
+            ErrorJson::new_404(format!("Unknown discoverable guild with ID {}", guild_id)).into(),
+        );
+    }
+
+    if sqlx::query!(
+        r#"SELECT EXISTS(SELECT * FROM members WHERE user_id = $1 AND guild_id = $2) AS "exists!""#,
+        bigint_user_id,
+        bigint_guild_id
+    )
+    .fetch_one(db)
+    .await?
+    .exists
+    {
+        return Err(ErrorJson::new_409("user has already joined this guild".to_string()).into());
+    };
+
+    let member = sqlx::query!(
+        r#"
+INSERT INTO members (user_id, guild_id, pending)
+VALUES ($1, $2, (SELECT rules IS NOT NULL FROM guilds WHERE id = $2))
+RETURNING joined_at, pending
+"#,
+        bigint_user_id,
+        bigint_guild_id
+    )
+    .fetch_one(db)
+    .await?;
+
+    let member_obj = Member {
+        user_id: Some(user_id),
+        user: Some(User {
+            id: user_id,
+            name: u.name,
+            avatar: u.avatar,
+            guilds: None,
+            flags,
+            discriminator: u.discriminator,
+            pronouns: u
+                .pronouns
+                .and_then(ferrischat_common::types::Pronouns::from_i16),
+        }),
+        guild_id: Some(guild_id),
+        guild: None,
+        joined_at: Some(member.joined_at),
+        invite_code: None,
+        inviter_id: None,
+        pending: Some(member.pending),
+    };
+
+    let event = WsOutboundEvent::MemberCreate {
+        member: member_obj.clone(),
+    };
+
+    fire_event(&event).await?;
+    Ok(crate::Json {
+        obj: member_obj,
+        code: 201,
+    })
+}
@@ -0,0 +1,22 @@
+mod join_guild;
+mod search_guilds;
+mod settings;
+
+pub use join_guild::*;
+pub use search_guilds::*;
+pub use settings::*;
+
+use axum::routing::{get, post};
+use axum::Router;
+
+pub fn generate_discovery_routes() -> axum::Router {
+    debug!("generating routes for discovery");
+    Router::new()
+        // GET    /discovery/guilds
+        .route(expand_version!("discovery/guilds"), get(search_guilds))
+        // POST   /discovery/guilds/:guild_id/join
+        .route(
+            expand_version!("discovery/guilds/:guild_id/join"),
+            post(join_discoverable_guild),
+        )
+}
@@ -0,0 +1,68 @@
+use crate::WebServerError;
+use axum::extract::Query;
+use ferrischat_common::request_json::DiscoveryUrlParams;
+use ferrischat_common::types::{DiscoverableGuild, ErrorJson};
+
+/// Number of guilds returned per page of discovery results.
+const PAGE_SIZE: i64 = 25;
+
+/// GET `/v0/discovery/guilds`
+///
+/// Does not require authorization.
+pub async fn search_guilds(
+    Query(DiscoveryUrlParams { q, tag, page }): Query<DiscoveryUrlParams>,
+) -> Result<crate::Json<Vec<DiscoverableGuild>>, WebServerError> {
+    let db = get_db_or_fail!();
+
+    let page = page.unwrap_or(0);
+    if page < 0 {
+        return Err(ErrorJson::new_400("page must be >= 0".to_string()).into());
+    }
+
+    let q = q.filter(|q| !q.trim().is_empty());
+    let tag = tag.map(|t| t.trim().to_ascii_lowercase());
+
+    let resp = sqlx::query!(
+        r#"
+SELECT g.id,
+       g.name,
+       g.avatar,
+       g.description,
+       g.tags,
+       count(m.user_id) AS "member_count!"
+FROM guilds g
+    INNER JOIN members m ON m.guild_id = g.id
+WHERE g.discoverable
+  AND ($1::TEXT IS NULL
+    OR to_tsvector('english', g.name || ' ' || coalesce(g.description, ''))
+           @@ websearch_to_tsquery('english', $1))
+  AND ($2::TEXT IS NULL OR g.tags @> ARRAY[$2])
+GROUP BY g.id
+ORDER BY "member_count!" DESC, g.id
+LIMIT $3 OFFSET $4
+"#,
+        q,
+        tag,
+        PAGE_SIZE,
+        page.saturating_mul(PAGE_SIZE),
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut guilds = Vec::with_capacity(resp.len());
+    for x in resp {
+        guilds.push(DiscoverableGuild {
+            id: bigdecimal_to_u128!(x.id),
+            name: x.name,
+            avatar: x.avatar,
+            description: x.description,
+            tags: x.tags,
+            member_count: x.member_count,
+        });
+    }
+
+    Ok(crate::Json {
+        obj: guilds,
+        code: 200,
+    })
+}
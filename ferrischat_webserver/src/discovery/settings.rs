@@ -0,0 +1,45 @@
+//! Validation for the discovery settings guild owners can set
+//!
+
+/// Maximum length of a guild's discovery description, in characters.
+pub const MAX_DESCRIPTION_LENGTH: usize = 300;
+/// Maximum number of tags a guild can be listed under.
+pub const MAX_TAGS: usize = 5;
+/// Maximum length of a single tag, in bytes.
+pub const MAX_TAG_LENGTH: usize = 24;
+
+/// Check a discovery description is usable, returning a reason if it is not.
+pub fn validate_discovery_description(description: &str) -> Result<(), String> {
+    if description.chars().count() > MAX_DESCRIPTION_LENGTH {
+        return Err(format!(
+            "guild descriptions must be at most {} characters long",
+            MAX_DESCRIPTION_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+/// Lowercase and deduplicate discovery tags, returning a reason if any are invalid.
+pub fn normalize_discovery_tags(tags: Vec<String>) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim().to_ascii_lowercase();
+        if tag.is_empty() || tag.len() > MAX_TAG_LENGTH {
+            return Err(format!(
+                "tags must be between 1 and {} characters long",
+                MAX_TAG_LENGTH
+            ));
+        }
+        if !tag.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-') {
+            return Err("tags may only contain ASCII letters, digits and `-`".to_string());
+        }
+        if !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+
+    if normalized.len() > MAX_TAGS {
+        return Err(format!("guilds can have at most {} tags", MAX_TAGS));
+    }
+    Ok(normalized)
+}
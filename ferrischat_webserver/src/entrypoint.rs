@@ -8,6 +8,13 @@ use axum::Router;
 #[allow(clippy::expect_used)]
 pub async fn entrypoint() {
     init_rng();
+    crate::storage::init_storage();
+    crate::unfurl::init_unfurler();
+
+    tokio::spawn(crate::threads::auto_archive_threads());
+    tokio::spawn(crate::messages::revisions::prune_message_revisions());
+    tokio::spawn(crate::messages::scheduled::dispatch_scheduled_messages());
+    tokio::spawn(crate::messages::reap_expired_messages());
 
     let router = Router::new()
         // GET    /teapot
@@ -18,11 +25,14 @@ pub async fn entrypoint() {
         // GET    /ping
         .route(expand_version!("ping"), get(async || (StatusCode::OK, "")))
         .merge(crate::auth::generate_auth_routes())
+        .merge(crate::avatars::generate_avatars_routes())
         .merge(crate::channels::generate_channels_routes())
+        .merge(crate::discovery::generate_discovery_routes())
         .merge(crate::guilds::generate_guilds_routes())
         .merge(crate::invites::generate_invites_routes())
         .merge(crate::members::generate_members_routes())
         .merge(crate::messages::generate_messages_route())
+        .merge(crate::threads::generate_threads_routes())
         .merge(crate::users::generate_users_route())
         .merge(crate::ws::generate_ws_route());
 
@@ -8,8 +8,10 @@ use ferrischat_redis::deadpool_redis::PoolError;
 use http::header::CONTENT_TYPE;
 use http::HeaderValue;
 use lettre::address::AddressError;
+use serde::Serialize;
 use sqlx::Error;
 use std::borrow::Cow;
+use std::fmt;
 
 pub enum WebServerError {
     Database(sqlx::Error),
@@ -21,6 +23,49 @@ pub enum WebServerError {
     Http(ErrorJson),
     RandomGenerationFailure,
     MissingNodeId,
+    Storage(std::io::Error),
+    MissingStorage,
+    /// The user has to wait before retrying. `retry_after` is in milliseconds.
+    RateLimited {
+        retry_after: u64,
+    },
+}
+
+/// Body sent along with a 429.
+#[derive(Serialize)]
+struct RateLimitedJson {
+    reason: &'static str,
+    /// Seconds to wait before retrying.
+    retry_after: f64,
+}
+
+impl fmt::Display for WebServerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebServerError::Database(e) => write!(f, "database returned an error: {}", e),
+            WebServerError::MissingDatabase => f.write_str("database pool was not found"),
+            WebServerError::Json(e) => write!(f, "JSON (de)serialization failed: {}", e),
+            WebServerError::Redis(e) => write!(f, "redis returned an error: {}", e),
+            WebServerError::MissingRedis => f.write_str("redis pool missing"),
+            WebServerError::RedisPool(e) => write!(f, "redis pool returned an error: {}", e),
+            WebServerError::Http(e) => write!(f, "request failed with status {}", e.get_code()),
+            WebServerError::RandomGenerationFailure => {
+                f.write_str("failed to generate random bits for token generation")
+            }
+            WebServerError::MissingNodeId => f.write_str("redis has not been set up yet"),
+            WebServerError::Storage(e) => write!(f, "file storage returned an error: {}", e),
+            WebServerError::MissingStorage => f.write_str("file storage was not set up"),
+            WebServerError::RateLimited { retry_after } => {
+                write!(f, "rate limited for another {}ms", retry_after)
+            }
+        }
+    }
+}
+
+impl fmt::Debug for WebServerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
 }
 
 impl From<PoolError> for WebServerError {
@@ -47,6 +92,12 @@ impl From<RedisError> for WebServerError {
     }
 }
 
+impl From<std::io::Error> for WebServerError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Storage(e)
+    }
+}
+
 impl From<ErrorJson> for WebServerError {
     fn from(e: ErrorJson) -> Self {
         Self::Http(e)
@@ -136,6 +187,10 @@ impl From<SplitTokenError> for WebServerError {
 
 impl IntoResponse for WebServerError {
     fn into_response(self) -> Response<BoxBody> {
+        if let WebServerError::RateLimited { retry_after } = self {
+            return rate_limited_response(retry_after);
+        }
+
         let body = match self {
             WebServerError::Database(e) => {
                 if let sqlx::Error::Database(e) = e {
@@ -171,7 +226,10 @@ impl IntoResponse for WebServerError {
                         .to_string(),
                 ),
             ),
+            WebServerError::Storage(e) => ErrorJson::new_500(format!("File storage returned an error: {}", e), false, None),
+            WebServerError::MissingStorage => ErrorJson::new_500("File storage was not set up".to_string(), false, None),
 
+            WebServerError::RateLimited { .. } => unreachable!("rate limits are handled above"),
             WebServerError::MissingNodeId => ErrorJson::new_500(
                 "Redis has not been set up yet".to_string(),
                 true,
@@ -206,3 +264,29 @@ impl IntoResponse for WebServerError {
             })
     }
 }
+
+fn rate_limited_response(retry_after: u64) -> Response<BoxBody> {
+    #[allow(clippy::cast_precision_loss)]
+    let resp = RateLimitedJson {
+        reason: "you are being rate limited",
+        retry_after: retry_after as f64 / 1000.0,
+    };
+    let bytes = match simd_json::to_vec(&resp) {
+        Ok(res) => res,
+        Err(err) => {
+            return Response::builder()
+                .status(http::StatusCode::INTERNAL_SERVER_ERROR)
+                .header(CONTENT_TYPE, HeaderValue::from_static("text/plain"))
+                .body(body::boxed(body::Full::from(err.to_string())))
+                .expect("failed to convert static data to a valid request");
+        }
+    };
+
+    Response::builder()
+        .status(http::StatusCode::TOO_MANY_REQUESTS)
+        .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+        // the header only takes whole seconds, so round up
+        .header(http::header::RETRY_AFTER, (retry_after + 999) / 1000)
+        .body(body::boxed(body::Full::from(bytes)))
+        .expect("failed to convert static data to a valid request")
+}
@@ -0,0 +1,73 @@
+use crate::ws::fire_event;
+use crate::WebServerError;
+use axum::extract::Path;
+use ferrischat_common::types::{ErrorJson, Member};
+use ferrischat_common::ws::WsOutboundEvent;
+
+/// POST `/v0/guilds/{guild_id}/rules/accept`
+///
+/// Accepts the guild's rules, which lets a pending member start posting.
+pub async fn accept_guild_rules(
+    Path(guild_id): Path<u128>,
+    crate::Authorization(user_id): crate::Authorization,
+) -> Result<crate::Json<Member>, WebServerError> {
+    let db = get_db_or_fail!();
+    let bigint_guild_id = u128_to_bigdecimal!(guild_id);
+    let bigint_user_id = u128_to_bigdecimal!(user_id);
+
+    let member = sqlx::query!(
+        "SELECT * FROM members WHERE user_id = $1 AND guild_id = $2",
+        bigint_user_id,
+        bigint_guild_id
+    )
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| ErrorJson::new_404(format!("Unknown member with ID {}", user_id)))?;
+
+    let inviter_id = match member.inviter_id {
+        Some(inviter_id) => Some(bigdecimal_to_u128!(inviter_id)),
+        None => None,
+    };
+
+    let old_member_obj = Member {
+        user_id: Some(user_id),
+        user: None,
+        guild_id: Some(guild_id),
+        guild: None,
+        joined_at: Some(member.joined_at),
+        invite_code: member.invite_code,
+        inviter_id,
+        pending: Some(member.pending),
+    };
+
+    if !member.pending {
+        return Ok(crate::Json {
+            obj: old_member_obj,
+            code: 200,
+        });
+    }
+
+    sqlx::query!(
+        "UPDATE members SET pending = false WHERE user_id = $1 AND guild_id = $2",
+        bigint_user_id,
+        bigint_guild_id
+    )
+    .execute(db)
+    .await?;
+
+    let new_member_obj = Member {
+        pending: Some(false),
+        ..old_member_obj.clone()
+    };
+
+    let event = WsOutboundEvent::MemberUpdate {
+        old: old_member_obj,
+        new: new_member_obj.clone(),
+    };
+
+    fire_event(&event).await?;
+    Ok(crate::Json {
+        obj: new_member_obj,
+        code: 200,
+    })
+}
@@ -1,22 +1,56 @@
 use crate::ws::fire_event;
 use crate::WebServerError;
+use axum::extract::Query;
 use axum::Json;
-use ferrischat_common::request_json::GuildCreateJson;
-use ferrischat_common::types::{Guild, GuildFlags, Member, ModelType};
+use ferrischat_common::perms::Permissions;
+use ferrischat_common::request_json::{CreateGuildUrlParams, GuildCreateJson};
+use ferrischat_common::types::{
+    Channel, ChannelType, ErrorJson, Guild, GuildFlags, Member, ModelType, Role,
+};
 use ferrischat_common::ws::WsOutboundEvent;
 use ferrischat_snowflake_generator::generate_snowflake;
+use std::collections::HashMap;
 
 /// POST /v0/guilds/
+///
+/// If `template` is passed, the guild's channels and roles are copied from that template,
+/// and the request body may be left out to use the template's name as well.
 pub async fn create_guild(
     auth: crate::Authorization,
-    guild_info: Json<GuildCreateJson>,
+    Query(CreateGuildUrlParams { template }): Query<CreateGuildUrlParams>,
+    guild_info: Option<Json<GuildCreateJson>>,
 ) -> Result<crate::Json<Guild>, WebServerError> {
     let db = get_db_or_fail!();
     let node_id = get_node_id!();
     let guild_id = generate_snowflake::<0>(ModelType::Guild as u8, node_id);
     let bigint_guild_id = u128_to_bigdecimal!(guild_id);
     let bigint_user_id = u128_to_bigdecimal!(auth.0);
-    let GuildCreateJson { name } = guild_info.0;
+
+    let template = match template {
+        Some(code) => Some(
+            sqlx::query!(
+                "SELECT code, name FROM guild_templates WHERE code = $1",
+                code
+            )
+            .fetch_optional(db)
+            .await?
+            .ok_or_else(|| ErrorJson::new_404(format!("Unknown template with code {}", code)))?,
+        ),
+        None => None,
+    };
+
+    let name = match (guild_info, &template) {
+        (Some(Json(GuildCreateJson { name })), _) => name,
+        (None, Some(template)) => template.name.clone(),
+        (None, None) => {
+            return Err(ErrorJson::new_400(
+                "a guild name is required unless a template is used".to_string(),
+            )
+            .into())
+        }
+    };
+
+    let mut tx = db.begin().await?;
 
     sqlx::query!(
         "INSERT INTO guilds(id, owner_id, name, flags) VALUES ($1, $2, $3, $4)",
@@ -25,30 +59,130 @@ pub async fn create_guild(
         name,
         0
     )
-    .execute(db)
+    .execute(&mut tx)
     .await?;
 
-    sqlx::query!(
-        "INSERT INTO members VALUES ($1, $2)",
+    let joined_at = sqlx::query!(
+        "INSERT INTO members (user_id, guild_id) VALUES ($1, $2) RETURNING joined_at",
         bigint_user_id,
         bigint_guild_id
     )
-    .execute(db)
-    .await?;
+    .fetch_one(&mut tx)
+    .await?
+    .joined_at;
+
+    let (channels, roles) = if let Some(template) = template {
+        let mut template_channels = sqlx::query!(
+            "SELECT * FROM guild_template_channels WHERE template_code = $1 ORDER BY position",
+            template.code
+        )
+        .fetch_all(&mut tx)
+        .await?;
+
+        // categories go first, so every channel's parent exists by the time it's made
+        template_channels.sort_by_key(|c| c.channel_type != ChannelType::Category as i16);
+
+        let mut category_ids = HashMap::new();
+        let mut channels = Vec::with_capacity(template_channels.len());
+        for template_channel in template_channels {
+            let channel_id = generate_snowflake::<0>(ModelType::Channel as u8, node_id);
+            let bigint_channel_id = u128_to_bigdecimal!(channel_id);
+            let channel_type =
+                ChannelType::from_i16(template_channel.channel_type).unwrap_or(ChannelType::Text);
+            let parent_id = template_channel
+                .parent_position
+                .and_then(|p| category_ids.get(&p).copied());
+
+            sqlx::query!(
+                "INSERT INTO channels (id, name, guild_id, position, channel_type, parent_id) VALUES ($1, $2, $3, $4, $5, $6)",
+                bigint_channel_id,
+                template_channel.name,
+                bigint_guild_id,
+                template_channel.position,
+                channel_type as i16,
+                parent_id.map(|id| u128_to_bigdecimal!(id))
+            )
+            .execute(&mut tx)
+            .await?;
+
+            if channel_type == ChannelType::Category {
+                category_ids.insert(template_channel.position, channel_id);
+            }
+
+            channels.push(Channel {
+                id: channel_id,
+                name: template_channel.name,
+                guild_id: Some(guild_id),
+                channel_type,
+                parent_id,
+                position: template_channel.position,
+                topic: None,
+                slowmode: 0,
+                nsfw: false,
+                announcement: false,
+                message_ttl: None,
+            });
+        }
+        channels.sort_by_key(|c| c.position);
+
+        let template_roles = sqlx::query!(
+            "SELECT * FROM guild_template_roles WHERE template_code = $1",
+            template.code
+        )
+        .fetch_all(&mut tx)
+        .await?;
+
+        let mut roles = Vec::with_capacity(template_roles.len());
+        for template_role in template_roles {
+            let role_id = generate_snowflake::<0>(ModelType::Role as u8, node_id);
+            let bigint_role_id = u128_to_bigdecimal!(role_id);
+
+            sqlx::query!(
+                "INSERT INTO roles VALUES ($1, $2, $3, $4, $5, $6)",
+                bigint_role_id,
+                template_role.name,
+                template_role.color,
+                template_role.position,
+                template_role.permissions,
+                bigint_guild_id
+            )
+            .execute(&mut tx)
+            .await?;
+
+            roles.push(Role {
+                id: role_id,
+                name: template_role.name,
+                color: template_role.color,
+                position: template_role.position,
+                guild_id,
+                permissions: Permissions::empty(),
+            });
+        }
+
+        (Some(channels), Some(roles))
+    } else {
+        (None, None)
+    };
+
+    tx.commit().await?;
 
     let guild_obj = Guild {
         id: guild_id,
         owner_id: auth.0,
         name,
-        channels: None,
+        channels,
         flags: GuildFlags::empty(),
         members: Some(vec![Member {
             guild_id: Some(guild_id),
             user_id: Some(auth.0),
             user: None,
             guild: None,
+            joined_at: Some(joined_at),
+            invite_code: None,
+            inviter_id: None,
+            pending: Some(false),
         }]),
-        roles: None,
+        roles,
         avatar: None,
     };
 
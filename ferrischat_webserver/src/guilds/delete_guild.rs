@@ -1,3 +1,5 @@
+use crate::avatars::{delete_image_files, ImageKind};
+use crate::storage::delete_attachment_files;
 use crate::ws::fire_event;
 use crate::WebServerError;
 use axum::extract::Path;
@@ -21,12 +23,40 @@ pub async fn delete_guild(
         return Err(ErrorJson::new_403("Forbidden".to_string()).into());
     }
 
+    let mut tx = db.begin().await?;
+
+    // the rows go with their messages, but the files have to be removed by hand afterwards
+    let attachments = sqlx::query!(
+        r#"
+SELECT a.id
+FROM attachments a
+    INNER JOIN messages m ON m.id = a.message_id
+    INNER JOIN channels c ON c.id = m.channel_id
+WHERE c.guild_id = $1
+"#,
+        bigint_guild_id
+    )
+    .fetch_all(&mut tx)
+    .await?;
+
     let guild_resp = sqlx::query!(
         "DELETE FROM guilds WHERE id = $1 RETURNING *",
         bigint_guild_id,
     )
-    .fetch_one(db)
+    .fetch_one(&mut tx)
     .await?;
+
+    tx.commit().await?;
+
+    let mut attachment_ids = Vec::with_capacity(attachments.len());
+    for x in attachments {
+        attachment_ids.push(bigdecimal_to_u128!(x.id));
+    }
+    delete_attachment_files(attachment_ids).await;
+    if let Some(ref avatar) = guild_resp.avatar {
+        delete_image_files(ImageKind::GuildIcon, guild_id, avatar).await;
+    }
+
     let guild_obj = Guild {
         id: guild_id,
         owner_id: auth.0,
@@ -38,6 +68,10 @@ pub async fn delete_guild(
             user_id: Some(auth.0),
             user: None,
             guild: None,
+            joined_at: None,
+            invite_code: None,
+            inviter_id: None,
+            pending: None,
         }]),
         roles: None,
         avatar: guild_resp.avatar,
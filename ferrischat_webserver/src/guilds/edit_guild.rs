@@ -1,15 +1,37 @@
+use super::validate_rules;
+use crate::avatars::{delete_replaced_image, upload_image, ImageKind};
+use crate::discovery::{normalize_discovery_tags, validate_discovery_description};
+use crate::invites::validate_vanity_code;
+use crate::messages::revisions::MAX_REVISION_RETENTION;
 use crate::ws::fire_event;
 use crate::WebServerError;
 use axum::extract::Path;
 use axum::Json;
 use ferrischat_common::request_json::GuildUpdateJson;
-use ferrischat_common::types::{ErrorJson, Guild, GuildFlags};
+use ferrischat_common::types::{ErrorJson, Guild, GuildFlags, Invite};
 use ferrischat_common::ws::WsOutboundEvent;
+use sqlx::types::time::OffsetDateTime;
 
+/// PATCH `/v0/guilds/{guild_id}`
+///
+/// `avatar` is a base64 encoded image, or empty to remove the icon.
 pub async fn edit_guild(
     Path(guild_id): Path<u128>,
-    Json(GuildUpdateJson { name, avatar }): Json<GuildUpdateJson>,
-    _: crate::Authorization,
+    Json(GuildUpdateJson {
+        name,
+        avatar,
+        vanity_code,
+        description,
+        tags,
+        discoverable,
+        verification_level,
+        min_account_age,
+        min_membership_age,
+        rules,
+        message_revisions,
+        message_revision_retention,
+    }): Json<GuildUpdateJson>,
+    auth: crate::Authorization,
 ) -> Result<crate::Json<Guild>, WebServerError> {
     let db = get_db_or_fail!();
 
@@ -30,6 +52,56 @@ pub async fn edit_guild(
         avatar: guild.avatar,
     };
 
+    if (vanity_code.is_some()
+        || description.is_some()
+        || tags.is_some()
+        || discoverable.is_some()
+        || verification_level.is_some()
+        || min_account_age.is_some()
+        || min_membership_age.is_some()
+        || rules.is_some()
+        || message_revisions.is_some()
+        || message_revision_retention.is_some())
+        && old_guild_obj.owner_id != auth.0
+    {
+        return Err(ErrorJson::new_403(
+            "only the guild owner can change vanity, discovery, screening or edit history settings"
+                .to_string(),
+        )
+        .into());
+    }
+
+    if min_account_age.map_or(false, |x| x < 0) || min_membership_age.map_or(false, |x| x < 0) {
+        return Err(ErrorJson::new_400("minimum ages must be >= 0".to_string()).into());
+    }
+
+    if message_revision_retention.map_or(false, |x| !(0..=MAX_REVISION_RETENTION).contains(&x)) {
+        return Err(ErrorJson::new_400(format!(
+            "message_revision_retention must be between 0 and {} days",
+            MAX_REVISION_RETENTION
+        ))
+        .into());
+    }
+
+    if let Some(ref rules) = rules {
+        validate_rules(rules).map_err(ErrorJson::new_400)?;
+    }
+
+    if let Some(ref vanity_code) = vanity_code {
+        if !vanity_code.is_empty() {
+            validate_vanity_code(vanity_code).map_err(ErrorJson::new_400)?;
+        }
+    }
+
+    if let Some(ref description) = description {
+        validate_discovery_description(description).map_err(ErrorJson::new_400)?;
+    }
+
+    let tags = tags
+        .map(normalize_discovery_tags)
+        .transpose()
+        .map_err(ErrorJson::new_400)?;
+
     if let Some(name) = name {
         sqlx::query!(
             "UPDATE guilds SET name = $1 WHERE id = $2",
@@ -40,7 +112,9 @@ pub async fn edit_guild(
         .await?;
     }
 
+    // the old icon is already in `old_guild_obj`
     if let Some(avatar) = avatar {
+        let avatar = upload_image(ImageKind::GuildIcon, guild_id, avatar).await?;
         sqlx::query!(
             "UPDATE guilds SET avatar = $1 WHERE id = $2",
             avatar,
@@ -48,6 +122,192 @@ pub async fn edit_guild(
         )
         .execute(db)
         .await?;
+        delete_replaced_image(
+            ImageKind::GuildIcon,
+            guild_id,
+            old_guild_obj.avatar.clone(),
+            &avatar,
+        )
+        .await;
+    }
+
+    // an empty description removes it
+    if let Some(description) = description {
+        sqlx::query!(
+            "UPDATE guilds SET description = NULLIF($1, '') WHERE id = $2",
+            description,
+            bigint_guild_id
+        )
+        .execute(db)
+        .await?;
+    }
+
+    if let Some(tags) = tags {
+        sqlx::query!(
+            "UPDATE guilds SET tags = $1 WHERE id = $2",
+            &tags[..],
+            bigint_guild_id
+        )
+        .execute(db)
+        .await?;
+    }
+
+    if let Some(discoverable) = discoverable {
+        sqlx::query!(
+            "UPDATE guilds SET discoverable = $1 WHERE id = $2",
+            discoverable,
+            bigint_guild_id
+        )
+        .execute(db)
+        .await?;
+    }
+
+    if let Some(verification_level) = verification_level {
+        sqlx::query!(
+            "UPDATE guilds SET verification_level = $1 WHERE id = $2",
+            verification_level as i16,
+            bigint_guild_id
+        )
+        .execute(db)
+        .await?;
+    }
+
+    if let Some(min_account_age) = min_account_age {
+        sqlx::query!(
+            "UPDATE guilds SET min_account_age = $1 WHERE id = $2",
+            min_account_age,
+            bigint_guild_id
+        )
+        .execute(db)
+        .await?;
+    }
+
+    if let Some(min_membership_age) = min_membership_age {
+        sqlx::query!(
+            "UPDATE guilds SET min_membership_age = $1 WHERE id = $2",
+            min_membership_age,
+            bigint_guild_id
+        )
+        .execute(db)
+        .await?;
+    }
+
+    // empty rules turn screening off, which lets everyone still pending through
+    if let Some(rules) = rules {
+        let mut tx = db.begin().await?;
+
+        sqlx::query!(
+            "UPDATE guilds SET rules = NULLIF($1, '') WHERE id = $2",
+            rules,
+            bigint_guild_id
+        )
+        .execute(&mut tx)
+        .await?;
+
+        if rules.is_empty() {
+            sqlx::query!(
+                "UPDATE members SET pending = false WHERE guild_id = $1",
+                bigint_guild_id
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+    }
+
+    // turning edit history off throws away what was already kept
+    if let Some(message_revisions) = message_revisions {
+        let mut tx = db.begin().await?;
+
+        sqlx::query!(
+            "UPDATE guilds SET message_revisions = $1 WHERE id = $2",
+            message_revisions,
+            bigint_guild_id
+        )
+        .execute(&mut tx)
+        .await?;
+
+        if !message_revisions {
+            sqlx::query!(
+                "DELETE FROM message_revisions r USING messages m, channels c WHERE m.id = r.message_id AND c.id = m.channel_id AND c.guild_id = $1",
+                bigint_guild_id
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+    }
+
+    // a retention of 0 keeps revisions forever
+    if let Some(message_revision_retention) = message_revision_retention {
+        sqlx::query!(
+            "UPDATE guilds SET message_revision_retention = NULLIF($1, 0) WHERE id = $2",
+            message_revision_retention,
+            bigint_guild_id
+        )
+        .execute(db)
+        .await?;
+    }
+
+    // an empty vanity code removes the guild's current one
+    if let Some(vanity_code) = vanity_code {
+        let mut tx = db.begin().await?;
+
+        let old_vanity = sqlx::query!(
+            "DELETE FROM invites WHERE guild_id = $1 AND vanity = true RETURNING *",
+            bigint_guild_id
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+
+        let new_vanity = if vanity_code.is_empty() {
+            None
+        } else {
+            let now = OffsetDateTime::now_utc().unix_timestamp();
+            let bigint_owner_id = u128_to_bigdecimal!(auth.0);
+            sqlx::query!(
+                "INSERT INTO invites VALUES ($1, $2, $3, $4, 0, null, null, true)",
+                vanity_code,
+                bigint_owner_id,
+                bigint_guild_id,
+                now
+            )
+            .execute(&mut tx)
+            .await?;
+
+            Some(Invite {
+                code: vanity_code,
+                owner_id: auth.0,
+                guild_id,
+                created_at: now,
+                uses: 0,
+                max_uses: None,
+                max_age: None,
+            })
+        };
+
+        tx.commit().await?;
+
+        if let Some(invite) = old_vanity {
+            let event = WsOutboundEvent::InviteDelete {
+                invite: Invite {
+                    code: invite.code,
+                    owner_id: bigdecimal_to_u128!(invite.owner_id),
+                    guild_id,
+                    created_at: invite.created_at,
+                    uses: invite.uses,
+                    max_uses: invite.max_uses,
+                    max_age: invite.max_age,
+                },
+            };
+            fire_event(&event).await?;
+        }
+        if let Some(invite) = new_vanity {
+            let event = WsOutboundEvent::InviteCreate { invite };
+            fire_event(&event).await?;
+        }
     }
 
     let guild = sqlx::query!("SELECT * FROM guilds WHERE id = $1", bigint_guild_id)
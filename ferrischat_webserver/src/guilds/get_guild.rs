@@ -1,7 +1,9 @@
 use crate::WebServerError;
 use axum::extract::{Path, Query};
 use ferrischat_common::request_json::GetGuildUrlParams;
-use ferrischat_common::types::{Channel, ErrorJson, Guild, GuildFlags, Member, User, UserFlags};
+use ferrischat_common::types::{
+    Channel, ChannelType, ErrorJson, Guild, GuildFlags, Member, User, UserFlags,
+};
 use num_traits::ToPrimitive;
 
 /// GET `/v0/guilds/{guild_id}`
@@ -20,11 +22,11 @@ pub async fn get_guild(
 
     let channels: Option<Vec<Channel>> = if params.channels.unwrap_or(true) {
         let resp = sqlx::query!(
-            "SELECT * FROM channels WHERE guild_id = $1",
-            bigint_guild_id
-        )
-        .fetch_all(db)
-        .await?;
+                "SELECT * FROM channels WHERE guild_id = $1 AND channel_type <> 4 ORDER BY position, id",
+                bigint_guild_id
+            )
+            .fetch_all(db)
+            .await?;
         Some(
             resp.iter()
                 .filter_map(|x| {
@@ -33,10 +35,20 @@ pub async fn get_guild(
                         name: x.name.clone(),
                         guild_id: x
                             .guild_id
-                            .with_scale(0)
-                            .into_bigint_and_exponent()
-                            .0
-                            .to_u128()?,
+                            .as_ref()
+                            .and_then(|id| id.with_scale(0).into_bigint_and_exponent().0.to_u128()),
+                        channel_type: ChannelType::from_i16(x.channel_type)
+                            .unwrap_or(ChannelType::Text),
+                        parent_id: x
+                            .parent_id
+                            .as_ref()
+                            .and_then(|id| id.with_scale(0).into_bigint_and_exponent().0.to_u128()),
+                        position: x.position,
+                        topic: x.topic.clone(),
+                        slowmode: x.slowmode,
+                        nsfw: x.nsfw,
+                        announcement: x.announcement,
+                        message_ttl: x.message_ttl,
                     })
                 })
                 .collect(),
@@ -91,6 +103,13 @@ pub async fn get_guild(
                         }),
                         guild_id: Some(guild_id),
                         guild: None,
+                        joined_at: Some(x.joined_at),
+                        invite_code: x.invite_code.clone(),
+                        inviter_id: x
+                            .inviter_id
+                            .as_ref()
+                            .and_then(|id| id.with_scale(0).into_bigint_and_exponent().0.to_u128()),
+                        pending: Some(x.pending),
                     })
                 })
                 .collect(),
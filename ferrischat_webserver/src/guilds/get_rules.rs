@@ -0,0 +1,32 @@
+use crate::WebServerError;
+use axum::extract::Path;
+use ferrischat_common::types::{ErrorJson, GuildRules, VerificationLevel};
+
+/// GET `/v0/guilds/{guild_id}/rules`
+pub async fn get_guild_rules(
+    _: crate::Authorization,
+    Path(guild_id): Path<u128>,
+) -> Result<crate::Json<GuildRules>, WebServerError> {
+    let db = get_db_or_fail!();
+    let bigint_guild_id = u128_to_bigdecimal!(guild_id);
+
+    let guild = sqlx::query!(
+        "SELECT rules, verification_level, min_account_age, min_membership_age FROM guilds WHERE id = $1",
+        bigint_guild_id
+    )
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| ErrorJson::new_404(format!("Unknown guild with ID {}", guild_id)))?;
+
+    Ok(crate::Json {
+        obj: GuildRules {
+            guild_id,
+            rules: guild.rules,
+            verification_level: VerificationLevel::from_i16(guild.verification_level)
+                .unwrap_or(VerificationLevel::None),
+            min_account_age: guild.min_account_age,
+            min_membership_age: guild.min_membership_age,
+        },
+        code: 200,
+    })
+}
@@ -1,13 +1,22 @@
+mod accept_rules;
 mod create_guild;
 mod delete_guild;
 mod edit_guild;
 mod get_guild;
+mod get_rules;
+mod permissions;
 pub mod roles;
+mod screening;
+pub mod templates;
 
+pub use accept_rules::*;
 pub use create_guild::*;
 pub use delete_guild::*;
 pub use edit_guild::*;
 pub use get_guild::*;
+pub use get_rules::*;
+pub use permissions::*;
+pub use screening::*;
 
 use axum::routing::{get, post};
 use axum::Router;
@@ -16,6 +25,7 @@ pub fn generate_guilds_routes() -> axum::Router {
     debug!("generating routes for guilds");
     Router::new()
         // POST   /guilds
+        // POST   /guilds?template=:code
         .route(expand_version!("guilds"), post(create_guild))
         // GET    /guilds/:guild_id
         // PATCH  /guilds/:guild_id
@@ -24,6 +34,18 @@ pub fn generate_guilds_routes() -> axum::Router {
             expand_version!("guilds/:guild_id"),
             get(get_guild).patch(edit_guild).delete(delete_guild),
         )
+        // GET    /guilds/:guild_id/rules
+        .route(
+            expand_version!("guilds/:guild_id/rules"),
+            get(get_guild_rules),
+        )
+        // POST   /guilds/:guild_id/rules/accept
+        .route(
+            expand_version!("guilds/:guild_id/rules/accept"),
+            post(accept_guild_rules),
+        )
         // roles routes
         .merge(roles::generate_roles_routes())
+        // templates routes
+        .merge(templates::generate_templates_routes())
 }
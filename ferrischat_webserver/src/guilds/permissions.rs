@@ -0,0 +1,23 @@
+use crate::WebServerError;
+use ferrischat_common::perms::Permissions;
+use sqlx::types::BigDecimal;
+
+/// Checks whether a user has all of `permissions` in a guild.
+///
+/// The guild owner has every permission.
+// TODO: check role permissions once they're stored: for now, nobody besides the owner has any
+pub async fn member_has_permissions(
+    db: &sqlx::PgPool,
+    guild_id: &BigDecimal,
+    user_id: &BigDecimal,
+    _permissions: Permissions,
+) -> Result<bool, WebServerError> {
+    Ok(sqlx::query!(
+        r#"SELECT EXISTS(SELECT * FROM guilds WHERE id = $1 AND owner_id = $2) AS "exists!""#,
+        guild_id,
+        user_id
+    )
+    .fetch_one(db)
+    .await?
+    .exists)
+}
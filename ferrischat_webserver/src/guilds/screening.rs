@@ -0,0 +1,90 @@
+use crate::WebServerError;
+use ferrischat_common::types::{ErrorJson, UserFlags, VerificationLevel};
+use sqlx::types::BigDecimal;
+
+/// Maximum length of a guild's rules, in characters.
+pub const MAX_RULES_LENGTH: usize = 4000;
+
+/// Checks that a guild's rules are short enough to store.
+///
+/// An empty string is accepted, and is used to remove the rules.
+pub fn validate_rules(rules: &str) -> Result<(), String> {
+    if rules.chars().count() > MAX_RULES_LENGTH {
+        return Err(format!(
+            "rules must be at most {} characters long",
+            MAX_RULES_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+/// Checks whether a user is allowed to post in a guild.
+///
+/// Returns a 403 if the user isn't a member, hasn't accepted the guild's rules yet,
+/// or doesn't meet the guild's verification level.
+/// The guild owner and bots are never held back by screening.
+pub async fn check_member_can_post(
+    db: &sqlx::PgPool,
+    guild_id: &BigDecimal,
+    user_id: &BigDecimal,
+) -> Result<(), WebServerError> {
+    // account age comes from the creation time in the upper 64 bits of the user's snowflake
+    let screening = sqlx::query!(
+        r#"
+SELECT g.owner_id,
+       g.verification_level,
+       m.pending,
+       u.verified,
+       u.flags,
+       snowflake_timestamp(u.id) <= extract(EPOCH FROM now()) * 1000 - g.min_account_age * 60000::BIGINT
+           AS "account_old_enough!",
+       m.joined_at <= (now() AT TIME ZONE 'UTC') - make_interval(mins => g.min_membership_age)
+           AS "member_old_enough!"
+FROM members m
+    INNER JOIN guilds g ON g.id = m.guild_id
+    INNER JOIN users u ON u.id = m.user_id
+WHERE m.guild_id = $1
+  AND m.user_id = $2
+"#,
+        guild_id,
+        user_id
+    )
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| ErrorJson::new_403("you are not a member of this guild".to_string()))?;
+
+    if &screening.owner_id == user_id
+        || UserFlags::from_bits_truncate(screening.flags).contains(UserFlags::BOT_ACCOUNT)
+    {
+        return Ok(());
+    }
+
+    if screening.pending {
+        return Err(ErrorJson::new_403(
+            "you must accept this guild's rules before posting".to_string(),
+        )
+        .into());
+    }
+
+    let level = VerificationLevel::from_i16(screening.verification_level)
+        .unwrap_or(VerificationLevel::None);
+    if level >= VerificationLevel::VerifiedEmail && !screening.verified {
+        return Err(
+            ErrorJson::new_403("this guild requires a verified email to post".to_string()).into(),
+        );
+    }
+    if level >= VerificationLevel::AccountAge && !screening.account_old_enough {
+        return Err(ErrorJson::new_403(
+            "your account is too new to post in this guild".to_string(),
+        )
+        .into());
+    }
+    if level >= VerificationLevel::MembershipAge && !screening.member_old_enough {
+        return Err(ErrorJson::new_403(
+            "you haven't been a member of this guild long enough to post".to_string(),
+        )
+        .into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use crate::invites::{generate_invite_code, invite_code_length};
+use crate::WebServerError;
+use axum::extract::Path;
+use ferrischat_common::perms::Permissions;
+use ferrischat_common::types::{
+    ChannelType, ErrorJson, GuildTemplate, TemplateChannel, TemplateRole,
+};
+use sqlx::types::time::OffsetDateTime;
+
+/// POST `/v0/guilds/{guild_id}/templates`
+pub async fn create_template(
+    Path(guild_id): Path<u128>,
+    auth: crate::Authorization,
+) -> Result<crate::Json<GuildTemplate>, WebServerError> {
+    let db = get_db_or_fail!();
+    let bigint_guild_id = u128_to_bigdecimal!(guild_id);
+    let bigint_user_id = u128_to_bigdecimal!(auth.0);
+
+    let guild = sqlx::query!(
+        "SELECT name, owner_id FROM guilds WHERE id = $1",
+        bigint_guild_id
+    )
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| ErrorJson::new_404(format!("Unknown guild with ID {}", guild_id)))?;
+    if bigdecimal_to_u128!(guild.owner_id) != auth.0 {
+        return Err(
+            ErrorJson::new_403("only the guild owner can create templates".to_string()).into(),
+        );
+    }
+
+    let code = generate_invite_code(invite_code_length())
+        .ok_or(WebServerError::RandomGenerationFailure)?;
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+
+    let mut tx = db.begin().await?;
+
+    sqlx::query!(
+        "INSERT INTO guild_templates VALUES ($1, $2, $3, $4, $5)",
+        code,
+        guild.name,
+        bigint_guild_id,
+        bigint_user_id,
+        now
+    )
+    .execute(&mut tx)
+    .await?;
+
+    // channels point at their category by its position in the template, since IDs aren't kept
+    let channels = sqlx::query!(
+        r#"
+WITH ordered AS (SELECT id,
+                        name,
+                        channel_type,
+                        parent_id,
+                        row_number() OVER (ORDER BY position, id) AS position
+                 FROM channels
+                 WHERE guild_id = $2
+                   AND channel_type IN ($3, $4))
+INSERT
+INTO guild_template_channels (template_code, position, name, channel_type, parent_position)
+SELECT $1, c.position, c.name, c.channel_type, p.position
+FROM ordered c
+    LEFT JOIN ordered p ON p.id = c.parent_id
+RETURNING position, name, channel_type, parent_position
+"#,
+        code,
+        bigint_guild_id,
+        ChannelType::Text as i16,
+        ChannelType::Category as i16
+    )
+    .fetch_all(&mut tx)
+    .await?;
+
+    let roles = sqlx::query!(
+        r#"
+INSERT INTO guild_template_roles
+SELECT $1, name, color, position, permissions
+FROM roles
+WHERE parent_guild = $2
+RETURNING name, color, position
+"#,
+        code,
+        bigint_guild_id
+    )
+    .fetch_all(&mut tx)
+    .await?;
+
+    tx.commit().await?;
+
+    let mut channels = channels
+        .into_iter()
+        .map(|x| {
+            (
+                x.position,
+                TemplateChannel {
+                    name: x.name,
+                    channel_type: ChannelType::from_i16(x.channel_type)
+                        .unwrap_or(ChannelType::Text),
+                    parent_position: x.parent_position,
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+    channels.sort_unstable_by_key(|(position, _)| *position);
+
+    Ok(crate::Json {
+        obj: GuildTemplate {
+            code,
+            name: guild.name,
+            source_guild_id: Some(guild_id),
+            creator_id: auth.0,
+            created_at: now,
+            channels: channels.into_iter().map(|(_, c)| c).collect(),
+            roles: roles
+                .into_iter()
+                .map(|x| TemplateRole {
+                    name: x.name,
+                    color: x.color,
+                    position: x.position,
+                    permissions: Permissions::empty(),
+                })
+                .collect(),
+        },
+        code: 201,
+    })
+}
@@ -0,0 +1,66 @@
+use crate::WebServerError;
+use axum::extract::Path;
+use ferrischat_common::perms::Permissions;
+use ferrischat_common::types::{
+    ChannelType, ErrorJson, GuildTemplate, TemplateChannel, TemplateRole,
+};
+
+/// GET `/v0/templates/{code}`
+pub async fn get_template(
+    Path(code): Path<String>,
+    _: crate::Authorization,
+) -> Result<crate::Json<GuildTemplate>, WebServerError> {
+    let db = get_db_or_fail!();
+
+    let template = sqlx::query!("SELECT * FROM guild_templates WHERE code = $1", code)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ErrorJson::new_404(format!("Unknown template with code {}", code)))?;
+
+    let channels = sqlx::query!(
+        "SELECT name, channel_type, parent_position FROM guild_template_channels WHERE template_code = $1 ORDER BY position",
+        code
+    )
+    .fetch_all(db)
+    .await?
+    .into_iter()
+    .map(|x| TemplateChannel {
+        name: x.name,
+        channel_type: ChannelType::from_i16(x.channel_type).unwrap_or(ChannelType::Text),
+        parent_position: x.parent_position,
+    })
+    .collect();
+
+    let roles = sqlx::query!(
+        "SELECT name, color, position FROM guild_template_roles WHERE template_code = $1",
+        code
+    )
+    .fetch_all(db)
+    .await?
+    .into_iter()
+    .map(|x| TemplateRole {
+        name: x.name,
+        color: x.color,
+        position: x.position,
+        permissions: Permissions::empty(),
+    })
+    .collect();
+
+    let source_guild_id = match template.source_guild_id {
+        Some(id) => Some(bigdecimal_to_u128!(id)),
+        None => None,
+    };
+
+    Ok(crate::Json {
+        obj: GuildTemplate {
+            code: template.code,
+            name: template.name,
+            source_guild_id,
+            creator_id: bigdecimal_to_u128!(template.creator_id),
+            created_at: template.created_at,
+            channels,
+            roles,
+        },
+        code: 200,
+    })
+}
@@ -0,0 +1,20 @@
+mod create_template;
+mod get_template;
+
+pub use create_template::*;
+pub use get_template::*;
+
+use axum::routing::{get, post};
+use axum::Router;
+
+pub fn generate_templates_routes() -> axum::Router {
+    debug!("generating routes for templates");
+    Router::new()
+        // POST   /guilds/:guild_id/templates
+        .route(
+            expand_version!("guilds/:guild_id/templates"),
+            post(create_template),
+        )
+        // GET    /templates/:code
+        .route(expand_version!("templates/:code"), get(get_template))
+}
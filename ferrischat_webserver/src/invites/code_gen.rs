@@ -0,0 +1,61 @@
+//! Generates invite codes and validates vanity codes
+//!
+
+use ring::rand::SecureRandom;
+
+const INVITE_CODE_ALPHABET: &[u8] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+/// Largest multiple of the alphabet length (62 * 4) that fits in a byte.
+/// Bytes at or above this are thrown away so every character is equally likely.
+const REJECTION_THRESHOLD: u8 = 248;
+
+/// Minimum and maximum length of a vanity invite code.
+pub const VANITY_CODE_LENGTH: std::ops::RangeInclusive<usize> = 3..=32;
+
+/// Length of randomly generated invite codes, as set in the config.
+pub fn invite_code_length() -> usize {
+    ferrischat_config::GLOBAL_CONFIG
+        .get()
+        .map_or(10, |cfg| cfg.invites.code_length)
+}
+
+/// Generate a random invite code of `len` characters using the global RNG.
+///
+/// Returns `None` if the RNG is unset or failed to generate random bytes.
+pub fn generate_invite_code(len: usize) -> Option<String> {
+    let rng = crate::RNG_CORE.get()?;
+    let mut code = String::with_capacity(len);
+    let mut buf = vec![0; len * 2];
+
+    while code.len() < len {
+        rng.fill(&mut buf).ok()?;
+        code.extend(
+            buf.iter()
+                .filter(|b| **b < REJECTION_THRESHOLD)
+                .map(|b| {
+                    char::from(INVITE_CODE_ALPHABET[usize::from(*b) % INVITE_CODE_ALPHABET.len()])
+                })
+                .take(len - code.len()),
+        );
+    }
+
+    Some(code)
+}
+
+/// Check a vanity code is usable, returning a reason if it is not.
+pub fn validate_vanity_code(code: &str) -> Result<(), String> {
+    if !VANITY_CODE_LENGTH.contains(&code.len()) {
+        return Err(format!(
+            "vanity codes must be between {} and {} characters long",
+            VANITY_CODE_LENGTH.start(),
+            VANITY_CODE_LENGTH.end()
+        ));
+    }
+    if !code.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-') {
+        return Err("vanity codes may only contain ASCII letters, digits and `-`".to_string());
+    }
+    if code.starts_with('-') || code.ends_with('-') {
+        return Err("vanity codes cannot start or end with `-`".to_string());
+    }
+    Ok(())
+}
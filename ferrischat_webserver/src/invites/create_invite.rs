@@ -1,3 +1,4 @@
+use crate::invites::{generate_invite_code, invite_code_length};
 use crate::ws::fire_event;
 use crate::WebServerError;
 use axum::extract::Path;
@@ -33,24 +34,44 @@ pub async fn create_invite(
     }
 
     let now = OffsetDateTime::now_utc().unix_timestamp();
-    let resp = sqlx::query!(
-        "INSERT INTO invites VALUES ((SELECT array_to_string( \
-            ARRAY(SELECT substr( \
-                'ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789', \
-                ((random()*(36-1)+1)::integer),1) FROM generate_series(1,10)),'') \
-            ), $1, $2, $3, $4, $5, $6) RETURNING code",
-        bigint_owner_id,
-        bigint_guild_id,
-        now,
-        0,
-        max_uses,
-        max_age
-    )
-    .fetch_one(db)
-    .await?;
+    let code_length = invite_code_length();
+
+    // codes are random enough that a collision is extremely unlikely,
+    // but retry a few times rather than failing the request if one happens
+    let mut attempts = 0;
+    let code = loop {
+        let code =
+            generate_invite_code(code_length).ok_or(WebServerError::RandomGenerationFailure)?;
+
+        let res = sqlx::query!(
+            "INSERT INTO invites VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT DO NOTHING RETURNING code",
+            code,
+            bigint_owner_id,
+            bigint_guild_id,
+            now,
+            0,
+            max_uses,
+            max_age
+        )
+        .fetch_optional(db)
+        .await?;
+
+        match res {
+            Some(r) => break r.code,
+            None if attempts < 5 => attempts += 1,
+            None => {
+                return Err(ErrorJson::new_500(
+                    "failed to generate a unique invite code".to_string(),
+                    false,
+                    None,
+                )
+                .into())
+            }
+        }
+    };
 
     let invite_obj = Invite {
-        code: resp.code,
+        code,
         owner_id,
         guild_id,
         created_at: now,
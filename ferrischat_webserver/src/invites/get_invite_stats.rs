@@ -0,0 +1,61 @@
+use crate::WebServerError;
+use axum::extract::Path;
+use ferrischat_common::types::{ErrorJson, InviteStats};
+
+/// GET `/v0/guilds/{guild_id}/invites/stats`
+pub async fn get_invite_stats(
+    Path(guild_id): Path<u128>,
+    crate::Authorization(authorized_user): crate::Authorization,
+) -> Result<crate::Json<Vec<InviteStats>>, WebServerError> {
+    let db = get_db_or_fail!();
+    let bigint_guild_id = u128_to_bigdecimal!(guild_id);
+    let bigint_authed_user = u128_to_bigdecimal!(authorized_user);
+
+    if sqlx::query!(
+        "SELECT user_id FROM members WHERE user_id = $1 AND guild_id = $2",
+        bigint_authed_user,
+        bigint_guild_id
+    )
+    .fetch_optional(db)
+    .await?
+    .is_none()
+    {
+        return Err(ErrorJson::new_403("you are not a member of this guild".to_string()).into());
+    }
+
+    // invites are deleted once they expire, so count from the members table instead:
+    // this keeps the stats for invites that no longer exist
+    let resp = sqlx::query!(
+        r#"
+SELECT invite_code AS "invite_code!",
+       inviter_id,
+       count(*) AS "joins!"
+FROM members
+WHERE guild_id = $1
+  AND invite_code IS NOT NULL
+GROUP BY invite_code, inviter_id
+ORDER BY "joins!" DESC
+"#,
+        bigint_guild_id
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut stats = Vec::with_capacity(resp.len());
+    for x in resp {
+        let inviter_id = match x.inviter_id {
+            Some(inviter_id) => Some(bigdecimal_to_u128!(inviter_id)),
+            None => None,
+        };
+        stats.push(InviteStats {
+            code: x.invite_code,
+            inviter_id,
+            joins: x.joins,
+        });
+    }
+
+    Ok(crate::Json {
+        obj: stats,
+        code: 200,
+    })
+}
@@ -1,11 +1,15 @@
+mod code_gen;
 mod create_invite;
 mod get_guild_invites;
 mod get_invite;
+mod get_invite_stats;
 mod use_invite;
 
+pub use code_gen::*;
 pub use create_invite::*;
 pub use get_guild_invites::*;
 pub use get_invite::*;
+pub use get_invite_stats::*;
 pub use use_invite::*;
 
 use axum::routing::{get, post};
@@ -20,6 +24,11 @@ pub fn generate_invites_routes() -> axum::Router {
             expand_version!("guilds/:guild_id/invites"),
             post(create_invite).get(get_guild_invites),
         )
+        // GET    /guilds/:guild_id/invites/stats
+        .route(
+            expand_version!("guilds/:guild_id/invites/stats"),
+            get(get_invite_stats),
+        )
         // GET    /invites/:code
         // POST   /invites/:code
         .route(
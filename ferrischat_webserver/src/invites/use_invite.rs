@@ -6,8 +6,6 @@ use ferrischat_common::ws::WsOutboundEvent;
 use sqlx::types::time::OffsetDateTime;
 use sqlx::types::BigDecimal;
 
-const FERRIS_EPOCH: i64 = 1_577_836_800_000;
-
 pub async fn use_invite(
     Path(invite_code): Path<String>,
     crate::Authorization(user_id): crate::Authorization,
@@ -35,8 +33,8 @@ pub async fn use_invite(
     let bigint_guild_id: BigDecimal = invite.guild_id;
     let guild_id = bigdecimal_to_u128!(bigint_guild_id);
     let uses = invite.uses + 1;
-    let unix_timestamp = OffsetDateTime::now_utc().unix_timestamp();
-    let now = unix_timestamp - FERRIS_EPOCH;
+    // invites are timestamped in plain Unix seconds, not snowflake time
+    let now = OffsetDateTime::now_utc().unix_timestamp();
     let mut to_delete = false;
     if let Some(max_uses) = invite.max_uses {
         if uses > max_uses.into() {
@@ -82,12 +80,19 @@ pub async fn use_invite(
         return Err(ErrorJson::new_409("user has already joined this guild".to_string()).into());
     };
 
-    sqlx::query!(
-        "INSERT INTO members VALUES ($1, $2)",
+    // members of guilds with rules set start out pending until they accept them
+    let member = sqlx::query!(
+        r#"
+INSERT INTO members (user_id, guild_id, invite_code, inviter_id, pending)
+VALUES ($1, $2, $3, $4, (SELECT rules IS NOT NULL FROM guilds WHERE id = $2))
+RETURNING joined_at, pending
+"#,
         bigint_user_id,
-        bigint_guild_id
+        bigint_guild_id,
+        invite_code,
+        invite.owner_id
     )
-    .execute(db)
+    .fetch_one(db)
     .await?;
 
     let member_obj = Member {
@@ -110,6 +115,10 @@ pub async fn use_invite(
         }),
         guild_id: Some(guild_id),
         guild: None,
+        joined_at: Some(member.joined_at),
+        invite_code: Some(invite_code.clone()),
+        inviter_id: Some(bigdecimal_to_u128!(invite.owner_id)),
+        pending: Some(member.pending),
     };
 
     sqlx::query!(
@@ -23,7 +23,9 @@ extern crate ferrischat_macros;
 extern crate tracing;
 
 mod auth;
+mod avatars;
 mod channels;
+mod discovery;
 mod entrypoint;
 mod errors;
 mod guilds;
@@ -32,6 +34,11 @@ mod json_response;
 mod members;
 mod messages;
 mod not_implemented;
+mod periodic;
+mod snowflake;
+mod storage;
+mod threads;
+mod unfurl;
 mod users;
 mod ws;
 
@@ -3,6 +3,7 @@ use crate::WebServerError;
 use axum::extract::Path;
 use ferrischat_common::types::{ErrorJson, Member};
 use ferrischat_common::ws::WsOutboundEvent;
+use num_traits::ToPrimitive;
 
 /// DELETE `/v0/guilds/{guild_id}/members/{member_id}`
 pub async fn delete_member(
@@ -32,11 +33,17 @@ pub async fn delete_member(
     )
     .fetch_optional(db)
     .await?
-    .map(|_| Member {
+    .map(|m| Member {
         user_id: Some(member_id),
         user: None,
         guild_id: Some(guild_id),
         guild: None,
+        joined_at: Some(m.joined_at),
+        invite_code: m.invite_code,
+        inviter_id: m
+            .inviter_id
+            .and_then(|x| x.with_scale(0).into_bigint_and_exponent().0.to_u128()),
+        pending: Some(m.pending),
     })
     .ok_or_else(|| {
         ErrorJson::new_404(format!(
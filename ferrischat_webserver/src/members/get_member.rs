@@ -12,17 +12,14 @@ pub async fn get_member(
 
     let db = get_db_or_fail!();
 
-    if !(sqlx::query!(
-        r#"SELECT EXISTS(SELECT * FROM members WHERE user_id = $1 AND guild_id = $2) AS "exists!""#,
+    let member = sqlx::query!(
+        "SELECT * FROM members WHERE user_id = $1 AND guild_id = $2",
         bigint_member_id,
         bigint_guild_id
     )
-    .fetch_one(db)
+    .fetch_optional(db)
     .await?
-    .exists)
-    {
-        return Err(ErrorJson::new_404(format!("Unknown member with ID {}", member_id)).into());
-    };
+    .ok_or_else(|| ErrorJson::new_404(format!("Unknown member with ID {}", member_id)))?;
 
     let user = sqlx::query!("SELECT * FROM users WHERE id = $1", bigint_member_id)
         .fetch_optional(db)
@@ -39,11 +36,20 @@ pub async fn get_member(
                 .and_then(ferrischat_common::types::Pronouns::from_i16),
         });
 
+    let inviter_id = match member.inviter_id {
+        Some(inviter_id) => Some(bigdecimal_to_u128!(inviter_id)),
+        None => None,
+    };
+
     let member_obj = Member {
         user_id: Some(member_id),
         user,
         guild_id: Some(guild_id),
         guild: None,
+        joined_at: Some(member.joined_at),
+        invite_code: member.invite_code,
+        inviter_id,
+        pending: Some(member.pending),
     };
 
     Ok(crate::Json {
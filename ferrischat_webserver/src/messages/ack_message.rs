@@ -0,0 +1,90 @@
+use crate::channels::dm_channels::check_dm_recipient;
+use crate::ws::fire_event;
+use crate::WebServerError;
+use axum::extract::Path;
+use ferrischat_common::types::{ErrorJson, ReadState};
+use ferrischat_common::ws::WsOutboundEvent;
+
+/// POST `/v0/channels/{channel_id}/messages/{message_id}/ack`
+///
+/// Marks the channel as read up to and including this message, and clears its mention count.
+/// Acking an older message than the last one marks the channel as unread again from there.
+pub async fn ack_message(
+    Path((channel_id, message_id)): Path<(u128, u128)>,
+    auth: crate::Authorization,
+) -> Result<crate::Json<ReadState>, WebServerError> {
+    let db = get_db_or_fail!();
+    let bigint_channel_id = u128_to_bigdecimal!(channel_id);
+    let bigint_message_id = u128_to_bigdecimal!(message_id);
+    let bigint_user_id = u128_to_bigdecimal!(auth.0);
+
+    let channel = sqlx::query!(
+        "SELECT guild_id FROM channels WHERE id = $1",
+        bigint_channel_id
+    )
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| ErrorJson::new_404("channel not found".to_string()))?;
+
+    match channel.guild_id {
+        Some(guild_id) => {
+            if !sqlx::query!(
+                r#"SELECT EXISTS(SELECT * FROM members WHERE guild_id = $1 AND user_id = $2) AS "exists!""#,
+                guild_id,
+                bigint_user_id
+            )
+            .fetch_one(db)
+            .await?
+            .exists
+            {
+                return Err(ErrorJson::new_404("channel not found".to_string()).into());
+            }
+        }
+        None => check_dm_recipient(db, channel_id, auth.0).await?,
+    }
+
+    if !sqlx::query!(
+        r#"SELECT EXISTS(SELECT * FROM messages WHERE id = $1 AND channel_id = $2) AS "exists!""#,
+        bigint_message_id,
+        bigint_channel_id
+    )
+    .fetch_one(db)
+    .await?
+    .exists
+    {
+        return Err(ErrorJson::new_404(format!("Unknown message with ID {}", message_id)).into());
+    }
+
+    sqlx::query!(
+        r#"
+INSERT INTO read_states (user_id, channel_id, last_message_id)
+VALUES ($1, $2, $3)
+ON CONFLICT (user_id, channel_id) DO UPDATE SET last_message_id = $3,
+                                               mention_count   = 0
+"#,
+        bigint_user_id,
+        bigint_channel_id,
+        bigint_message_id
+    )
+    .execute(db)
+    .await?;
+
+    let read_state = ReadState {
+        channel_id,
+        last_message_id: Some(message_id),
+        mention_count: 0,
+    };
+
+    // keep the user's other sessions in sync
+    let event = WsOutboundEvent::MessageAck {
+        user_id: auth.0,
+        read_state: read_state.clone(),
+    };
+
+    fire_event(&event).await?;
+
+    Ok(crate::Json {
+        obj: read_state,
+        code: 200,
+    })
+}
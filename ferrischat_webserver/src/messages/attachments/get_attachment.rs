@@ -0,0 +1,90 @@
+use crate::channels::dm_channels::check_dm_recipient;
+use crate::storage::{attachment_key, get_storage};
+use crate::WebServerError;
+use axum::extract::Path;
+use bytes::Bytes;
+use ferrischat_common::types::ErrorJson;
+use http::header::{CONTENT_DISPOSITION, CONTENT_TYPE, X_CONTENT_TYPE_OPTIONS};
+use http::{HeaderMap, HeaderValue};
+
+/// GET `/v0/channels/{channel_id}/attachments/{attachment_id}`
+///
+/// Only files that were recognised as images are shown inline. Everything else is sent as a download.
+pub async fn get_attachment(
+    Path((channel_id, attachment_id)): Path<(u128, u128)>,
+    auth: crate::Authorization,
+) -> Result<(HeaderMap, Bytes), WebServerError> {
+    let db = get_db_or_fail!();
+
+    let attachment = sqlx::query!(
+        r#"
+SELECT a.filename,
+       a.content_type,
+       a.width,
+       c.guild_id
+FROM attachments a
+    INNER JOIN messages m ON m.id = a.message_id
+    INNER JOIN channels c ON c.id = m.channel_id
+WHERE a.id = $1
+  AND m.channel_id = $2
+"#,
+        u128_to_bigdecimal!(attachment_id),
+        u128_to_bigdecimal!(channel_id)
+    )
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| ErrorJson::new_404(format!("Unknown attachment with ID {}", attachment_id)))?;
+
+    match attachment.guild_id {
+        Some(guild_id) => {
+            if !sqlx::query!(
+                r#"SELECT EXISTS(SELECT * FROM members WHERE guild_id = $1 AND user_id = $2) AS "exists!""#,
+                guild_id,
+                u128_to_bigdecimal!(auth.0)
+            )
+            .fetch_one(db)
+            .await?
+            .exists
+            {
+                return Err(ErrorJson::new_404(format!(
+                    "Unknown attachment with ID {}",
+                    attachment_id
+                ))
+                .into());
+            }
+        }
+        // only recipients can see DMs
+        None => check_dm_recipient(db, channel_id, auth.0).await?,
+    }
+
+    let data = get_storage()?
+        .get(&attachment_key(attachment_id))
+        .await?
+        .ok_or_else(|| {
+            ErrorJson::new_404(format!("Unknown attachment with ID {}", attachment_id))
+        })?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_str(&attachment.content_type)
+            .unwrap_or_else(|_| HeaderValue::from_static("application/octet-stream")),
+    );
+    headers.insert(X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+    let disposition = if attachment.width.is_some() {
+        "inline"
+    } else {
+        "attachment"
+    };
+    headers.insert(
+        CONTENT_DISPOSITION,
+        HeaderValue::from_str(&format!(
+            "{}; filename*=UTF-8''{}",
+            disposition,
+            urlencoding::encode(&attachment.filename)
+        ))
+        .unwrap_or_else(|_| HeaderValue::from_static("attachment")),
+    );
+
+    Ok((headers, data))
+}
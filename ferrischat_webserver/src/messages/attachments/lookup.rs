@@ -0,0 +1,60 @@
+use crate::WebServerError;
+use ferrischat_common::types::Attachment;
+use num_traits::ToPrimitive;
+use std::collections::HashMap;
+
+/// Where an attachment can be downloaded from.
+pub fn attachment_url(channel_id: u128, attachment_id: u128) -> String {
+    format!(
+        "/v{}/channels/{}/attachments/{}",
+        crate::API_VERSION,
+        channel_id,
+        attachment_id
+    )
+}
+
+/// Fetches the attachments on each of these messages, in one query.
+///
+/// Messages without any attachments are left out. Attachments are in the order they were uploaded.
+pub async fn load_attachments(
+    db: &sqlx::PgPool,
+    message_ids: &[u128],
+) -> Result<HashMap<u128, Vec<Attachment>>, WebServerError> {
+    let mut bigint_message_ids = Vec::with_capacity(message_ids.len());
+    for id in message_ids {
+        bigint_message_ids.push(u128_to_bigdecimal!(*id));
+    }
+
+    let resp = sqlx::query!(
+        r#"
+SELECT a.*,
+       m.channel_id
+FROM attachments a
+    INNER JOIN messages m ON m.id = a.message_id
+WHERE a.message_id = ANY ($1)
+ORDER BY a.id
+"#,
+        &bigint_message_ids[..]
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut attachments: HashMap<u128, Vec<Attachment>> = HashMap::new();
+    for x in resp {
+        let id = bigdecimal_to_u128!(x.id);
+        attachments
+            .entry(bigdecimal_to_u128!(x.message_id))
+            .or_default()
+            .push(Attachment {
+                id,
+                filename: x.filename,
+                size: x.size.to_u64().unwrap_or_default(),
+                content_type: x.content_type,
+                width: x.width.and_then(|w| w.to_u32()),
+                height: x.height.and_then(|h| h.to_u32()),
+                url: attachment_url(bigdecimal_to_u128!(x.channel_id), id),
+            });
+    }
+
+    Ok(attachments)
+}
@@ -0,0 +1,20 @@
+mod get_attachment;
+mod lookup;
+mod upload;
+
+pub use get_attachment::*;
+pub use lookup::*;
+pub use upload::*;
+
+use axum::routing::get;
+use axum::Router;
+
+pub fn generate_attachments_routes() -> axum::Router {
+    debug!("generating routes for attachments");
+    Router::new()
+        // GET    /channels/:channel_id/attachments/:attachment_id
+        .route(
+            expand_version!("channels/:channel_id/attachments/:attachment_id"),
+            get(get_attachment),
+        )
+}
@@ -0,0 +1,188 @@
+use super::attachment_url;
+use crate::storage::{attachment_key, delete_attachment_files, get_storage};
+use crate::WebServerError;
+use axum::body::Body;
+use axum::extract::multipart::MultipartError;
+use axum::extract::{FromRequest, Multipart, RequestParts};
+use axum::Json;
+use bytes::{Bytes, BytesMut};
+use ferrischat_common::request_json::MessageCreateJson;
+use ferrischat_common::types::{Attachment, ErrorJson, ModelType};
+use ferrischat_snowflake_generator::generate_snowflake;
+
+/// Longest filename kept for an attachment, in characters.
+pub const MAX_FILENAME_LENGTH: usize = 255;
+
+/// A file sent along with a new message that hasn't been stored yet.
+pub struct PendingAttachment {
+    pub filename: String,
+    pub content_type: String,
+    pub data: Bytes,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+/// The body of a new message.
+///
+/// This is either plain JSON, or `multipart/form-data` with the JSON in a `payload_json` field
+/// and a file in every other field.
+pub struct MessageCreateBody {
+    pub json: MessageCreateJson,
+    pub files: Vec<PendingAttachment>,
+}
+
+fn multipart_error(e: MultipartError) -> ErrorJson {
+    ErrorJson::new_400(format!("invalid multipart body: {}", e))
+}
+
+/// Strips any directories and control characters from a client-supplied filename.
+fn sanitize_filename(filename: Option<&str>) -> String {
+    let filename: String = filename
+        .and_then(|f| f.rsplit(&['/', '\\'][..]).next())
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control() && *c != '"')
+        .take(MAX_FILENAME_LENGTH)
+        .collect();
+    let filename = filename.trim();
+    if filename.is_empty() || filename == "." || filename == ".." {
+        "file".to_string()
+    } else {
+        filename.to_string()
+    }
+}
+
+#[async_trait::async_trait]
+impl FromRequest<Body> for MessageCreateBody {
+    type Rejection = WebServerError;
+
+    async fn from_request(req: &mut RequestParts<Body>) -> Result<Self, Self::Rejection> {
+        let is_multipart = req
+            .headers()
+            .and_then(|h| h.get(http::header::CONTENT_TYPE))
+            .and_then(|v| v.to_str().ok())
+            .map_or(false, |v| v.starts_with("multipart/form-data"));
+        if !is_multipart {
+            let json = Json::<MessageCreateJson>::from_request(req)
+                .await
+                .map_err(|e| ErrorJson::new_400(format!("invalid JSON body: {}", e)))?;
+            return Ok(Self {
+                json: json.0,
+                files: vec![],
+            });
+        }
+
+        let (max_size, max_files) = ferrischat_config::GLOBAL_CONFIG
+            .get()
+            .map_or((25 * 1024 * 1024, 10), |cfg| {
+                (cfg.attachments.max_size, cfg.attachments.max_per_message)
+            });
+
+        let mut multipart = Multipart::from_request(req)
+            .await
+            .map_err(|e| ErrorJson::new_400(format!("invalid multipart body: {}", e)))?;
+
+        let mut json = None;
+        let mut files = Vec::new();
+        while let Some(mut field) = multipart.next_field().await.map_err(multipart_error)? {
+            if field.name() == Some("payload_json") {
+                let mut data = field.bytes().await.map_err(multipart_error)?.to_vec();
+                json = Some(
+                    simd_json::from_slice::<MessageCreateJson>(&mut data[..])
+                        .map_err(|e| ErrorJson::new_400(format!("invalid payload_json: {}", e)))?,
+                );
+                continue;
+            }
+
+            if files.len() == max_files {
+                return Err(ErrorJson::new_400(format!(
+                    "at most {} files can be attached to a message",
+                    max_files
+                ))
+                .into());
+            }
+
+            let filename = sanitize_filename(field.file_name().or_else(|| field.name()));
+            let content_type = field.content_type().map_or_else(
+                || "application/octet-stream".to_string(),
+                ToString::to_string,
+            );
+
+            // read it a chunk at a time so oversized files are turned away without buffering them
+            let mut data = BytesMut::new();
+            while let Some(chunk) = field.chunk().await.map_err(multipart_error)? {
+                if data.len() + chunk.len() > max_size {
+                    return Err(ErrorJson::new_400(format!(
+                        "{} is larger than the {} byte limit",
+                        filename, max_size
+                    ))
+                    .into());
+                }
+                data.extend_from_slice(&chunk);
+            }
+
+            let (width, height) = if content_type.starts_with("image/") {
+                match imagesize::blob_size(&data) {
+                    Ok(size) => (
+                        u32::try_from(size.width).ok(),
+                        u32::try_from(size.height).ok(),
+                    ),
+                    Err(_) => (None, None),
+                }
+            } else {
+                (None, None)
+            };
+
+            files.push(PendingAttachment {
+                filename,
+                content_type,
+                data: data.freeze(),
+                width,
+                height,
+            });
+        }
+
+        let json = json.ok_or_else(|| {
+            ErrorJson::new_400("multipart bodies need a payload_json field".to_string())
+        })?;
+        Ok(Self { json, files })
+    }
+}
+
+/// Puts these files in storage and gives them IDs.
+///
+/// If any of them can't be stored, the ones that were are removed again.
+pub async fn store_attachments(
+    channel_id: u128,
+    files: Vec<PendingAttachment>,
+) -> Result<Vec<Attachment>, WebServerError> {
+    if files.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let storage = get_storage()?;
+    let node_id = get_node_id!();
+
+    let mut attachments: Vec<Attachment> = Vec::with_capacity(files.len());
+    for file in files {
+        let id = generate_snowflake::<0>(ModelType::Attachment as u8, node_id);
+        let size = file.data.len() as u64;
+
+        if let Err(e) = storage.put(&attachment_key(id), file.data).await {
+            delete_attachment_files(attachments.iter().map(|a| a.id).collect()).await;
+            return Err(e.into());
+        }
+
+        attachments.push(Attachment {
+            id,
+            filename: file.filename,
+            size,
+            content_type: file.content_type,
+            width: file.width,
+            height: file.height,
+            url: attachment_url(channel_id, id),
+        });
+    }
+
+    Ok(attachments)
+}
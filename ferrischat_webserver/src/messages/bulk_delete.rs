@@ -0,0 +1,185 @@
+use crate::channels::pins::fire_pins_update;
+use crate::guilds::member_has_permissions;
+use crate::storage::delete_attachment_files;
+use crate::ws::fire_event;
+use crate::WebServerError;
+use axum::extract::Path;
+use axum::Json;
+use ferrischat_common::perms::Permissions;
+use ferrischat_common::request_json::BulkDeleteMessagesJson;
+use ferrischat_common::types::{Channel, ChannelType, ErrorJson};
+use ferrischat_common::ws::WsOutboundEvent;
+
+/// Maximum number of messages that can be deleted in one request, as set in the config.
+pub fn max_bulk_delete() -> usize {
+    ferrischat_config::GLOBAL_CONFIG
+        .get()
+        .map_or(100, |cfg| cfg.messages.max_bulk_delete)
+}
+
+/// POST `/v0/channels/{channel_id}/messages/bulk-delete`
+///
+/// Deletes either the messages in `message_ids`, or the last `limit` messages sent by `author_id`.
+/// IDs that don't exist in the channel are ignored.
+pub async fn bulk_delete_messages(
+    Path(channel_id): Path<u128>,
+    Json(BulkDeleteMessagesJson {
+        message_ids,
+        author_id,
+        limit,
+    }): Json<BulkDeleteMessagesJson>,
+    auth: crate::Authorization,
+) -> Result<http::StatusCode, WebServerError> {
+    let bigint_channel_id = u128_to_bigdecimal!(channel_id);
+
+    let db = get_db_or_fail!();
+
+    let max = max_bulk_delete();
+    let limit = match (&message_ids, author_id, limit) {
+        (Some(ids), None, None) => {
+            if ids.is_empty() || ids.len() > max {
+                return Err(ErrorJson::new_400(format!(
+                    "between 1 and {} messages can be deleted at once",
+                    max
+                ))
+                .into());
+            }
+            ids.len()
+        }
+        (None, Some(_), Some(limit)) => {
+            if limit == 0 || limit > max {
+                return Err(
+                    ErrorJson::new_400(format!("limit must be between 1 and {}", max)).into(),
+                );
+            }
+            limit
+        }
+        _ => {
+            return Err(ErrorJson::new_400(
+                "either message_ids, or both author_id and limit, must be given".to_string(),
+            )
+            .into())
+        }
+    };
+
+    let channel = sqlx::query!("SELECT * FROM channels WHERE id = $1", bigint_channel_id)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ErrorJson::new_404(format!("Unknown channel with ID {}", channel_id)))?;
+
+    // nobody moderates DMs
+    let guild_id = channel
+        .guild_id
+        .ok_or_else(|| ErrorJson::new_400("messages can't be bulk deleted in DMs".to_string()))?;
+    if !member_has_permissions(
+        db,
+        &guild_id,
+        &u128_to_bigdecimal!(auth.0),
+        Permissions::MANAGE_MESSAGES,
+    )
+    .await?
+    {
+        return Err(ErrorJson::new_403(
+            "you need the manage messages permission to bulk delete messages".to_string(),
+        )
+        .into());
+    }
+
+    let message_ids = message_ids.map(|ids| {
+        ids.into_iter()
+            .map(|id| u128_to_bigdecimal!(id))
+            .collect::<Vec<_>>()
+    });
+
+    let mut tx = db.begin().await?;
+
+    let to_delete = sqlx::query!(
+        r#"
+SELECT id
+FROM messages
+WHERE channel_id = $1
+  AND ($2::numeric(39)[] IS NULL OR id = ANY ($2))
+  AND ($3::numeric(39) IS NULL OR author_id = $3)
+ORDER BY id DESC
+LIMIT $4
+FOR UPDATE
+"#,
+        bigint_channel_id,
+        message_ids.as_deref(),
+        author_id.map(|id| u128_to_bigdecimal!(id)),
+        i64::try_from(limit).unwrap_or(i64::MAX)
+    )
+    .fetch_all(&mut tx)
+    .await?
+    .into_iter()
+    .map(|x| x.id)
+    .collect::<Vec<_>>();
+
+    if to_delete.is_empty() {
+        return Ok(http::StatusCode::NO_CONTENT);
+    }
+
+    let attachment_ids = sqlx::query!(
+        "SELECT id FROM attachments WHERE message_id = ANY ($1)",
+        &to_delete
+    )
+    .fetch_all(&mut tx)
+    .await?;
+
+    let were_pinned = !sqlx::query!(
+        "DELETE FROM pins WHERE message_id = ANY ($1) RETURNING message_id",
+        &to_delete
+    )
+    .fetch_all(&mut tx)
+    .await?
+    .is_empty();
+
+    sqlx::query!(
+        "DELETE FROM messages WHERE id = ANY ($1) AND channel_id = $2",
+        &to_delete,
+        bigint_channel_id
+    )
+    .execute(&mut tx)
+    .await?;
+
+    tx.commit().await?;
+
+    let mut deleted = Vec::with_capacity(to_delete.len());
+    for id in to_delete {
+        deleted.push(bigdecimal_to_u128!(id));
+    }
+    let mut files = Vec::with_capacity(attachment_ids.len());
+    for x in attachment_ids {
+        files.push(bigdecimal_to_u128!(x.id));
+    }
+    delete_attachment_files(files).await;
+
+    let channel_obj = Channel {
+        id: channel_id,
+        name: channel.name,
+        guild_id: Some(bigdecimal_to_u128!(guild_id)),
+        channel_type: ChannelType::from_i16(channel.channel_type).unwrap_or(ChannelType::Text),
+        parent_id: match channel.parent_id {
+            Some(parent_id) => Some(bigdecimal_to_u128!(parent_id)),
+            None => None,
+        },
+        position: channel.position,
+        topic: channel.topic,
+        slowmode: channel.slowmode,
+        nsfw: channel.nsfw,
+        announcement: channel.announcement,
+        message_ttl: channel.message_ttl,
+    };
+
+    let event = WsOutboundEvent::MessageDeleteBulk {
+        channel: channel_obj.clone(),
+        message_ids: deleted,
+    };
+
+    fire_event(&event).await?;
+
+    if were_pinned {
+        fire_pins_update(db, channel_obj).await?;
+    }
+    Ok(http::StatusCode::NO_CONTENT)
+}
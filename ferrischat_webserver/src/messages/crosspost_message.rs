@@ -0,0 +1,207 @@
+use super::{load_embeds, message_expiry, save_message};
+use crate::guilds::member_has_permissions;
+use crate::ws::fire_event;
+use crate::WebServerError;
+use axum::extract::Path;
+use ferrischat_common::perms::Permissions;
+use ferrischat_common::types::{
+    Channel, ChannelType, ErrorJson, Message, MessageReference, ModelType, User, UserFlags,
+};
+use ferrischat_common::ws::WsOutboundEvent;
+use ferrischat_snowflake_generator::generate_snowflake;
+
+/// POST `/v0/channels/{channel_id}/messages/{message_id}/crosspost`
+///
+/// Copies a message from an announcement channel into every channel following it.
+/// The copies keep the original author, and point back to the original message.
+/// Each message can only be crossposted once.
+pub async fn crosspost_message(
+    Path((channel_id, message_id)): Path<(u128, u128)>,
+    auth: crate::Authorization,
+) -> Result<crate::Json<Vec<Message>>, WebServerError> {
+    let db = get_db_or_fail!();
+    let bigint_channel_id = u128_to_bigdecimal!(channel_id);
+    let bigint_message_id = u128_to_bigdecimal!(message_id);
+    let bigint_user_id = u128_to_bigdecimal!(auth.0);
+
+    let channel = sqlx::query!(
+        "SELECT guild_id, announcement FROM channels WHERE id = $1",
+        bigint_channel_id
+    )
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| ErrorJson::new_404(format!("Unknown channel with ID {}", channel_id)))?;
+    let guild_id = match channel.guild_id {
+        Some(guild_id) if channel.announcement => guild_id,
+        _ => {
+            return Err(ErrorJson::new_400(
+                "only messages in announcement channels can be crossposted".to_string(),
+            )
+            .into())
+        }
+    };
+
+    let message = sqlx::query!(
+        r#"
+SELECT m.*,
+       a.name AS author_name,
+       a.avatar AS avatar,
+       a.flags AS author_flags,
+       a.discriminator AS author_discriminator,
+       a.pronouns AS author_pronouns
+FROM messages m
+    CROSS JOIN LATERAL (
+        SELECT *
+        FROM users
+        WHERE id = m.author_id
+        ) AS a
+WHERE m.id = $1
+  AND m.channel_id = $2
+  AND (m.expires_at IS NULL OR m.expires_at > extract(epoch FROM now()))
+"#,
+        bigint_message_id,
+        bigint_channel_id
+    )
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| ErrorJson::new_404(format!("Unknown message with ID {}", message_id)))?;
+
+    if message.author_id != bigint_user_id
+        && !member_has_permissions(db, &guild_id, &bigint_user_id, Permissions::MANAGE_MESSAGES)
+            .await?
+    {
+        return Err(ErrorJson::new_403(
+            "only the author or a moderator can crosspost this message".to_string(),
+        )
+        .into());
+    }
+    if message.crosspost_message_id.is_some() {
+        return Err(ErrorJson::new_400(
+            "crossposted messages can't be crossposted again".to_string(),
+        )
+        .into());
+    }
+    let author_id = bigdecimal_to_u128!(message.author_id);
+    let author = User {
+        id: author_id,
+        name: message.author_name,
+        avatar: message.avatar,
+        guilds: None,
+        flags: UserFlags::from_bits_truncate(message.author_flags),
+        discriminator: message.author_discriminator,
+        pronouns: message
+            .author_pronouns
+            .and_then(ferrischat_common::types::Pronouns::from_i16),
+    };
+    let content = message.content.unwrap_or_default();
+    let embeds = load_embeds(message.embeds)?;
+    let reference = MessageReference {
+        guild_id: Some(bigdecimal_to_u128!(guild_id)),
+        channel_id,
+        message_id,
+    };
+
+    let targets = sqlx::query!(
+        r#"
+SELECT c.*
+FROM channel_followers f
+    INNER JOIN channels c ON c.id = f.target_channel_id
+WHERE f.source_channel_id = $1
+"#,
+        bigint_channel_id
+    )
+    .fetch_all(db)
+    .await?;
+
+    let node_id = get_node_id!();
+    let mut crossposts = Vec::with_capacity(targets.len());
+    for target in targets {
+        let target = Channel {
+            id: bigdecimal_to_u128!(target.id),
+            name: target.name,
+            guild_id: match target.guild_id {
+                Some(guild_id) => Some(bigdecimal_to_u128!(guild_id)),
+                None => None,
+            },
+            channel_type: ChannelType::from_i16(target.channel_type).unwrap_or(ChannelType::Text),
+            parent_id: match target.parent_id {
+                Some(parent_id) => Some(bigdecimal_to_u128!(parent_id)),
+                None => None,
+            },
+            position: target.position,
+            topic: target.topic,
+            slowmode: target.slowmode,
+            nsfw: target.nsfw,
+            announcement: target.announcement,
+            message_ttl: target.message_ttl,
+        };
+        // copies of an expiring message go with it
+        let expires_at = message
+            .expires_at
+            .or_else(|| message_expiry(target.message_ttl));
+
+        crossposts.push(Message {
+            id: generate_snowflake::<0>(ModelType::Message as u8, node_id),
+            content: Some(content.clone()),
+            channel_id: target.id,
+            channel: target,
+            author_id,
+            author: Some(author.clone()),
+            edited_at: None,
+            expires_at,
+            embeds: embeds.clone(),
+            reactions: vec![],
+            // the files stay with the original
+            attachments: vec![],
+            // they point into the original's guild
+            mentions: vec![],
+            mention_roles: vec![],
+            mention_channels: vec![],
+            mention_everyone: false,
+            crossposted_from: Some(reference.clone()),
+            // what it replied to isn't in the follower's channel
+            reply_to: None,
+            nonce: None,
+        });
+    }
+
+    // nothing follows the channel, so there's nothing to mark as crossposted
+    if crossposts.is_empty() {
+        return Ok(crate::Json {
+            obj: crossposts,
+            code: 200,
+        });
+    }
+
+    let mut tx = db.begin().await?;
+    // claiming the message in the same transaction as the copies means concurrent
+    // requests can't both copy it, and a failed copy leaves it unclaimed
+    if sqlx::query!(
+        "UPDATE messages SET crossposted = true WHERE id = $1 AND NOT crossposted RETURNING id",
+        bigint_message_id
+    )
+    .fetch_optional(&mut tx)
+    .await?
+    .is_none()
+    {
+        return Err(
+            ErrorJson::new_409("this message has already been crossposted".to_string()).into(),
+        );
+    }
+    for crosspost in &crossposts {
+        save_message(&mut tx, crosspost).await?;
+    }
+    tx.commit().await?;
+
+    for crosspost in &crossposts {
+        fire_event(&WsOutboundEvent::MessageCreate {
+            message: crosspost.clone(),
+        })
+        .await?;
+    }
+
+    Ok(crate::Json {
+        obj: crossposts,
+        code: 200,
+    })
+}
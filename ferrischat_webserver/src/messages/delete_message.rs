@@ -1,13 +1,19 @@
+use super::attachments::load_attachments;
+use super::reactions::load_reactions;
+use super::{build_message_reference, load_embeds, load_reply, mention_ids_from_row};
+use crate::channels::dm_channels::check_dm_recipient;
+use crate::channels::pins::fire_pins_update;
+use crate::storage::delete_attachment_files;
 use crate::ws::fire_event;
 use crate::WebServerError;
 use axum::extract::Path;
-use ferrischat_common::types::{Channel, ErrorJson, Message, User, UserFlags};
+use ferrischat_common::types::{Channel, ChannelType, ErrorJson, Message, User, UserFlags};
 use ferrischat_common::ws::WsOutboundEvent;
 
 /// DELETE `/v0/channels/{channel_id}/messages/{message_id}`
 pub async fn delete_message(
     Path((channel_id, message_id)): Path<(u128, u128)>,
-    _: crate::Authorization,
+    auth: crate::Authorization,
 ) -> Result<http::StatusCode, WebServerError> {
     let bigint_message_id = u128_to_bigdecimal!(message_id);
     let bigint_channel_id = u128_to_bigdecimal!(channel_id);
@@ -19,6 +25,11 @@ pub async fn delete_message(
         .await?
         .ok_or_else(|| ErrorJson::new_404(format!("Unknown channel with ID {}", channel_id)))?;
 
+    // only recipients can see DMs
+    if channel.guild_id.is_none() {
+        check_dm_recipient(db, channel_id, auth.0).await?;
+    }
+
     let message = sqlx::query!(
         r#"
 SELECT m.*,
@@ -43,13 +54,42 @@ WHERE m.id = $1
     .await?
     .ok_or_else(|| ErrorJson::new_404(format!("Unknown message with ID {}", message_id)))?;
 
+    // nobody moderates DMs, so recipients can only delete their own messages
+    if channel.guild_id.is_none() && message.author_id != u128_to_bigdecimal!(auth.0) {
+        return Err(
+            ErrorJson::new_403("you can only delete your own messages in DMs".to_string()).into(),
+        );
+    }
+
     let channel_obj = Channel {
         name: channel.name,
         id: channel_id,
-        guild_id: bigdecimal_to_u128!(channel.guild_id),
+        guild_id: match channel.guild_id {
+            Some(guild_id) => Some(bigdecimal_to_u128!(guild_id)),
+            None => None,
+        },
+        channel_type: ChannelType::from_i16(channel.channel_type).unwrap_or(ChannelType::Text),
+        parent_id: match channel.parent_id {
+            Some(parent_id) => Some(bigdecimal_to_u128!(parent_id)),
+            None => None,
+        },
+        position: channel.position,
+        topic: channel.topic,
+        slowmode: channel.slowmode,
+        nsfw: channel.nsfw,
+        announcement: channel.announcement,
+        message_ttl: channel.message_ttl,
     };
 
     let author_id = bigdecimal_to_u128!(message.author_id);
+    let reactions = load_reactions(db, &[message_id], auth.0)
+        .await?
+        .remove(&message_id)
+        .unwrap_or_default();
+    let attachments = load_attachments(db, &[message_id])
+        .await?
+        .remove(&message_id)
+        .unwrap_or_default();
 
     let msg_obj = Message {
         id: message_id,
@@ -58,7 +98,28 @@ WHERE m.id = $1
         author_id,
         content: message.content,
         edited_at: message.edited_at,
-        embeds: vec![],
+        expires_at: message.expires_at,
+        embeds: load_embeds(message.embeds)?,
+        mentions: mention_ids_from_row(message.mentions)?,
+        mention_roles: mention_ids_from_row(message.mention_roles)?,
+        mention_channels: mention_ids_from_row(message.mention_channels)?,
+        mention_everyone: message.mention_everyone,
+        reactions,
+        attachments,
+        crossposted_from: build_message_reference(
+            message.crosspost_guild_id,
+            message.crosspost_channel_id,
+            message.crosspost_message_id,
+        )?,
+        reply_to: load_reply(
+            db,
+            match message.reply_to_id {
+                Some(reply_to_id) => Some(bigdecimal_to_u128!(reply_to_id)),
+                None => None,
+            },
+            message.reply_mention_author,
+        )
+        .await?,
         author: Some(User {
             id: author_id,
             name: message.author_name,
@@ -73,18 +134,36 @@ WHERE m.id = $1
         nonce: None,
     };
 
+    let mut tx = db.begin().await?;
+
+    let was_pinned = sqlx::query!(
+        "DELETE FROM pins WHERE message_id = $1 RETURNING message_id",
+        bigint_message_id
+    )
+    .fetch_optional(&mut tx)
+    .await?
+    .is_some();
+
     sqlx::query!(
         "DELETE FROM messages WHERE id = $1 AND channel_id = $2",
         bigint_message_id,
         bigint_channel_id
     )
-    .execute(db)
+    .execute(&mut tx)
     .await?;
 
+    tx.commit().await?;
+
+    delete_attachment_files(msg_obj.attachments.iter().map(|a| a.id).collect()).await;
+
     let event = WsOutboundEvent::MessageDelete {
         message: msg_obj.clone(),
     };
 
     fire_event(&event).await?;
+
+    if was_pinned {
+        fire_pins_update(db, msg_obj.channel).await?;
+    }
     Ok(http::StatusCode::NO_CONTENT)
 }
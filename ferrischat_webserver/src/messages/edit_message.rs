@@ -1,14 +1,25 @@
+use super::attachments::load_attachments;
+use super::reactions::load_reactions;
+use super::revisions::record_revision;
+use super::{
+    load_embeds, load_reply, mention_ids_from_row, mention_ids_to_row, parse_mentions,
+    resolve_mentions, validate_embeds,
+};
+use crate::channels::dm_channels::check_dm_recipient;
 use crate::ws::fire_event;
 use crate::WebServerError;
 use axum::extract::Path;
 use axum::Json;
 use ferrischat_common::request_json::MessageUpdateJson;
-use ferrischat_common::types::{Channel, ErrorJson, Message, User, UserFlags};
+use ferrischat_common::types::{Channel, ChannelType, ErrorJson, Message, User, UserFlags};
 use ferrischat_common::ws::WsOutboundEvent;
 
+/// PATCH `/v0/channels/{channel_id}/messages/{message_id}`
+///
+/// Only the fields that are given are changed.
 pub async fn edit_message(
     Path((channel_id, message_id)): Path<(u128, u128)>,
-    Json(MessageUpdateJson { content }): Json<MessageUpdateJson>,
+    Json(MessageUpdateJson { content, embeds }): Json<MessageUpdateJson>,
     auth: crate::Authorization,
 ) -> Result<crate::Json<Message>, WebServerError> {
     let bigint_channel_id = u128_to_bigdecimal!(channel_id);
@@ -24,20 +35,43 @@ pub async fn edit_message(
             .into());
         }
     }
+    if let Some(ref embeds) = embeds {
+        validate_embeds(embeds).map_err(ErrorJson::new_400)?;
+    }
 
     let channel = sqlx::query!("SELECT * FROM channels WHERE id = $1", bigint_channel_id)
         .fetch_optional(db)
         .await?
         .ok_or_else(|| ErrorJson::new_404("channel not found".to_string()))?;
 
+    // only recipients can see DMs
+    if channel.guild_id.is_none() {
+        check_dm_recipient(db, channel_id, auth.0).await?;
+    }
+
+    let bigint_guild_id = channel.guild_id.clone();
     let channel_obj = Channel {
         id: channel_id,
         name: channel.name,
-        guild_id: bigdecimal_to_u128!(channel.guild_id),
+        guild_id: match channel.guild_id {
+            Some(guild_id) => Some(bigdecimal_to_u128!(guild_id)),
+            None => None,
+        },
+        channel_type: ChannelType::from_i16(channel.channel_type).unwrap_or(ChannelType::Text),
+        parent_id: match channel.parent_id {
+            Some(parent_id) => Some(bigdecimal_to_u128!(parent_id)),
+            None => None,
+        },
+        position: channel.position,
+        topic: channel.topic,
+        slowmode: channel.slowmode,
+        nsfw: channel.nsfw,
+        announcement: channel.announcement,
+        message_ttl: channel.message_ttl,
     };
 
     let resp = sqlx::query!(
-        "SELECT m.*, a.avatar AS avatar, a.name AS author_name, a.flags AS author_flags, a.discriminator AS author_discriminator, a.pronouns AS author_pronouns FROM messages m CROSS JOIN LATERAL (SELECT * FROM users WHERE id = m.author_id) AS a WHERE m.id = $1 AND m.channel_id = $2",
+        "SELECT m.*, a.avatar AS avatar, a.name AS author_name, a.flags AS author_flags, a.discriminator AS author_discriminator, a.pronouns AS author_pronouns FROM messages m CROSS JOIN LATERAL (SELECT * FROM users WHERE id = m.author_id) AS a WHERE m.id = $1 AND m.channel_id = $2 AND (m.expires_at IS NULL OR m.expires_at > extract(epoch FROM now()))",
         bigint_message_id,
         bigint_channel_id,
     )
@@ -46,6 +80,25 @@ pub async fn edit_message(
         .ok_or_else(|| ErrorJson::new_404(format!("Unknown message with ID {}", message_id)),
         )?;
 
+    let reactions = load_reactions(db, &[message_id], auth.0)
+        .await?
+        .remove(&message_id)
+        .unwrap_or_default();
+    let attachments = load_attachments(db, &[message_id])
+        .await?
+        .remove(&message_id)
+        .unwrap_or_default();
+
+    let reply_to = load_reply(
+        db,
+        match resp.reply_to_id {
+            Some(ref reply_to_id) => Some(bigdecimal_to_u128!(reply_to_id)),
+            None => None,
+        },
+        resp.reply_mention_author,
+    )
+    .await?;
+
     let old_message_obj = {
         let author_id = bigdecimal_to_u128!(resp.author_id);
         if author_id != auth.0 {
@@ -55,6 +108,19 @@ pub async fn edit_message(
             .into());
         }
 
+        // the copies have to stay faithful to the original
+        if resp.crosspost_message_id.is_some() {
+            return Err(
+                ErrorJson::new_400("crossposted messages can't be edited".to_string()).into(),
+            );
+        }
+
+        if embeds.as_ref().map_or(false, |e| !e.is_empty())
+            && !UserFlags::from_bits_truncate(resp.author_flags).contains(UserFlags::BOT_ACCOUNT)
+        {
+            return Err(ErrorJson::new_403("only bots can send embeds".to_string()).into());
+        }
+
         let author_id = bigdecimal_to_u128!(resp.author_id);
 
         Message {
@@ -64,7 +130,16 @@ pub async fn edit_message(
             author_id,
             content: resp.content,
             edited_at: resp.edited_at,
-            embeds: vec![],
+            expires_at: resp.expires_at,
+            embeds: load_embeds(resp.embeds)?,
+            mentions: mention_ids_from_row(resp.mentions)?,
+            mention_roles: mention_ids_from_row(resp.mention_roles)?,
+            mention_channels: mention_ids_from_row(resp.mention_channels)?,
+            mention_everyone: resp.mention_everyone,
+            reactions: reactions.clone(),
+            attachments: attachments.clone(),
+            crossposted_from: None,
+            reply_to: reply_to.clone(),
             author: Some(User {
                 id: author_id,
                 name: resp.author_name,
@@ -80,13 +155,73 @@ pub async fn edit_message(
         }
     };
 
-    let message = sqlx::query!("UPDATE messages SET content = $1, edited_at = now()::timestamp without time zone WHERE channel_id = $2 AND id = $3 RETURNING *", content, bigint_channel_id, bigint_message_id)
-        .fetch_optional(db)
-        .await?
-        .ok_or_else(|| ErrorJson::new_404(
-            format!("Unknown message with ID {}", message_id)
-        )
-        )?;
+    // mentions follow the content, and a reply keeps pinging its author if it did before
+    let mentions = match content {
+        Some(ref content) => {
+            let mut mentions = parse_mentions(content);
+            if let Some(ref reply_to) = reply_to {
+                if let Some(ref reply_author) = reply_to.author {
+                    if reply_to.mention_author
+                        && reply_author.id != auth.0
+                        && !mentions.users.contains(&reply_author.id)
+                    {
+                        mentions.users.push(reply_author.id);
+                    }
+                }
+            }
+            Some(
+                resolve_mentions(
+                    db,
+                    &bigint_channel_id,
+                    bigint_guild_id.as_ref(),
+                    &u128_to_bigdecimal!(auth.0),
+                    mentions,
+                )
+                .await?,
+            )
+        }
+        None => None,
+    };
+
+    let mut tx = db.begin().await?;
+
+    // only changes to what was said are worth keeping
+    if content.is_some() || embeds.is_some() {
+        if let Some(ref guild_id) = bigint_guild_id {
+            record_revision(&mut tx, guild_id, &bigint_message_id).await?;
+        }
+    }
+
+    // anything left out stays as it was
+    let message = sqlx::query!(
+        r#"
+UPDATE messages
+SET content          = coalesce($1, content),
+    embeds           = coalesce($4, embeds),
+    mentions         = coalesce($5, mentions),
+    mention_roles    = coalesce($6, mention_roles),
+    mention_channels = coalesce($7, mention_channels),
+    mention_everyone = coalesce($8, mention_everyone),
+    edited_at        = now()::timestamp without time zone
+WHERE channel_id = $2
+  AND id = $3
+RETURNING *
+"#,
+        content,
+        bigint_channel_id,
+        bigint_message_id,
+        embeds.map(sqlx::types::Json) as _,
+        mentions.as_ref().map(|m| mention_ids_to_row(&m.users)),
+        mentions.as_ref().map(|m| mention_ids_to_row(&m.roles)),
+        mentions.as_ref().map(|m| mention_ids_to_row(&m.channels)),
+        mentions.as_ref().map(|m| m.everyone)
+    )
+    .fetch_optional(&mut tx)
+    .await?
+    .ok_or_else(|| ErrorJson::new_404(format!("Unknown message with ID {}", message_id)))?;
+
+    tx.commit().await?;
+
     let new_msg_obj = Message {
         id: message_id,
         channel: channel_obj,
@@ -94,7 +229,16 @@ pub async fn edit_message(
         author_id: bigdecimal_to_u128!(message.author_id),
         content: message.content,
         edited_at: message.edited_at,
-        embeds: vec![],
+        expires_at: message.expires_at,
+        embeds: load_embeds(message.embeds)?,
+        mentions: mention_ids_from_row(message.mentions)?,
+        mention_roles: mention_ids_from_row(message.mention_roles)?,
+        mention_channels: mention_ids_from_row(message.mention_channels)?,
+        mention_everyone: message.mention_everyone,
+        reactions,
+        attachments,
+        crossposted_from: None,
+        reply_to,
         author: old_message_obj.author.clone(),
         nonce: None,
     };
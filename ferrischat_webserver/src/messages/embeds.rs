@@ -0,0 +1,118 @@
+use crate::WebServerError;
+use ferrischat_common::types::{Embed, ErrorJson};
+use serde::Deserialize;
+use sqlx::types::JsonValue;
+
+/// Maximum number of embeds on a single message.
+pub const MAX_EMBEDS_PER_MESSAGE: usize = 10;
+/// Maximum number of fields in a single embed.
+pub const MAX_EMBED_FIELDS: usize = 25;
+/// Longest embed title, and longest field name, in characters.
+pub const MAX_EMBED_TITLE_LENGTH: usize = 256;
+/// Longest embed description, in characters.
+pub const MAX_EMBED_DESCRIPTION_LENGTH: usize = 4096;
+/// Longest field value, in characters.
+pub const MAX_EMBED_FIELD_VALUE_LENGTH: usize = 1024;
+/// Longest footer text, in characters.
+pub const MAX_EMBED_FOOTER_LENGTH: usize = 2048;
+/// Most text all the embeds on a message can hold together, in characters.
+pub const MAX_EMBED_TOTAL_LENGTH: usize = 6000;
+/// Longest URL an embed can reference, in bytes.
+pub const MAX_EMBED_URL_LENGTH: usize = 2048;
+
+fn check_length(what: &str, text: &str, max: usize) -> Result<usize, String> {
+    let len = text.chars().count();
+    if len > max {
+        return Err(format!("{} must be at most {} characters", what, max));
+    }
+    Ok(len)
+}
+
+fn check_url(what: &str, url: &str) -> Result<(), String> {
+    if url.len() > MAX_EMBED_URL_LENGTH {
+        return Err(format!(
+            "{} must be at most {} bytes",
+            what, MAX_EMBED_URL_LENGTH
+        ));
+    }
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return Err(format!("{} must be an http or https URL", what));
+    }
+    Ok(())
+}
+
+/// Check embeds can be sent on a message, returning a reason if they can't.
+pub fn validate_embeds(embeds: &[Embed]) -> Result<(), String> {
+    if embeds.len() > MAX_EMBEDS_PER_MESSAGE {
+        return Err(format!(
+            "messages can have at most {} embeds",
+            MAX_EMBEDS_PER_MESSAGE
+        ));
+    }
+
+    let mut total = 0;
+    for embed in embeds {
+        if let Some(ref title) = embed.title {
+            total += check_length("embed titles", title, MAX_EMBED_TITLE_LENGTH)?;
+        }
+        if let Some(ref description) = embed.description {
+            total += check_length(
+                "embed descriptions",
+                description,
+                MAX_EMBED_DESCRIPTION_LENGTH,
+            )?;
+        }
+        if let Some(ref url) = embed.url {
+            check_url("embed URLs", url)?;
+        }
+        if embed.color.map_or(false, |c| c > 0x00FF_FFFF) {
+            return Err("embed colors must be RGB values".to_string());
+        }
+
+        if embed.fields.len() > MAX_EMBED_FIELDS {
+            return Err(format!(
+                "embeds can have at most {} fields",
+                MAX_EMBED_FIELDS
+            ));
+        }
+        for field in &embed.fields {
+            if field.name.is_empty() || field.value.is_empty() {
+                return Err("embed fields need a name and a value".to_string());
+            }
+            total += check_length("embed field names", &field.name, MAX_EMBED_TITLE_LENGTH)?;
+            total += check_length(
+                "embed field values",
+                &field.value,
+                MAX_EMBED_FIELD_VALUE_LENGTH,
+            )?;
+        }
+
+        if let Some(ref footer) = embed.footer {
+            total += check_length("embed footers", &footer.text, MAX_EMBED_FOOTER_LENGTH)?;
+            if let Some(ref icon_url) = footer.icon_url {
+                check_url("embed footer icons", icon_url)?;
+            }
+        }
+        if let Some(ref image) = embed.image {
+            check_url("embed images", &image.url)?;
+        }
+        if let Some(ref thumbnail) = embed.thumbnail {
+            check_url("embed thumbnails", &thumbnail.url)?;
+        }
+    }
+
+    if total > MAX_EMBED_TOTAL_LENGTH {
+        return Err(format!(
+            "embeds on a message can hold at most {} characters of text in total",
+            MAX_EMBED_TOTAL_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+/// Reads the embeds stored on a message row.
+pub fn load_embeds(embeds: JsonValue) -> Result<Vec<Embed>, WebServerError> {
+    Vec::<Embed>::deserialize(embeds).map_err(|e| {
+        ErrorJson::new_500(format!("stored embeds are invalid: {}", e), true, None).into()
+    })
+}
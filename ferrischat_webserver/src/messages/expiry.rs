@@ -0,0 +1,236 @@
+use super::attachments::load_attachments;
+use super::{
+    build_message_reference, build_reply, load_embeds, load_reply_targets, mention_ids_from_row,
+};
+use crate::channels::pins::fire_pins_update;
+use crate::periodic::run_periodically;
+use crate::storage::delete_attachment_files;
+use crate::ws::fire_event;
+use crate::WebServerError;
+use ferrischat_common::types::{Channel, ChannelType, Message, User, UserFlags};
+use ferrischat_common::ws::WsOutboundEvent;
+use sqlx::types::time::OffsetDateTime;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Longest a message can be kept before it expires, in seconds: 7 days.
+pub const MAX_MESSAGE_TTL: i32 = 604_800;
+
+/// How often to look for messages that have expired.
+const REAP_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Most expired messages deleted in one go. Anything left over waits for the next round.
+const REAP_BATCH_SIZE: i64 = 100;
+
+/// Checks a message TTL, in seconds. 0 means the message never expires.
+pub fn validate_message_ttl(ttl: i32) -> Result<(), String> {
+    if (0..=MAX_MESSAGE_TTL).contains(&ttl) {
+        Ok(())
+    } else {
+        Err(format!(
+            "message TTLs must be between 0 and {} seconds",
+            MAX_MESSAGE_TTL
+        ))
+    }
+}
+
+/// When a message sent now with this TTL expires, as a Unix timestamp in seconds.
+pub fn message_expiry(ttl: Option<i32>) -> Option<i64> {
+    match ttl {
+        Some(ttl) if ttl > 0 => Some(OffsetDateTime::now_utc().unix_timestamp() + i64::from(ttl)),
+        _ => None,
+    }
+}
+
+/// Deletes messages once they've expired, firing `MessageDelete` for each.
+///
+/// Reads already hide expired messages, so this only has to catch up eventually.
+/// This never returns, so it should be spawned as its own task.
+pub async fn reap_expired_messages() {
+    run_periodically(
+        "deleting expired messages",
+        REAP_INTERVAL,
+        delete_expired_messages,
+    )
+    .await;
+}
+
+#[allow(clippy::too_many_lines)]
+async fn delete_expired_messages() -> Result<usize, WebServerError> {
+    let db = get_db_or_fail!();
+
+    let mut tx = db.begin().await?;
+
+    // SKIP LOCKED lets several webservers reap at once without deleting the same messages twice
+    let resp = sqlx::query!(
+        r#"
+SELECT m.id AS "id!",
+       m.content,
+       m.channel_id AS "channel_id!",
+       m.author_id AS "author_id!",
+       m.edited_at,
+       m.expires_at,
+       m.embeds AS "embeds!",
+       m.mentions AS "mentions!",
+       m.mention_roles AS "mention_roles!",
+       m.mention_channels AS "mention_channels!",
+       m.mention_everyone AS "mention_everyone!",
+       m.crosspost_guild_id,
+       m.crosspost_channel_id,
+       m.crosspost_message_id,
+       m.reply_to_id,
+       m.reply_mention_author AS "reply_mention_author!",
+       c.name AS channel_name,
+       c.guild_id AS channel_guild_id,
+       c.channel_type,
+       c.parent_id AS channel_parent_id,
+       c.position AS channel_position,
+       c.topic AS channel_topic,
+       c.slowmode AS channel_slowmode,
+       c.nsfw AS channel_nsfw,
+       c.announcement AS channel_announcement,
+       c.message_ttl AS channel_message_ttl,
+       a.name AS author_name,
+       a.avatar AS avatar,
+       a.flags AS author_flags,
+       a.discriminator AS author_discriminator,
+       a.pronouns AS author_pronouns
+FROM messages m
+    INNER JOIN channels c ON c.id = m.channel_id
+    INNER JOIN users a ON a.id = m.author_id
+WHERE m.expires_at <= $1
+ORDER BY m.expires_at
+LIMIT $2
+FOR UPDATE OF m SKIP LOCKED
+"#,
+        OffsetDateTime::now_utc().unix_timestamp(),
+        REAP_BATCH_SIZE
+    )
+    .fetch_all(&mut tx)
+    .await?;
+
+    if resp.is_empty() {
+        return Ok(0);
+    }
+
+    let mut ids = Vec::with_capacity(resp.len());
+    let mut bigint_ids = Vec::with_capacity(resp.len());
+    let mut reply_ids = Vec::new();
+    for x in &resp {
+        ids.push(bigdecimal_to_u128!(x.id));
+        bigint_ids.push(x.id.clone());
+        if let Some(ref reply_to_id) = x.reply_to_id {
+            reply_ids.push(bigdecimal_to_u128!(reply_to_id));
+        }
+    }
+    let mut attachments = load_attachments(db, &ids).await?;
+    let reply_targets = load_reply_targets(db, &reply_ids).await?;
+
+    let mut pinned_channels = Vec::new();
+    for x in sqlx::query!(
+        "DELETE FROM pins WHERE message_id = ANY ($1) RETURNING channel_id",
+        &bigint_ids
+    )
+    .fetch_all(&mut tx)
+    .await?
+    {
+        pinned_channels.push(bigdecimal_to_u128!(x.channel_id));
+    }
+
+    sqlx::query!("DELETE FROM messages WHERE id = ANY ($1)", &bigint_ids)
+        .execute(&mut tx)
+        .await?;
+
+    tx.commit().await?;
+
+    let mut messages = Vec::with_capacity(resp.len());
+    for (x, id) in resp.into_iter().zip(ids) {
+        let author_id = bigdecimal_to_u128!(x.author_id);
+
+        messages.push(Message {
+            id,
+            content: x.content,
+            channel: Channel {
+                id: bigdecimal_to_u128!(x.channel_id),
+                name: x.channel_name,
+                guild_id: match x.channel_guild_id {
+                    Some(guild_id) => Some(bigdecimal_to_u128!(guild_id)),
+                    None => None,
+                },
+                channel_type: ChannelType::from_i16(x.channel_type).unwrap_or(ChannelType::Text),
+                parent_id: match x.channel_parent_id {
+                    Some(parent_id) => Some(bigdecimal_to_u128!(parent_id)),
+                    None => None,
+                },
+                position: x.channel_position,
+                topic: x.channel_topic,
+                slowmode: x.channel_slowmode,
+                nsfw: x.channel_nsfw,
+                announcement: x.channel_announcement,
+                message_ttl: x.channel_message_ttl,
+            },
+            channel_id: bigdecimal_to_u128!(x.channel_id),
+            author_id,
+            author: Some(User {
+                id: author_id,
+                name: x.author_name,
+                avatar: x.avatar,
+                guilds: None,
+                flags: UserFlags::from_bits_truncate(x.author_flags),
+                discriminator: x.author_discriminator,
+                pronouns: x
+                    .author_pronouns
+                    .and_then(ferrischat_common::types::Pronouns::from_i16),
+            }),
+            edited_at: x.edited_at,
+            expires_at: x.expires_at,
+            embeds: load_embeds(x.embeds)?,
+            mentions: mention_ids_from_row(x.mentions)?,
+            mention_roles: mention_ids_from_row(x.mention_roles)?,
+            mention_channels: mention_ids_from_row(x.mention_channels)?,
+            mention_everyone: x.mention_everyone,
+            reactions: vec![],
+            attachments: attachments.remove(&id).unwrap_or_default(),
+            crossposted_from: build_message_reference(
+                x.crosspost_guild_id,
+                x.crosspost_channel_id,
+                x.crosspost_message_id,
+            )?,
+            reply_to: match x.reply_to_id {
+                Some(reply_to_id) => Some(build_reply(
+                    &reply_targets,
+                    bigdecimal_to_u128!(reply_to_id),
+                    x.reply_mention_author,
+                )),
+                None => None,
+            },
+            nonce: None,
+        });
+    }
+
+    let mut files = Vec::new();
+    for message in &messages {
+        files.extend(message.attachments.iter().map(|a| a.id));
+    }
+    delete_attachment_files(files).await;
+
+    let n = messages.len();
+    let mut channels = HashMap::new();
+    for message in messages {
+        channels
+            .entry(message.channel_id)
+            .or_insert_with(|| message.channel.clone());
+        let event = WsOutboundEvent::MessageDelete { message };
+        fire_event(&event).await?;
+    }
+
+    pinned_channels.sort_unstable();
+    pinned_channels.dedup();
+    for channel_id in pinned_channels {
+        if let Some(channel) = channels.remove(&channel_id) {
+            fire_pins_update(db, channel).await?;
+        }
+    }
+
+    Ok(n)
+}
@@ -1,11 +1,15 @@
+use super::attachments::load_attachments;
+use super::reactions::load_reactions;
+use super::{build_message_reference, load_embeds, load_reply, mention_ids_from_row};
+use crate::channels::dm_channels::check_dm_recipient;
 use crate::WebServerError;
 use axum::extract::Path;
-use ferrischat_common::types::{Channel, ErrorJson, Message, User, UserFlags};
+use ferrischat_common::types::{Channel, ChannelType, ErrorJson, Message, User, UserFlags};
 
 /// GET `/v0/guilds/{guild_id}/channels/{channel_id}/messages/{message_id}`
 pub async fn get_message(
     Path((channel_id, message_id)): Path<(u128, u128)>,
-    _: crate::Authorization,
+    auth: crate::Authorization,
 ) -> Result<crate::Json<Message>, WebServerError> {
     let db = get_db_or_fail!();
     let bigint_message_id = u128_to_bigdecimal!(message_id);
@@ -16,15 +20,34 @@ pub async fn get_message(
         .await?
         .ok_or_else(|| ErrorJson::new_404("channel not found".to_string()))?;
 
+    // only recipients can see DMs
+    if channel.guild_id.is_none() {
+        check_dm_recipient(db, channel_id, auth.0).await?;
+    }
+
     let channel_obj = Channel {
         id: channel_id,
         name: channel.name,
-        guild_id: bigdecimal_to_u128!(channel.guild_id),
+        guild_id: match channel.guild_id {
+            Some(guild_id) => Some(bigdecimal_to_u128!(guild_id)),
+            None => None,
+        },
+        channel_type: ChannelType::from_i16(channel.channel_type).unwrap_or(ChannelType::Text),
+        parent_id: match channel.parent_id {
+            Some(parent_id) => Some(bigdecimal_to_u128!(parent_id)),
+            None => None,
+        },
+        position: channel.position,
+        topic: channel.topic,
+        slowmode: channel.slowmode,
+        nsfw: channel.nsfw,
+        announcement: channel.announcement,
+        message_ttl: channel.message_ttl,
     };
 
     let m =
         sqlx::query!(
-            "SELECT m.*, a.pronouns AS author_pronouns, a.avatar AS author_avatar, a.name AS author_name, a.flags AS author_flags, a.discriminator AS author_discriminator FROM messages m CROSS JOIN LATERAL (SELECT * FROM users WHERE id = m.author_id) AS a WHERE m.id = $1 AND m.channel_id = $2",
+            "SELECT m.*, a.pronouns AS author_pronouns, a.avatar AS author_avatar, a.name AS author_name, a.flags AS author_flags, a.discriminator AS author_discriminator FROM messages m CROSS JOIN LATERAL (SELECT * FROM users WHERE id = m.author_id) AS a WHERE m.id = $1 AND m.channel_id = $2 AND (m.expires_at IS NULL OR m.expires_at > extract(epoch FROM now()))",
             bigint_message_id,
             bigint_channel_id
         )
@@ -35,6 +58,15 @@ pub async fn get_message(
                     format!("Unknown message with ID {}", message_id),
                 )
             })?;
+    let reactions = load_reactions(db, &[message_id], auth.0)
+        .await?
+        .remove(&message_id)
+        .unwrap_or_default();
+    let attachments = load_attachments(db, &[message_id])
+        .await?
+        .remove(&message_id)
+        .unwrap_or_default();
+
     Ok(crate::Json {
         obj: Message {
             id: message_id,
@@ -43,7 +75,28 @@ pub async fn get_message(
             channel_id,
             author_id: bigdecimal_to_u128!(m.author_id),
             edited_at: m.edited_at,
-            embeds: vec![],
+            expires_at: m.expires_at,
+            embeds: load_embeds(m.embeds)?,
+            mentions: mention_ids_from_row(m.mentions)?,
+            mention_roles: mention_ids_from_row(m.mention_roles)?,
+            mention_channels: mention_ids_from_row(m.mention_channels)?,
+            mention_everyone: m.mention_everyone,
+            reactions,
+            attachments,
+            crossposted_from: build_message_reference(
+                m.crosspost_guild_id,
+                m.crosspost_channel_id,
+                m.crosspost_message_id,
+            )?,
+            reply_to: load_reply(
+                db,
+                match m.reply_to_id {
+                    Some(reply_to_id) => Some(bigdecimal_to_u128!(reply_to_id)),
+                    None => None,
+                },
+                m.reply_mention_author,
+            )
+            .await?,
             author: Some(User {
                 id: bigdecimal_to_u128!(m.author_id),
                 name: m.author_name,
@@ -0,0 +1,172 @@
+use crate::guilds::member_has_permissions;
+use crate::WebServerError;
+use ferrischat_common::perms::Permissions;
+use sqlx::types::BigDecimal;
+
+/// Most mentions of each kind kept from a single message. Anything past this is ignored.
+pub const MAX_MENTIONS: usize = 100;
+
+/// Mentions in a message, either straight out of its content or once they've been checked.
+#[derive(Default)]
+pub struct Mentions {
+    pub users: Vec<u128>,
+    pub roles: Vec<u128>,
+    pub channels: Vec<u128>,
+    pub everyone: bool,
+}
+
+fn push_unique(ids: &mut Vec<u128>, id: u128) {
+    if ids.len() < MAX_MENTIONS && !ids.contains(&id) {
+        ids.push(id);
+    }
+}
+
+/// Finds `<@user_id>`, `<@!user_id>`, `<@&role_id>`, `<#channel_id>` and `@everyone` in message content.
+///
+/// Nothing is checked here: see [`resolve_mentions`].
+pub fn parse_mentions(content: &str) -> Mentions {
+    let mut mentions = Mentions {
+        everyone: content.contains("@everyone"),
+        ..Mentions::default()
+    };
+
+    let mut rest = content;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let end = match rest.find('>') {
+            Some(end) => end,
+            None => break,
+        };
+        let tag = &rest[..end];
+
+        let (ids, id) = if let Some(id) = tag.strip_prefix("@&") {
+            (&mut mentions.roles, id)
+        } else if let Some(id) = tag.strip_prefix("@!").or_else(|| tag.strip_prefix('@')) {
+            (&mut mentions.users, id)
+        } else if let Some(id) = tag.strip_prefix('#') {
+            (&mut mentions.channels, id)
+        } else {
+            continue;
+        };
+        if let Ok(id) = id.parse::<u128>() {
+            push_unique(ids, id);
+            rest = &rest[end + 1..];
+        }
+    }
+
+    mentions
+}
+
+/// Turns mentioned IDs into what's stored in a message row.
+pub fn mention_ids_to_row(ids: &[u128]) -> Vec<BigDecimal> {
+    ids.iter().map(|id| u128_to_bigdecimal!(*id)).collect()
+}
+
+/// Reads mentioned IDs back out of a message row.
+pub fn mention_ids_from_row(ids: Vec<BigDecimal>) -> Result<Vec<u128>, WebServerError> {
+    let mut out = Vec::with_capacity(ids.len());
+    for id in ids {
+        out.push(bigdecimal_to_u128!(id));
+    }
+    Ok(out)
+}
+
+/// Drops any mentions that don't point at something in the channel's guild.
+///
+/// Users have to be members of the guild, or recipients of a DM. Roles and channels have to belong to the guild.
+/// `@everyone` only counts if the author has the permission for it, and never does in DMs.
+/// Mentions that don't check out aren't an error: they're left as plain text.
+pub async fn resolve_mentions(
+    db: &sqlx::PgPool,
+    channel_id: &BigDecimal,
+    guild_id: Option<&BigDecimal>,
+    author_id: &BigDecimal,
+    mentions: Mentions,
+) -> Result<Mentions, WebServerError> {
+    let guild_id = match guild_id {
+        Some(guild_id) => guild_id,
+        None => {
+            let users = sqlx::query!(
+                r#"SELECT user_id AS "user_id!" FROM channel_recipients WHERE channel_id = $1 AND user_id = ANY ($2)"#,
+                channel_id,
+                &mention_ids_to_row(&mentions.users)[..]
+            )
+            .fetch_all(db)
+            .await?;
+            let found = mention_ids_from_row(users.into_iter().map(|x| x.user_id).collect())?;
+            return Ok(Mentions {
+                users: mentions
+                    .users
+                    .into_iter()
+                    .filter(|id| found.contains(id))
+                    .collect(),
+                ..Mentions::default()
+            });
+        }
+    };
+
+    // keep the order they were written in, rather than whatever order the database finds them in
+    let users = if mentions.users.is_empty() {
+        vec![]
+    } else {
+        let found = sqlx::query!(
+            r#"SELECT user_id AS "user_id!" FROM members WHERE guild_id = $1 AND user_id = ANY ($2)"#,
+            guild_id,
+            &mention_ids_to_row(&mentions.users)[..]
+        )
+        .fetch_all(db)
+        .await?;
+        let found = mention_ids_from_row(found.into_iter().map(|x| x.user_id).collect())?;
+        mentions
+            .users
+            .into_iter()
+            .filter(|id| found.contains(id))
+            .collect()
+    };
+
+    let roles = if mentions.roles.is_empty() {
+        vec![]
+    } else {
+        let found = sqlx::query!(
+            "SELECT id FROM roles WHERE parent_guild = $1 AND id = ANY ($2)",
+            guild_id,
+            &mention_ids_to_row(&mentions.roles)[..]
+        )
+        .fetch_all(db)
+        .await?;
+        let found = mention_ids_from_row(found.into_iter().map(|x| x.id).collect())?;
+        mentions
+            .roles
+            .into_iter()
+            .filter(|id| found.contains(id))
+            .collect()
+    };
+
+    let channels = if mentions.channels.is_empty() {
+        vec![]
+    } else {
+        let found = sqlx::query!(
+            "SELECT id FROM channels WHERE guild_id = $1 AND id = ANY ($2)",
+            guild_id,
+            &mention_ids_to_row(&mentions.channels)[..]
+        )
+        .fetch_all(db)
+        .await?;
+        let found = mention_ids_from_row(found.into_iter().map(|x| x.id).collect())?;
+        mentions
+            .channels
+            .into_iter()
+            .filter(|id| found.contains(id))
+            .collect()
+    };
+
+    let everyone = mentions.everyone
+        && member_has_permissions(db, guild_id, author_id, Permissions::MENTION_EVERYONE).await?;
+
+    Ok(Mentions {
+        users,
+        roles,
+        channels,
+        everyone,
+    })
+}
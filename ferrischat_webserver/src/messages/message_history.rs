@@ -1,171 +1,220 @@
+use super::attachments::load_attachments;
+use super::reactions::load_reactions;
+use super::{
+    build_message_reference, build_reply, load_embeds, load_reply_targets, mention_ids_from_row,
+};
+use crate::channels::dm_channels::check_dm_recipient;
 use crate::WebServerError;
 use axum::extract::{Path, Query};
 use ferrischat_common::request_json::GetMessageHistoryParams;
-use ferrischat_common::types::{Channel, ErrorJson, Message, MessageHistory, User, UserFlags};
+use ferrischat_common::types::{
+    Channel, ChannelType, ErrorJson, Message, MessageHistory, User, UserFlags,
+};
+
+/// Number of messages returned when no limit is given.
+pub const DEFAULT_HISTORY_LIMIT: i64 = 50;
+/// Largest number of messages that can be fetched at once.
+pub const MAX_HISTORY_LIMIT: i64 = 100;
 
 /// GET `/v0/channels/{channel_id}/messages`
+///
+/// At most one of the `before`, `after` and `around` cursors can be given.
+/// Without any, the latest messages are returned.
+/// `around` includes the message it points at, and takes half the messages from either side of it.
+/// Messages are newest first unless `oldest_first` is set.
 pub async fn get_message_history(
     Path(channel_id): Path<u128>,
-    _: crate::Authorization,
+    auth: crate::Authorization,
     Query(GetMessageHistoryParams {
         limit,
         oldest_first,
-        mut offset,
+        before,
+        after,
+        around,
     }): Query<GetMessageHistoryParams>,
 ) -> Result<crate::Json<MessageHistory>, WebServerError> {
     let bigint_channel_id = u128_to_bigdecimal!(channel_id);
     let db = get_db_or_fail!();
 
-    let oldest_first = oldest_first.unwrap_or(false);
-
-    if limit < Some(0) {
-        return Err(ErrorJson::new_400("limit must be > 0".to_string()).into());
+    let limit = limit.unwrap_or(DEFAULT_HISTORY_LIMIT);
+    if !(1..=MAX_HISTORY_LIMIT).contains(&limit) {
+        return Err(ErrorJson::new_400(format!(
+            "limit must be between 1 and {}",
+            MAX_HISTORY_LIMIT
+        ))
+        .into());
     }
 
-    if offset < Some(0) {
-        offset = Some(0);
-    }
+    // the newest messages below `upper`, and the oldest ones above `lower`
+    let (upper, newer_limit, lower, older_limit) = match (before, after, around) {
+        (None, None, None) => (u128::MAX, limit, 0, 0),
+        (Some(before), None, None) => (before, limit, 0, 0),
+        (None, Some(after), None) => (0, 0, after, limit),
+        (None, None, Some(around)) => (
+            around.saturating_add(1),
+            limit - limit / 2,
+            around,
+            limit / 2,
+        ),
+        _ => {
+            return Err(ErrorJson::new_400(
+                "only one of before, after and around can be given".to_string(),
+            )
+            .into())
+        }
+    };
 
     let channel = sqlx::query!("SELECT * FROM channels WHERE id = $1", bigint_channel_id)
         .fetch_optional(db)
         .await?
         .ok_or_else(|| ErrorJson::new_404("channel not found".to_string()))?;
 
+    // only recipients can see DMs
+    if channel.guild_id.is_none() {
+        check_dm_recipient(db, channel_id, auth.0).await?;
+    }
+
     let channel_obj = Channel {
         id: channel_id,
         name: channel.name,
-        guild_id: bigdecimal_to_u128!(channel.guild_id),
+        guild_id: match channel.guild_id {
+            Some(guild_id) => Some(bigdecimal_to_u128!(guild_id)),
+            None => None,
+        },
+        channel_type: ChannelType::from_i16(channel.channel_type).unwrap_or(ChannelType::Text),
+        parent_id: match channel.parent_id {
+            Some(parent_id) => Some(bigdecimal_to_u128!(parent_id)),
+            None => None,
+        },
+        position: channel.position,
+        topic: channel.topic,
+        slowmode: channel.slowmode,
+        nsfw: channel.nsfw,
+        announcement: channel.announcement,
+        message_ttl: channel.message_ttl,
     };
 
-    let messages: Vec<_> = if oldest_first {
-        let resp = sqlx::query!(
-            r#"
-SELECT m.*,
-       a.avatar AS avatar,
-       a.name AS author_name,
-       a.flags AS author_flags,
-       a.discriminator AS author_discriminator,
-       a.pronouns AS author_pronouns
-FROM messages m
-    CROSS JOIN LATERAL (
-        SELECT *
-        FROM users
-        WHERE id = m.author_id
-        ) as a
-WHERE channel_id = $1
-ORDER BY id ASC
-LIMIT $2 OFFSET $3
-"#,
-            bigint_channel_id,
-            limit,
-            offset,
-        )
-        .fetch_all(db)
-        .await?;
-
-        resp.into_iter()
-            .map(|x| {
-                (
-                    x.id,
-                    x.content,
-                    x.channel_id,
-                    x.avatar,
-                    x.author_id,
-                    x.author_name,
-                    x.author_flags,
-                    x.author_discriminator,
-                    x.author_pronouns,
-                    x.edited_at,
-                )
-            })
-            .collect()
-    } else {
-        let resp = sqlx::query!(
-            r#"
-SELECT m.*,
+    // each half walks messages_channel_id_id_idx from the cursor, so deep pages are as cheap as the first
+    let resp = sqlx::query!(
+        r#"
+SELECT m.id AS "id!",
+       m.content,
+       m.author_id AS "author_id!",
+       m.edited_at,
+       m.expires_at,
+       m.embeds AS "embeds!",
+       m.mentions AS "mentions!",
+       m.mention_roles AS "mention_roles!",
+       m.mention_channels AS "mention_channels!",
+       m.mention_everyone AS "mention_everyone!",
+       m.crosspost_guild_id,
+       m.crosspost_channel_id,
+       m.crosspost_message_id,
+       m.reply_to_id,
+       m.reply_mention_author AS "reply_mention_author!",
        a.name AS author_name,
        a.avatar AS avatar,
        a.flags AS author_flags,
        a.discriminator AS author_discriminator,
        a.pronouns AS author_pronouns
-FROM messages m
+FROM (
+         (SELECT *
+          FROM messages
+          WHERE channel_id = $1
+            AND id < $2
+            AND (expires_at IS NULL OR expires_at > extract(epoch FROM now()))
+          ORDER BY id DESC
+          LIMIT $3)
+         UNION ALL
+         (SELECT *
+          FROM messages
+          WHERE channel_id = $1
+            AND id > $4
+            AND (expires_at IS NULL OR expires_at > extract(epoch FROM now()))
+          ORDER BY id ASC
+          LIMIT $5)
+     ) AS m
     CROSS JOIN LATERAL (
         SELECT *
-        FROM users 
+        FROM users
         WHERE id = m.author_id
-        ) as a
-WHERE channel_id = $1
-ORDER BY id DESC
-LIMIT $2 OFFSET $3
+        ) AS a
+ORDER BY m.id DESC
 "#,
-            bigint_channel_id,
-            limit,
-            offset,
-        )
-        .fetch_all(db)
-        .await?;
+        bigint_channel_id,
+        u128_to_bigdecimal!(upper),
+        newer_limit,
+        u128_to_bigdecimal!(lower),
+        older_limit,
+    )
+    .fetch_all(db)
+    .await?;
 
-        resp.into_iter()
-            .map(|x| {
-                (
-                    x.id,
-                    x.content,
-                    x.channel_id,
-                    x.avatar,
-                    x.author_id,
-                    x.author_name,
-                    x.author_flags,
-                    x.author_discriminator,
-                    x.author_pronouns,
-                    x.edited_at,
-                )
-            })
-            .collect()
-    };
+    let mut ids = Vec::with_capacity(resp.len());
+    let mut reply_ids = Vec::new();
+    for x in &resp {
+        ids.push(bigdecimal_to_u128!(x.id));
+        if let Some(ref reply_to_id) = x.reply_to_id {
+            reply_ids.push(bigdecimal_to_u128!(reply_to_id));
+        }
+    }
+    let mut reactions = load_reactions(db, &ids, auth.0).await?;
+    let mut attachments = load_attachments(db, &ids).await?;
+    let reply_targets = load_reply_targets(db, &reply_ids).await?;
 
-    let mut output_messages = Vec::with_capacity(messages.len());
-    for (
-        id,
-        content,
-        channel_id,
-        avatar,
-        author_id,
-        author_name,
-        author_flags,
-        author_discriminator,
-        author_pronouns,
-        edited_at,
-    ) in messages
-    {
-        let author_id = bigdecimal_to_u128!(author_id);
-        let id = bigdecimal_to_u128!(id);
-        let channel_id = bigdecimal_to_u128!(channel_id);
+    let mut messages = Vec::with_capacity(resp.len());
+    for (x, id) in resp.into_iter().zip(ids) {
+        let author_id = bigdecimal_to_u128!(x.author_id);
 
-        output_messages.push(Message {
+        messages.push(Message {
             id,
-            content,
+            content: x.content,
             channel: channel_obj.clone(),
             channel_id,
             author_id,
             author: Some(User {
                 id: author_id,
-                name: author_name,
-                avatar,
+                name: x.author_name,
+                avatar: x.avatar,
                 guilds: None,
-                flags: UserFlags::from_bits_truncate(author_flags),
-                discriminator: author_discriminator,
-                pronouns: author_pronouns.and_then(ferrischat_common::types::Pronouns::from_i16),
+                flags: UserFlags::from_bits_truncate(x.author_flags),
+                discriminator: x.author_discriminator,
+                pronouns: x
+                    .author_pronouns
+                    .and_then(ferrischat_common::types::Pronouns::from_i16),
             }),
-            edited_at,
-            embeds: vec![],
+            edited_at: x.edited_at,
+            expires_at: x.expires_at,
+            embeds: load_embeds(x.embeds)?,
+            mentions: mention_ids_from_row(x.mentions)?,
+            mention_roles: mention_ids_from_row(x.mention_roles)?,
+            mention_channels: mention_ids_from_row(x.mention_channels)?,
+            mention_everyone: x.mention_everyone,
+            reactions: reactions.remove(&id).unwrap_or_default(),
+            attachments: attachments.remove(&id).unwrap_or_default(),
+            crossposted_from: build_message_reference(
+                x.crosspost_guild_id,
+                x.crosspost_channel_id,
+                x.crosspost_message_id,
+            )?,
+            reply_to: match x.reply_to_id {
+                Some(reply_to_id) => Some(build_reply(
+                    &reply_targets,
+                    bigdecimal_to_u128!(reply_to_id),
+                    x.reply_mention_author,
+                )),
+                None => None,
+            },
             nonce: None,
         });
     }
 
+    if oldest_first.unwrap_or(false) {
+        messages.reverse();
+    }
+
     Ok(crate::Json {
-        obj: MessageHistory {
-            messages: output_messages,
-        },
+        obj: MessageHistory { messages },
         code: 200,
     })
 }
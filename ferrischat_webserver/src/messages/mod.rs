@@ -1,13 +1,37 @@
+mod ack_message;
+pub mod attachments;
+mod bulk_delete;
+mod crosspost_message;
 mod delete_message;
 mod edit_message;
+mod embeds;
+mod expiry;
 mod get_messages;
+mod mentions;
 mod message_history;
+pub mod reactions;
+mod read_states;
+mod reference;
+mod replies;
+pub mod revisions;
+pub mod scheduled;
+mod search_messages;
 mod send_message;
 
+pub use ack_message::*;
+pub use bulk_delete::*;
+pub use crosspost_message::*;
 pub use delete_message::*;
 pub use edit_message::*;
+pub use embeds::*;
+pub use expiry::*;
 pub use get_messages::*;
+pub use mentions::*;
 pub use message_history::*;
+pub use read_states::*;
+pub use reference::*;
+pub use replies::*;
+pub use search_messages::*;
 pub use send_message::*;
 
 use axum::routing::{get, post};
@@ -29,4 +53,32 @@ pub fn generate_messages_route() -> axum::Router {
             expand_version!("channels/:channel_id/messages/:message_id"),
             get(get_message).patch(edit_message).delete(delete_message),
         )
+        // POST   /channels/:channel_id/messages/bulk-delete
+        .route(
+            expand_version!("channels/:channel_id/messages/bulk-delete"),
+            post(bulk_delete_messages),
+        )
+        // POST   /channels/:channel_id/messages/:message_id/crosspost
+        .route(
+            expand_version!("channels/:channel_id/messages/:message_id/crosspost"),
+            post(crosspost_message),
+        )
+        // POST   /channels/:channel_id/messages/:message_id/ack
+        .route(
+            expand_version!("channels/:channel_id/messages/:message_id/ack"),
+            post(ack_message),
+        )
+        // attachments routes
+        .merge(attachments::generate_attachments_routes())
+        // GET    /guilds/:guild_id/messages/search
+        .route(
+            expand_version!("guilds/:guild_id/messages/search"),
+            get(search_messages),
+        )
+        // reactions routes
+        .merge(reactions::generate_reactions_routes())
+        // revisions routes
+        .merge(revisions::generate_revisions_routes())
+        // scheduled messages routes
+        .merge(scheduled::generate_scheduled_routes())
 }
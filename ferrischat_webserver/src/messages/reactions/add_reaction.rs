@@ -0,0 +1,90 @@
+use super::{load_reaction_channel, validate_emoji, MAX_REACTIONS_PER_MESSAGE};
+use crate::channels::dm_channels::check_dm_recipient;
+use crate::guilds::check_member_can_post;
+use crate::ws::fire_event;
+use crate::WebServerError;
+use axum::extract::Path;
+use ferrischat_common::types::ErrorJson;
+use ferrischat_common::ws::WsOutboundEvent;
+
+/// PUT `/v0/channels/{channel_id}/messages/{message_id}/reactions/{emoji}/@me`
+///
+/// Reacting twice with the same emoji does nothing.
+pub async fn add_reaction(
+    Path((channel_id, message_id, emoji, user_id)): Path<(u128, u128, String, String)>,
+    auth: crate::Authorization,
+) -> Result<http::StatusCode, WebServerError> {
+    let db = get_db_or_fail!();
+    let bigint_message_id = u128_to_bigdecimal!(message_id);
+    let bigint_user_id = u128_to_bigdecimal!(auth.0);
+
+    // this shares a route with removing other users' reactions, but nobody can react for someone else
+    if user_id != "@me" {
+        return Err(ErrorJson::new_400("you can only react as `@me`".to_string()).into());
+    }
+    validate_emoji(&emoji).map_err(ErrorJson::new_400)?;
+
+    let channel = load_reaction_channel(db, channel_id, message_id).await?;
+    match channel.guild_id {
+        Some(guild_id) => {
+            check_member_can_post(db, &u128_to_bigdecimal!(guild_id), &bigint_user_id).await?;
+        }
+        None => check_dm_recipient(db, channel_id, auth.0).await?,
+    }
+
+    let mut tx = db.begin().await?;
+
+    // locking the message keeps concurrent reactions from going over the limit together
+    sqlx::query!(
+        "SELECT id FROM messages WHERE id = $1 FOR UPDATE",
+        bigint_message_id
+    )
+    .fetch_optional(&mut tx)
+    .await?
+    .ok_or_else(|| ErrorJson::new_404(format!("Unknown message with ID {}", message_id)))?;
+
+    let counts = sqlx::query!(
+        r#"
+SELECT count(DISTINCT emoji)                 AS "emoji_count!",
+       coalesce(bool_or(emoji = $2), false) AS "has_emoji!"
+FROM reactions
+WHERE message_id = $1
+"#,
+        bigint_message_id,
+        emoji
+    )
+    .fetch_one(&mut tx)
+    .await?;
+    if !counts.has_emoji && counts.emoji_count >= MAX_REACTIONS_PER_MESSAGE {
+        return Err(ErrorJson::new_400(format!(
+            "messages can have at most {} different reactions",
+            MAX_REACTIONS_PER_MESSAGE
+        ))
+        .into());
+    }
+
+    let added = sqlx::query!(
+        "INSERT INTO reactions (message_id, user_id, emoji) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING RETURNING message_id",
+        bigint_message_id,
+        bigint_user_id,
+        emoji
+    )
+    .fetch_optional(&mut tx)
+    .await?
+    .is_some();
+
+    tx.commit().await?;
+
+    if added {
+        let event = WsOutboundEvent::ReactionAdd {
+            channel,
+            message_id,
+            user_id: auth.0,
+            emoji,
+        };
+
+        fire_event(&event).await?;
+    }
+
+    Ok(http::StatusCode::NO_CONTENT)
+}
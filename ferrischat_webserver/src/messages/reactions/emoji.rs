@@ -0,0 +1,26 @@
+/// Longest emoji that can be reacted with, in bytes. Long ZWJ sequences need the room.
+pub const MAX_EMOJI_LENGTH: usize = 64;
+/// Maximum number of different emoji on a single message.
+pub const MAX_REACTIONS_PER_MESSAGE: i64 = 20;
+
+/// Check an emoji can be reacted with, returning a reason if it can't.
+///
+/// Only Unicode emoji are supported so far. `:` is kept back for custom emoji, which will look like `name:id`.
+pub fn validate_emoji(emoji: &str) -> Result<(), String> {
+    if emoji.is_empty() || emoji.len() > MAX_EMOJI_LENGTH {
+        return Err(format!(
+            "emoji must be between 1 and {} bytes long",
+            MAX_EMOJI_LENGTH
+        ));
+    }
+    if emoji.is_ascii() {
+        return Err("custom emoji aren't supported yet".to_string());
+    }
+    if emoji
+        .chars()
+        .any(|c| c == ':' || c.is_whitespace() || c.is_control())
+    {
+        return Err("emoji can't contain `:`, whitespace or control characters".to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use super::{load_reaction_channel, validate_emoji};
+use crate::channels::dm_channels::check_dm_recipient;
+use crate::WebServerError;
+use axum::extract::{Path, Query};
+use ferrischat_common::request_json::GetReactionsParams;
+use ferrischat_common::types::{ErrorJson, User, UserFlags};
+
+/// Number of users returned when no limit is given.
+pub const DEFAULT_REACTIONS_LIMIT: i64 = 25;
+/// Largest number of users that can be fetched at once.
+pub const MAX_REACTIONS_LIMIT: i64 = 100;
+
+/// GET `/v0/channels/{channel_id}/messages/{message_id}/reactions/{emoji}`
+///
+/// Lists the users who reacted with an emoji, ordered by ID.
+/// Pass the last ID you got as `after` to fetch the next page.
+pub async fn get_reactions(
+    Path((channel_id, message_id, emoji)): Path<(u128, u128, String)>,
+    Query(GetReactionsParams { after, limit }): Query<GetReactionsParams>,
+    auth: crate::Authorization,
+) -> Result<crate::Json<Vec<User>>, WebServerError> {
+    let db = get_db_or_fail!();
+
+    validate_emoji(&emoji).map_err(ErrorJson::new_400)?;
+    let limit = limit.unwrap_or(DEFAULT_REACTIONS_LIMIT);
+    if !(1..=MAX_REACTIONS_LIMIT).contains(&limit) {
+        return Err(ErrorJson::new_400(format!(
+            "limit must be between 1 and {}",
+            MAX_REACTIONS_LIMIT
+        ))
+        .into());
+    }
+
+    let channel = load_reaction_channel(db, channel_id, message_id).await?;
+    match channel.guild_id {
+        Some(guild_id) => {
+            if !sqlx::query!(
+                r#"SELECT EXISTS(SELECT * FROM members WHERE guild_id = $1 AND user_id = $2) AS "exists!""#,
+                u128_to_bigdecimal!(guild_id),
+                u128_to_bigdecimal!(auth.0)
+            )
+            .fetch_one(db)
+            .await?
+            .exists
+            {
+                return Err(ErrorJson::new_404(format!(
+                    "Unknown message with ID {}",
+                    message_id
+                ))
+                .into());
+            }
+        }
+        // only recipients can see DMs
+        None => check_dm_recipient(db, channel_id, auth.0).await?,
+    }
+
+    let resp = sqlx::query!(
+        r#"
+SELECT u.*
+FROM reactions r
+    INNER JOIN users u ON u.id = r.user_id
+WHERE r.message_id = $1
+  AND r.emoji = $2
+  AND r.user_id > $3
+ORDER BY r.user_id
+LIMIT $4
+"#,
+        u128_to_bigdecimal!(message_id),
+        emoji,
+        u128_to_bigdecimal!(after.unwrap_or(0)),
+        limit
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut users = Vec::with_capacity(resp.len());
+    for u in resp {
+        users.push(User {
+            id: bigdecimal_to_u128!(u.id),
+            name: u.name,
+            avatar: u.avatar,
+            guilds: None,
+            flags: UserFlags::from_bits_truncate(u.flags),
+            discriminator: u.discriminator,
+            pronouns: u
+                .pronouns
+                .and_then(ferrischat_common::types::Pronouns::from_i16),
+        });
+    }
+
+    Ok(crate::Json {
+        obj: users,
+        code: 200,
+    })
+}
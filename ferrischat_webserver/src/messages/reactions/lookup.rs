@@ -0,0 +1,92 @@
+use crate::WebServerError;
+use ferrischat_common::types::{Channel, ChannelType, ErrorJson, Reaction};
+use std::collections::HashMap;
+
+/// Fetches the channel a message is in, making sure the message exists.
+///
+/// Callers still have to check the user can see the channel.
+pub async fn load_reaction_channel(
+    db: &sqlx::PgPool,
+    channel_id: u128,
+    message_id: u128,
+) -> Result<Channel, WebServerError> {
+    let channel = sqlx::query!(
+        r#"
+SELECT c.*
+FROM messages m
+    INNER JOIN channels c ON c.id = m.channel_id
+WHERE m.id = $1
+  AND m.channel_id = $2
+"#,
+        u128_to_bigdecimal!(message_id),
+        u128_to_bigdecimal!(channel_id)
+    )
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| ErrorJson::new_404(format!("Unknown message with ID {}", message_id)))?;
+
+    Ok(Channel {
+        id: channel_id,
+        name: channel.name,
+        guild_id: match channel.guild_id {
+            Some(guild_id) => Some(bigdecimal_to_u128!(guild_id)),
+            None => None,
+        },
+        channel_type: ChannelType::from_i16(channel.channel_type).unwrap_or(ChannelType::Text),
+        parent_id: match channel.parent_id {
+            Some(parent_id) => Some(bigdecimal_to_u128!(parent_id)),
+            None => None,
+        },
+        position: channel.position,
+        topic: channel.topic,
+        slowmode: channel.slowmode,
+        nsfw: channel.nsfw,
+        announcement: channel.announcement,
+        message_ttl: channel.message_ttl,
+    })
+}
+
+/// Counts the reactions on each of these messages, as seen by `user_id`.
+///
+/// Messages without any reactions are left out. Emoji are in the order they were first reacted with.
+pub async fn load_reactions(
+    db: &sqlx::PgPool,
+    message_ids: &[u128],
+    user_id: u128,
+) -> Result<HashMap<u128, Vec<Reaction>>, WebServerError> {
+    let mut bigint_message_ids = Vec::with_capacity(message_ids.len());
+    for id in message_ids {
+        bigint_message_ids.push(u128_to_bigdecimal!(*id));
+    }
+
+    let resp = sqlx::query!(
+        r#"
+SELECT message_id,
+       emoji,
+       count(*)              AS "count!",
+       bool_or(user_id = $2) AS "me!"
+FROM reactions
+WHERE message_id = ANY ($1)
+GROUP BY message_id, emoji
+ORDER BY min(created_at)
+"#,
+        &bigint_message_ids[..],
+        u128_to_bigdecimal!(user_id)
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut reactions: HashMap<u128, Vec<Reaction>> = HashMap::new();
+    for x in resp {
+        reactions
+            .entry(bigdecimal_to_u128!(x.message_id))
+            .or_default()
+            .push(Reaction {
+                emoji: x.emoji,
+                count: x.count,
+                me: x.me,
+            });
+    }
+
+    Ok(reactions)
+}
@@ -0,0 +1,30 @@
+mod add_reaction;
+mod emoji;
+mod get_reactions;
+mod lookup;
+mod remove_reaction;
+
+pub use add_reaction::*;
+pub use emoji::*;
+pub use get_reactions::*;
+pub use lookup::*;
+pub use remove_reaction::*;
+
+use axum::routing::{get, put};
+use axum::Router;
+
+pub fn generate_reactions_routes() -> axum::Router {
+    debug!("generating routes for reactions");
+    Router::new()
+        // GET    /channels/:channel_id/messages/:message_id/reactions/:emoji
+        .route(
+            expand_version!("channels/:channel_id/messages/:message_id/reactions/:emoji"),
+            get(get_reactions),
+        )
+        // PUT    /channels/:channel_id/messages/:message_id/reactions/:emoji/@me
+        // DELETE /channels/:channel_id/messages/:message_id/reactions/:emoji/:user_id
+        .route(
+            expand_version!("channels/:channel_id/messages/:message_id/reactions/:emoji/:user_id"),
+            put(add_reaction).delete(remove_reaction),
+        )
+}
@@ -0,0 +1,74 @@
+use super::{load_reaction_channel, validate_emoji};
+use crate::guilds::member_has_permissions;
+use crate::ws::fire_event;
+use crate::WebServerError;
+use axum::extract::Path;
+use ferrischat_common::perms::Permissions;
+use ferrischat_common::types::ErrorJson;
+use ferrischat_common::ws::WsOutboundEvent;
+
+/// DELETE `/v0/channels/{channel_id}/messages/{message_id}/reactions/{emoji}/{user_id}`
+///
+/// `user_id` can be `@me` to remove your own reaction.
+/// Removing anyone else's needs the manage messages permission, so it can't be done in DMs.
+pub async fn remove_reaction(
+    Path((channel_id, message_id, emoji, user_id)): Path<(u128, u128, String, String)>,
+    auth: crate::Authorization,
+) -> Result<http::StatusCode, WebServerError> {
+    let db = get_db_or_fail!();
+
+    validate_emoji(&emoji).map_err(ErrorJson::new_400)?;
+    let user_id = match user_id.as_str() {
+        "@me" => auth.0,
+        id => id
+            .parse::<u128>()
+            .map_err(|_| ErrorJson::new_400("user ID must be a snowflake or `@me`".to_string()))?,
+    };
+
+    let channel = load_reaction_channel(db, channel_id, message_id).await?;
+    if user_id != auth.0 {
+        let is_moderator = match channel.guild_id {
+            Some(guild_id) => {
+                member_has_permissions(
+                    db,
+                    &u128_to_bigdecimal!(guild_id),
+                    &u128_to_bigdecimal!(auth.0),
+                    Permissions::MANAGE_MESSAGES,
+                )
+                .await?
+            }
+            None => false,
+        };
+        if !is_moderator {
+            return Err(ErrorJson::new_403(
+                "you need the manage messages permission to remove other users' reactions"
+                    .to_string(),
+            )
+            .into());
+        }
+    }
+
+    if sqlx::query!(
+        "DELETE FROM reactions WHERE message_id = $1 AND user_id = $2 AND emoji = $3 RETURNING message_id",
+        u128_to_bigdecimal!(message_id),
+        u128_to_bigdecimal!(user_id),
+        emoji
+    )
+    .fetch_optional(db)
+    .await?
+    .is_none()
+    {
+        return Err(ErrorJson::new_404("Unknown reaction".to_string()).into());
+    }
+
+    let event = WsOutboundEvent::ReactionRemove {
+        channel,
+        message_id,
+        user_id,
+        emoji,
+    };
+
+    fire_event(&event).await?;
+
+    Ok(http::StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,85 @@
+use super::{mention_ids_to_row, Mentions};
+use crate::WebServerError;
+use sqlx::types::BigDecimal;
+
+/// Updates read states once a message has been sent.
+///
+/// The author has read the channel up to their own message.
+/// In DMs, every message counts as a mention for the other recipients.
+/// Elsewhere, only mentioned users' mention counts go up: directly, through a role, or with `@everyone`.
+/// `mentions` must already have been through [`super::resolve_mentions`].
+pub async fn update_read_states_for_new_message(
+    db: &sqlx::PgPool,
+    channel_id: &BigDecimal,
+    guild_id: Option<&BigDecimal>,
+    author_id: &BigDecimal,
+    message_id: &BigDecimal,
+    mentions: &Mentions,
+) -> Result<(), WebServerError> {
+    sqlx::query!(
+        r#"
+INSERT INTO read_states (user_id, channel_id, last_message_id)
+VALUES ($1, $2, $3)
+ON CONFLICT (user_id, channel_id) DO UPDATE SET last_message_id = $3,
+                                               mention_count   = 0
+"#,
+        author_id,
+        channel_id,
+        message_id
+    )
+    .execute(db)
+    .await?;
+
+    match guild_id {
+        None => {
+            sqlx::query!(
+                r#"
+INSERT INTO read_states (user_id, channel_id, mention_count)
+SELECT user_id, channel_id, 1
+FROM channel_recipients
+WHERE channel_id = $1
+  AND user_id <> $2
+ON CONFLICT (user_id, channel_id) DO UPDATE SET mention_count = read_states.mention_count + 1
+"#,
+                channel_id,
+                author_id
+            )
+            .execute(db)
+            .await?;
+        }
+        Some(guild_id)
+            if !mentions.users.is_empty() || !mentions.roles.is_empty() || mentions.everyone =>
+        {
+            sqlx::query!(
+                r#"
+INSERT INTO read_states (user_id, channel_id, mention_count)
+SELECT DISTINCT user_id, $1::numeric(39), 1
+FROM (SELECT unnest($2::numeric(39)[]) AS user_id
+      UNION
+      SELECT user_id
+      FROM role_data
+      WHERE guild_id = $4
+        AND role_id = ANY ($5)
+      UNION
+      SELECT user_id
+      FROM members
+      WHERE guild_id = $4
+        AND $6) AS mentioned
+WHERE user_id <> $3
+ON CONFLICT (user_id, channel_id) DO UPDATE SET mention_count = read_states.mention_count + 1
+"#,
+                channel_id,
+                &mention_ids_to_row(&mentions.users)[..],
+                author_id,
+                guild_id,
+                &mention_ids_to_row(&mentions.roles)[..],
+                mentions.everyone
+            )
+            .execute(db)
+            .await?;
+        }
+        Some(_) => {}
+    }
+
+    Ok(())
+}
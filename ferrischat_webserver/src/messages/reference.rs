@@ -0,0 +1,26 @@
+use crate::WebServerError;
+use ferrischat_common::types::MessageReference;
+use sqlx::types::BigDecimal;
+
+/// Builds a reference to another message out of the nullable columns it's stored in.
+///
+/// Returns `None` unless both the channel and message IDs are set.
+pub fn build_message_reference(
+    guild_id: Option<BigDecimal>,
+    channel_id: Option<BigDecimal>,
+    message_id: Option<BigDecimal>,
+) -> Result<Option<MessageReference>, WebServerError> {
+    let (channel_id, message_id) = match (channel_id, message_id) {
+        (Some(channel_id), Some(message_id)) => (channel_id, message_id),
+        _ => return Ok(None),
+    };
+
+    Ok(Some(MessageReference {
+        guild_id: match guild_id {
+            Some(guild_id) => Some(bigdecimal_to_u128!(guild_id)),
+            None => None,
+        },
+        channel_id: bigdecimal_to_u128!(channel_id),
+        message_id: bigdecimal_to_u128!(message_id),
+    }))
+}
@@ -0,0 +1,98 @@
+use crate::WebServerError;
+use ferrischat_common::types::{ReplyReference, User, UserFlags};
+use std::collections::HashMap;
+
+/// How much of the replied-to message's content is shown in a reply.
+pub const REPLY_EXCERPT_LENGTH: usize = 100;
+
+/// Fetches the author and an excerpt of each of these messages, in one query.
+///
+/// Messages that no longer exist are left out.
+pub async fn load_reply_targets(
+    db: &sqlx::PgPool,
+    message_ids: &[u128],
+) -> Result<HashMap<u128, (User, Option<String>)>, WebServerError> {
+    let mut bigint_message_ids = Vec::with_capacity(message_ids.len());
+    for id in message_ids {
+        bigint_message_ids.push(u128_to_bigdecimal!(*id));
+    }
+
+    let resp = sqlx::query!(
+        r#"
+SELECT m.id AS message_id,
+       m.content,
+       u.*
+FROM messages m
+    INNER JOIN users u ON u.id = m.author_id
+WHERE m.id = ANY ($1)
+  AND (m.expires_at IS NULL OR m.expires_at > extract(epoch FROM now()))
+"#,
+        &bigint_message_ids[..]
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut targets = HashMap::with_capacity(resp.len());
+    for x in resp {
+        targets.insert(
+            bigdecimal_to_u128!(x.message_id),
+            (
+                User {
+                    id: bigdecimal_to_u128!(x.id),
+                    name: x.name,
+                    avatar: x.avatar,
+                    guilds: None,
+                    flags: UserFlags::from_bits_truncate(x.flags),
+                    discriminator: x.discriminator,
+                    pronouns: x
+                        .pronouns
+                        .and_then(ferrischat_common::types::Pronouns::from_i16),
+                },
+                x.content
+                    .map(|c| c.chars().take(REPLY_EXCERPT_LENGTH).collect()),
+            ),
+        );
+    }
+
+    Ok(targets)
+}
+
+/// Builds the stub a reply shows of the message it's replying to,
+/// out of what [`load_reply_targets`] found.
+pub fn build_reply(
+    targets: &HashMap<u128, (User, Option<String>)>,
+    message_id: u128,
+    mention_author: bool,
+) -> ReplyReference {
+    match targets.get(&message_id) {
+        Some((author, excerpt)) => ReplyReference {
+            message_id,
+            author: Some(author.clone()),
+            excerpt: excerpt.clone(),
+            deleted: false,
+            mention_author,
+        },
+        None => ReplyReference {
+            message_id,
+            author: None,
+            excerpt: None,
+            deleted: true,
+            mention_author,
+        },
+    }
+}
+
+/// Resolves what a single message is replying to, if anything.
+pub async fn load_reply(
+    db: &sqlx::PgPool,
+    reply_to_id: Option<u128>,
+    mention_author: bool,
+) -> Result<Option<ReplyReference>, WebServerError> {
+    match reply_to_id {
+        Some(id) => {
+            let targets = load_reply_targets(db, &[id]).await?;
+            Ok(Some(build_reply(&targets, id, mention_author)))
+        }
+        None => Ok(None),
+    }
+}
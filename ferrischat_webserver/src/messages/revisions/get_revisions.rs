@@ -0,0 +1,80 @@
+use crate::guilds::member_has_permissions;
+use crate::messages::load_embeds;
+use crate::WebServerError;
+use axum::extract::Path;
+use ferrischat_common::perms::Permissions;
+use ferrischat_common::types::{ErrorJson, MessageRevision};
+
+/// GET `/v0/channels/{channel_id}/messages/{message_id}/revisions`
+///
+/// Oldest first. `revised_at` is when each revision was replaced by the next one.
+pub async fn get_revisions(
+    Path((channel_id, message_id)): Path<(u128, u128)>,
+    auth: crate::Authorization,
+) -> Result<crate::Json<Vec<MessageRevision>>, WebServerError> {
+    let bigint_channel_id = u128_to_bigdecimal!(channel_id);
+    let bigint_message_id = u128_to_bigdecimal!(message_id);
+
+    let db = get_db_or_fail!();
+
+    let channel = sqlx::query!(
+        "SELECT guild_id FROM channels WHERE id = $1",
+        bigint_channel_id
+    )
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| ErrorJson::new_404(format!("Unknown channel with ID {}", channel_id)))?;
+
+    // DMs never keep edit history
+    let guild_id = channel
+        .guild_id
+        .ok_or_else(|| ErrorJson::new_400("edit history isn't kept for DM messages".to_string()))?;
+
+    if !member_has_permissions(
+        db,
+        &guild_id,
+        &u128_to_bigdecimal!(auth.0),
+        Permissions::MANAGE_MESSAGES,
+    )
+    .await?
+    {
+        return Err(ErrorJson::new_403(
+            "you need the manage messages permission to see edit history".to_string(),
+        )
+        .into());
+    }
+
+    if !sqlx::query!(
+        r#"SELECT EXISTS(SELECT * FROM messages WHERE id = $1 AND channel_id = $2) AS "exists!""#,
+        bigint_message_id,
+        bigint_channel_id
+    )
+    .fetch_one(db)
+    .await?
+    .exists
+    {
+        return Err(ErrorJson::new_404(format!("Unknown message with ID {}", message_id)).into());
+    }
+
+    let resp = sqlx::query!(
+        "SELECT * FROM message_revisions WHERE message_id = $1 ORDER BY id",
+        bigint_message_id
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut revisions = Vec::with_capacity(resp.len());
+    for x in resp {
+        revisions.push(MessageRevision {
+            message_id,
+            content: x.content,
+            embeds: load_embeds(x.embeds)?,
+            revised_at: x.revised_at,
+        });
+    }
+
+    Ok(crate::Json {
+        obj: revisions,
+        code: 200,
+    })
+}
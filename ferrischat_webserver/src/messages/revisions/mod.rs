@@ -0,0 +1,20 @@
+mod get_revisions;
+mod prune;
+mod record;
+
+pub use get_revisions::*;
+pub use prune::*;
+pub use record::*;
+
+use axum::routing::get;
+use axum::Router;
+
+pub fn generate_revisions_routes() -> axum::Router {
+    debug!("generating routes for message revisions");
+    Router::new()
+        // GET    /channels/:channel_id/messages/:message_id/revisions
+        .route(
+            expand_version!("channels/:channel_id/messages/:message_id/revisions"),
+            get(get_revisions),
+        )
+}
@@ -0,0 +1,42 @@
+use crate::periodic::run_periodically;
+use crate::WebServerError;
+use std::time::Duration;
+
+/// Longest a guild can keep revisions for, in days: about 10 years.
+pub const MAX_REVISION_RETENTION: i32 = 3650;
+
+/// How often to look for revisions that are past their guild's retention period.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Deletes message revisions older than their guild's retention period.
+///
+/// This never returns, so it should be spawned as its own task.
+pub async fn prune_message_revisions() {
+    run_periodically(
+        "pruning message revisions",
+        PRUNE_INTERVAL,
+        prune_expired_revisions,
+    )
+    .await;
+}
+
+async fn prune_expired_revisions() -> Result<u64, WebServerError> {
+    let db = get_db_or_fail!();
+
+    Ok(sqlx::query!(
+        r#"
+DELETE
+FROM message_revisions r
+    USING messages m, channels c, guilds g
+WHERE m.id = r.message_id
+  AND c.id = m.channel_id
+  AND g.id = c.guild_id
+  AND g.message_revision_retention IS NOT NULL
+  AND r.revised_at < now() AT TIME ZONE 'UTC' - make_interval(days => least(g.message_revision_retention, $1))
+"#,
+        MAX_REVISION_RETENTION
+    )
+    .execute(db)
+    .await?
+    .rows_affected())
+}
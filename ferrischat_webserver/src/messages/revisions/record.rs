@@ -0,0 +1,27 @@
+use crate::WebServerError;
+use sqlx::types::BigDecimal;
+
+/// Saves a message's current content and embeds as a revision, if its guild keeps edit history.
+///
+/// This has to run before the edit itself, in the same transaction.
+pub async fn record_revision(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &BigDecimal,
+    message_id: &BigDecimal,
+) -> Result<(), WebServerError> {
+    sqlx::query!(
+        r#"
+INSERT INTO message_revisions (message_id, content, embeds)
+SELECT m.id, m.content, m.embeds
+FROM messages m
+WHERE m.id = $1
+  AND EXISTS(SELECT * FROM guilds WHERE id = $2 AND message_revisions)
+"#,
+        message_id,
+        guild_id
+    )
+    .execute(tx)
+    .await?;
+
+    Ok(())
+}
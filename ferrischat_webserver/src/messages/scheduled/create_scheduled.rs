@@ -0,0 +1,99 @@
+use super::{max_scheduled_messages, validate_send_at};
+use crate::messages::validate_message;
+use crate::WebServerError;
+use axum::extract::Path;
+use axum::Json;
+use ferrischat_common::request_json::{MessageCreateJson, ScheduledMessageCreateJson};
+use ferrischat_common::types::{ErrorJson, ModelType, ScheduledMessage};
+use ferrischat_snowflake_generator::generate_snowflake;
+
+/// POST `/v0/channels/{channel_id}/scheduled-messages`
+///
+/// `send_at` is a Unix timestamp, in seconds.
+/// Permissions are checked again when the message is sent, so it's dropped if the author can't post by then.
+pub async fn create_scheduled_message(
+    Path(channel_id): Path<u128>,
+    Json(ScheduledMessageCreateJson {
+        content,
+        embeds,
+        reply_to,
+        mention_author,
+        send_at,
+    }): Json<ScheduledMessageCreateJson>,
+    auth: crate::Authorization,
+) -> Result<crate::Json<ScheduledMessage>, WebServerError> {
+    validate_send_at(send_at).map_err(ErrorJson::new_400)?;
+
+    let db = get_db_or_fail!();
+
+    // it has to pass everything an immediate send would
+    let embeds = embeds.unwrap_or_default();
+    validate_message(
+        db,
+        auth.0,
+        channel_id,
+        MessageCreateJson {
+            content: content.clone(),
+            nonce: None,
+            reply_to,
+            mention_author,
+            embeds: Some(embeds.clone()),
+            ttl: None,
+        },
+    )
+    .await?;
+
+    let bigint_channel_id = u128_to_bigdecimal!(channel_id);
+    let bigint_author_id = u128_to_bigdecimal!(auth.0);
+
+    let scheduled = sqlx::query!(
+        r#"SELECT count(*) AS "count!" FROM scheduled_messages WHERE author_id = $1"#,
+        bigint_author_id
+    )
+    .fetch_one(db)
+    .await?
+    .count;
+    let max = max_scheduled_messages();
+    if usize::try_from(scheduled).unwrap_or(usize::MAX) >= max {
+        return Err(ErrorJson::new_400(format!(
+            "users can have at most {} scheduled messages",
+            max
+        ))
+        .into());
+    }
+
+    let node_id = get_node_id!();
+    let id = generate_snowflake::<0>(ModelType::ScheduledMessage as u8, node_id);
+    let mention_author = mention_author.unwrap_or(false);
+
+    sqlx::query!(
+        r#"
+INSERT INTO scheduled_messages (id, channel_id, author_id, content, embeds, reply_to_id, mention_author, send_at)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+"#,
+        u128_to_bigdecimal!(id),
+        bigint_channel_id,
+        bigint_author_id,
+        content,
+        sqlx::types::Json(&embeds) as _,
+        reply_to.map(|id| u128_to_bigdecimal!(id)),
+        mention_author,
+        send_at
+    )
+    .execute(db)
+    .await?;
+
+    Ok(crate::Json {
+        obj: ScheduledMessage {
+            id,
+            channel_id,
+            author_id: auth.0,
+            content,
+            embeds,
+            reply_to,
+            mention_author,
+            send_at,
+        },
+        code: 201,
+    })
+}
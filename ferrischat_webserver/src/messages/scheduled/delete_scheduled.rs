@@ -0,0 +1,31 @@
+use crate::WebServerError;
+use axum::extract::Path;
+use ferrischat_common::types::ErrorJson;
+
+/// DELETE `/v0/channels/{channel_id}/scheduled-messages/{scheduled_message_id}`
+///
+/// Cancels a scheduled message before it's sent.
+pub async fn delete_scheduled_message(
+    Path((channel_id, scheduled_message_id)): Path<(u128, u128)>,
+    auth: crate::Authorization,
+) -> Result<http::StatusCode, WebServerError> {
+    let db = get_db_or_fail!();
+
+    let deleted = sqlx::query!(
+        "DELETE FROM scheduled_messages WHERE id = $1 AND channel_id = $2 AND author_id = $3 RETURNING id",
+        u128_to_bigdecimal!(scheduled_message_id),
+        u128_to_bigdecimal!(channel_id),
+        u128_to_bigdecimal!(auth.0)
+    )
+    .fetch_optional(db)
+    .await?;
+
+    match deleted {
+        Some(_) => Ok(http::StatusCode::NO_CONTENT),
+        None => Err(ErrorJson::new_404(format!(
+            "Unknown scheduled message with ID {}",
+            scheduled_message_id
+        ))
+        .into()),
+    }
+}
@@ -0,0 +1,191 @@
+use crate::messages::{load_embeds, send_message};
+use crate::periodic::run_periodically;
+use crate::WebServerError;
+use ferrischat_common::request_json::MessageCreateJson;
+use ferrischat_redis::REDIS_MANAGER;
+use sqlx::types::time::OffsetDateTime;
+use std::time::Duration;
+
+/// How often to look for scheduled messages that are due.
+const DISPATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Most scheduled messages sent in one go. Anything left over waits for the next round.
+const DISPATCH_BATCH_SIZE: i64 = 100;
+
+/// Redis key held by whichever node is currently dispatching.
+const DISPATCH_LOCK_KEY: &str = "scheduled_messages:dispatch_lock";
+
+/// How long the dispatch lock lasts if it isn't released, in milliseconds.
+/// Rows are claimed before they're sent, so this expiring early only means two nodes share a round.
+const DISPATCH_LOCK_TTL: u64 = 60_000;
+
+/// How many times a scheduled message is tried before it's dropped.
+/// Slowmode doesn't count towards this.
+const MAX_DISPATCH_ATTEMPTS: i32 = 5;
+
+/// Only deletes the lock if it's still held by the caller.
+const RELEASE_LOCK_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("DEL", KEYS[1])
+end
+return 0
+"#;
+
+/// Sends scheduled messages once they're due.
+///
+/// Every node can run this: a lock in Redis makes sure only one of them dispatches at a time.
+/// This never returns, so it should be spawned as its own task.
+pub async fn dispatch_scheduled_messages() {
+    run_periodically(
+        "sending scheduled messages",
+        DISPATCH_INTERVAL,
+        dispatch_due_messages,
+    )
+    .await;
+}
+
+async fn dispatch_due_messages() -> Result<usize, WebServerError> {
+    let db = get_db_or_fail!();
+    let node_id = get_node_id!();
+
+    let mut redis = REDIS_MANAGER
+        .get()
+        .ok_or(WebServerError::MissingRedis)?
+        .get()
+        .await?;
+
+    // the lock expires on its own, so a node dying mid-batch can't stop dispatching for good
+    let token = node_id.to_string();
+    let locked = ferrischat_redis::redis::cmd("SET")
+        .arg(DISPATCH_LOCK_KEY)
+        .arg(&token)
+        .arg("PX")
+        .arg(DISPATCH_LOCK_TTL)
+        .arg("NX")
+        .query_async::<_, Option<String>>(&mut redis)
+        .await?
+        .is_some();
+    if !locked {
+        return Ok(0);
+    }
+
+    let sent = send_due_messages(db).await;
+
+    ferrischat_redis::redis::Script::new(RELEASE_LOCK_SCRIPT)
+        .key(DISPATCH_LOCK_KEY)
+        .arg(&token)
+        .invoke_async::<_, i64>(&mut redis)
+        .await?;
+
+    sent
+}
+
+async fn send_due_messages(db: &sqlx::PgPool) -> Result<usize, WebServerError> {
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+
+    // rows are taken off the queue before they're sent, so each one goes out at most once
+    let due = sqlx::query!(
+        r#"
+DELETE
+FROM scheduled_messages
+WHERE id IN (SELECT id
+             FROM scheduled_messages
+             WHERE send_at <= $1
+             ORDER BY send_at, id
+             LIMIT $2
+             FOR UPDATE SKIP LOCKED)
+RETURNING *
+"#,
+        now,
+        DISPATCH_BATCH_SIZE
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut sent = 0;
+    for x in due {
+        let id = bigdecimal_to_u128!(x.id);
+        let embeds = match load_embeds(x.embeds.clone()) {
+            Ok(embeds) => embeds,
+            Err(e) => {
+                warn!(
+                    "scheduled message {} has invalid embeds, dropping it: {}",
+                    id, e
+                );
+                continue;
+            }
+        };
+        let json = MessageCreateJson {
+            content: x.content.clone(),
+            nonce: None,
+            reply_to: match x.reply_to_id {
+                Some(ref reply_to_id) => Some(bigdecimal_to_u128!(reply_to_id)),
+                None => None,
+            },
+            mention_author: Some(x.mention_author),
+            embeds: Some(embeds),
+            ttl: None,
+        };
+
+        // everything is checked again, since the author may have lost access in the meantime
+        let attempts = match send_message(
+            db,
+            bigdecimal_to_u128!(x.author_id),
+            bigdecimal_to_u128!(x.channel_id),
+            json,
+            vec![],
+        )
+        .await
+        {
+            Ok(_) => {
+                sent += 1;
+                continue;
+            }
+            // slowmode only holds it back until the next round
+            Err(WebServerError::RateLimited { .. }) => x.attempts,
+            Err(e @ WebServerError::Http(_)) => {
+                warn!(
+                    "scheduled message {} can no longer be sent, dropping it: {}",
+                    id, e
+                );
+                continue;
+            }
+            Err(e) => {
+                error!("failed to send scheduled message {}: {}", id, e);
+                x.attempts + 1
+            }
+        };
+
+        if attempts >= MAX_DISPATCH_ATTEMPTS {
+            warn!(
+                "scheduled message {} failed {} times, dropping it",
+                id, attempts
+            );
+            continue;
+        }
+
+        // one message failing mustn't hold up the rest of the queue
+        if let Err(e) = sqlx::query!(
+            r#"
+INSERT INTO scheduled_messages (id, channel_id, author_id, content, embeds, reply_to_id, mention_author, send_at, attempts)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+"#,
+            x.id,
+            x.channel_id,
+            x.author_id,
+            x.content,
+            x.embeds,
+            x.reply_to_id,
+            x.mention_author,
+            x.send_at,
+            attempts
+        )
+        .execute(db)
+        .await
+        {
+            error!("failed to requeue scheduled message {}: {}", id, e);
+        }
+    }
+
+    Ok(sent)
+}
@@ -0,0 +1,82 @@
+use super::{load_scheduled_message, validate_send_at};
+use crate::messages::validate_embeds;
+use crate::WebServerError;
+use axum::extract::Path;
+use axum::Json;
+use ferrischat_common::request_json::ScheduledMessageUpdateJson;
+use ferrischat_common::types::{ErrorJson, ScheduledMessage, UserFlags};
+
+/// PATCH `/v0/channels/{channel_id}/scheduled-messages/{scheduled_message_id}`
+///
+/// Only the fields that are given are changed.
+pub async fn edit_scheduled_message(
+    Path((channel_id, scheduled_message_id)): Path<(u128, u128)>,
+    Json(ScheduledMessageUpdateJson {
+        content,
+        embeds,
+        send_at,
+    }): Json<ScheduledMessageUpdateJson>,
+    auth: crate::Authorization,
+) -> Result<crate::Json<ScheduledMessage>, WebServerError> {
+    if content.as_ref().map_or(false, |c| c.len() > 10240) {
+        return Err(ErrorJson::new_400(
+            "message content size must be fewer than 10,240 bytes".to_string(),
+        )
+        .into());
+    }
+    if let Some(ref embeds) = embeds {
+        validate_embeds(embeds).map_err(ErrorJson::new_400)?;
+    }
+    if let Some(send_at) = send_at {
+        validate_send_at(send_at).map_err(ErrorJson::new_400)?;
+    }
+
+    let db = get_db_or_fail!();
+
+    if embeds.as_ref().map_or(false, |e| !e.is_empty()) {
+        let flags = sqlx::query!(
+            "SELECT flags FROM users WHERE id = $1",
+            u128_to_bigdecimal!(auth.0)
+        )
+        .fetch_one(db)
+        .await?
+        .flags;
+        if !UserFlags::from_bits_truncate(flags).contains(UserFlags::BOT_ACCOUNT) {
+            return Err(ErrorJson::new_403("only bots can send embeds".to_string()).into());
+        }
+    }
+
+    // anything left out stays as it was
+    let updated = sqlx::query!(
+        r#"
+UPDATE scheduled_messages
+SET content = coalesce($1, content),
+    embeds  = coalesce($2, embeds),
+    send_at = coalesce($3, send_at)
+WHERE id = $4
+  AND channel_id = $5
+  AND author_id = $6
+RETURNING id
+"#,
+        content,
+        embeds.map(sqlx::types::Json) as _,
+        send_at,
+        u128_to_bigdecimal!(scheduled_message_id),
+        u128_to_bigdecimal!(channel_id),
+        u128_to_bigdecimal!(auth.0)
+    )
+    .fetch_optional(db)
+    .await?;
+    if updated.is_none() {
+        return Err(ErrorJson::new_404(format!(
+            "Unknown scheduled message with ID {}",
+            scheduled_message_id
+        ))
+        .into());
+    }
+
+    Ok(crate::Json {
+        obj: load_scheduled_message(db, channel_id, scheduled_message_id, auth.0).await?,
+        code: 200,
+    })
+}
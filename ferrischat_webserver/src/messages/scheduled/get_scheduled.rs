@@ -0,0 +1,58 @@
+use super::load_scheduled_message;
+use crate::messages::load_embeds;
+use crate::WebServerError;
+use axum::extract::Path;
+use ferrischat_common::types::ScheduledMessage;
+
+/// GET `/v0/channels/{channel_id}/scheduled-messages`
+///
+/// Only lists the user's own scheduled messages, soonest first.
+pub async fn get_scheduled_messages(
+    Path(channel_id): Path<u128>,
+    auth: crate::Authorization,
+) -> Result<crate::Json<Vec<ScheduledMessage>>, WebServerError> {
+    let db = get_db_or_fail!();
+
+    let resp = sqlx::query!(
+        "SELECT * FROM scheduled_messages WHERE channel_id = $1 AND author_id = $2 ORDER BY send_at, id",
+        u128_to_bigdecimal!(channel_id),
+        u128_to_bigdecimal!(auth.0)
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut scheduled = Vec::with_capacity(resp.len());
+    for x in resp {
+        scheduled.push(ScheduledMessage {
+            id: bigdecimal_to_u128!(x.id),
+            channel_id,
+            author_id: auth.0,
+            content: x.content,
+            embeds: load_embeds(x.embeds)?,
+            reply_to: match x.reply_to_id {
+                Some(reply_to_id) => Some(bigdecimal_to_u128!(reply_to_id)),
+                None => None,
+            },
+            mention_author: x.mention_author,
+            send_at: x.send_at,
+        });
+    }
+
+    Ok(crate::Json {
+        obj: scheduled,
+        code: 200,
+    })
+}
+
+/// GET `/v0/channels/{channel_id}/scheduled-messages/{scheduled_message_id}`
+pub async fn get_scheduled_message(
+    Path((channel_id, scheduled_message_id)): Path<(u128, u128)>,
+    auth: crate::Authorization,
+) -> Result<crate::Json<ScheduledMessage>, WebServerError> {
+    let db = get_db_or_fail!();
+
+    Ok(crate::Json {
+        obj: load_scheduled_message(db, channel_id, scheduled_message_id, auth.0).await?,
+        code: 200,
+    })
+}
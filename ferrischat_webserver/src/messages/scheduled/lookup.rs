@@ -0,0 +1,68 @@
+use crate::messages::load_embeds;
+use crate::WebServerError;
+use ferrischat_common::types::{ErrorJson, ScheduledMessage};
+use sqlx::types::time::OffsetDateTime;
+
+/// Furthest ahead a message can be scheduled, in seconds: 30 days.
+pub const MAX_SCHEDULE_AHEAD: i64 = 2_592_000;
+
+/// Maximum number of scheduled messages per user, as set in the config.
+pub fn max_scheduled_messages() -> usize {
+    ferrischat_config::GLOBAL_CONFIG
+        .get()
+        .map_or(100, |cfg| cfg.messages.max_scheduled_messages)
+}
+
+/// Checks a delivery time is in the future, and not too far into it.
+pub fn validate_send_at(send_at: i64) -> Result<(), String> {
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    if send_at <= now {
+        return Err("send_at must be in the future".to_string());
+    }
+    if send_at - now > MAX_SCHEDULE_AHEAD {
+        return Err(format!(
+            "messages can be scheduled at most {} days ahead",
+            MAX_SCHEDULE_AHEAD / 86400
+        ));
+    }
+    Ok(())
+}
+
+/// Fetches one of a user's scheduled messages.
+///
+/// Nobody else can see them, so other users' scheduled messages are reported as unknown.
+pub async fn load_scheduled_message(
+    db: &sqlx::PgPool,
+    channel_id: u128,
+    scheduled_message_id: u128,
+    user_id: u128,
+) -> Result<ScheduledMessage, WebServerError> {
+    let x = sqlx::query!(
+        "SELECT * FROM scheduled_messages WHERE id = $1 AND channel_id = $2 AND author_id = $3",
+        u128_to_bigdecimal!(scheduled_message_id),
+        u128_to_bigdecimal!(channel_id),
+        u128_to_bigdecimal!(user_id)
+    )
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| {
+        ErrorJson::new_404(format!(
+            "Unknown scheduled message with ID {}",
+            scheduled_message_id
+        ))
+    })?;
+
+    Ok(ScheduledMessage {
+        id: scheduled_message_id,
+        channel_id,
+        author_id: user_id,
+        content: x.content,
+        embeds: load_embeds(x.embeds)?,
+        reply_to: match x.reply_to_id {
+            Some(reply_to_id) => Some(bigdecimal_to_u128!(reply_to_id)),
+            None => None,
+        },
+        mention_author: x.mention_author,
+        send_at: x.send_at,
+    })
+}
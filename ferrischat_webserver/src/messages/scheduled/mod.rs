@@ -0,0 +1,36 @@
+mod create_scheduled;
+mod delete_scheduled;
+mod dispatch;
+mod edit_scheduled;
+mod get_scheduled;
+mod lookup;
+
+pub use create_scheduled::*;
+pub use delete_scheduled::*;
+pub use dispatch::*;
+pub use edit_scheduled::*;
+pub use get_scheduled::*;
+pub use lookup::*;
+
+use axum::routing::get;
+use axum::Router;
+
+pub fn generate_scheduled_routes() -> axum::Router {
+    debug!("generating routes for scheduled messages");
+    Router::new()
+        // POST   /channels/:channel_id/scheduled-messages
+        // GET    /channels/:channel_id/scheduled-messages
+        .route(
+            expand_version!("channels/:channel_id/scheduled-messages"),
+            get(get_scheduled_messages).post(create_scheduled_message),
+        )
+        // GET    /channels/:channel_id/scheduled-messages/:scheduled_message_id
+        // PATCH  /channels/:channel_id/scheduled-messages/:scheduled_message_id
+        // DELETE /channels/:channel_id/scheduled-messages/:scheduled_message_id
+        .route(
+            expand_version!("channels/:channel_id/scheduled-messages/:scheduled_message_id"),
+            get(get_scheduled_message)
+                .patch(edit_scheduled_message)
+                .delete(delete_scheduled_message),
+        )
+}
@@ -0,0 +1,217 @@
+use super::attachments::load_attachments;
+use super::reactions::load_reactions;
+use super::{
+    build_message_reference, build_reply, load_embeds, load_reply_targets, mention_ids_from_row,
+    DEFAULT_HISTORY_LIMIT, MAX_HISTORY_LIMIT,
+};
+use crate::snowflake::snowflake_at;
+use crate::WebServerError;
+use axum::extract::{Path, Query};
+use ferrischat_common::request_json::SearchMessagesParams;
+use ferrischat_common::types::{
+    Channel, ChannelType, ErrorJson, Message, MessageHistory, User, UserFlags,
+};
+
+/// Longest search query, in bytes.
+pub const MAX_SEARCH_QUERY_LENGTH: usize = 512;
+
+/// GET `/v0/guilds/{guild_id}/messages/search`
+///
+/// `content` is matched with web search syntax: quoted phrases, `or`, and `-` to exclude words.
+/// Every other filter is optional. `since` and `until` are Unix timestamps, in seconds.
+/// Results are newest first. Pass the last one's ID as `before` to get the next page.
+pub async fn search_messages(
+    Path(guild_id): Path<u128>,
+    auth: crate::Authorization,
+    Query(SearchMessagesParams {
+        content,
+        author_id,
+        channel_id,
+        since,
+        until,
+        has_link,
+        pinned,
+        before,
+        limit,
+    }): Query<SearchMessagesParams>,
+) -> Result<crate::Json<MessageHistory>, WebServerError> {
+    let db = get_db_or_fail!();
+    let bigint_guild_id = u128_to_bigdecimal!(guild_id);
+
+    let limit = limit.unwrap_or(DEFAULT_HISTORY_LIMIT);
+    if !(1..=MAX_HISTORY_LIMIT).contains(&limit) {
+        return Err(ErrorJson::new_400(format!(
+            "limit must be between 1 and {}",
+            MAX_HISTORY_LIMIT
+        ))
+        .into());
+    }
+    if content
+        .as_ref()
+        .map_or(false, |c| c.len() > MAX_SEARCH_QUERY_LENGTH)
+    {
+        return Err(ErrorJson::new_400(format!(
+            "search queries must be at most {} bytes",
+            MAX_SEARCH_QUERY_LENGTH
+        ))
+        .into());
+    }
+
+    // every channel in a guild is readable by its members, until channel permissions exist
+    if !sqlx::query!(
+        r#"SELECT EXISTS(SELECT * FROM members WHERE guild_id = $1 AND user_id = $2) AS "exists!""#,
+        bigint_guild_id,
+        u128_to_bigdecimal!(auth.0)
+    )
+    .fetch_one(db)
+    .await?
+    .exists
+    {
+        return Err(ErrorJson::new_404(format!("Unknown guild with ID {}", guild_id)).into());
+    }
+
+    // date ranges turn into ID ranges, since snowflakes start with their creation time
+    let upper = until.map_or(u128::MAX, |until| snowflake_at(until.saturating_add(1)));
+    let upper = before.map_or(upper, |before| before.min(upper));
+    let lower = since.map_or(0, snowflake_at);
+
+    let resp = sqlx::query!(
+        r#"
+SELECT m.id AS "id!",
+       m.content,
+       m.channel_id AS "channel_id!",
+       m.author_id AS "author_id!",
+       m.edited_at,
+       m.expires_at,
+       m.embeds AS "embeds!",
+       m.mentions AS "mentions!",
+       m.mention_roles AS "mention_roles!",
+       m.mention_channels AS "mention_channels!",
+       m.mention_everyone AS "mention_everyone!",
+       m.crosspost_guild_id,
+       m.crosspost_channel_id,
+       m.crosspost_message_id,
+       m.reply_to_id,
+       m.reply_mention_author AS "reply_mention_author!",
+       c.name AS channel_name,
+       c.channel_type,
+       c.parent_id AS channel_parent_id,
+       c.position AS channel_position,
+       c.topic AS channel_topic,
+       c.slowmode AS channel_slowmode,
+       c.nsfw AS channel_nsfw,
+       c.announcement AS channel_announcement,
+       c.message_ttl AS channel_message_ttl,
+       a.name AS author_name,
+       a.avatar AS avatar,
+       a.flags AS author_flags,
+       a.discriminator AS author_discriminator,
+       a.pronouns AS author_pronouns
+FROM messages m
+    INNER JOIN channels c ON c.id = m.channel_id
+    INNER JOIN users a ON a.id = m.author_id
+WHERE c.guild_id = $1
+  AND m.id < $2
+  AND m.id >= $3
+  AND (m.expires_at IS NULL OR m.expires_at > extract(epoch FROM now()))
+  AND ($4::text IS NULL OR m.search_vector @@ websearch_to_tsquery('english', $4))
+  AND ($5::numeric(39) IS NULL OR m.author_id = $5)
+  AND ($6::numeric(39) IS NULL OR m.channel_id = $6)
+  AND ($7::boolean IS NULL OR (coalesce(m.content, '') ~* 'https?://') = $7)
+  AND ($8::boolean IS NULL OR EXISTS(SELECT * FROM pins p WHERE p.message_id = m.id) = $8)
+ORDER BY m.id DESC
+LIMIT $9
+"#,
+        bigint_guild_id,
+        u128_to_bigdecimal!(upper),
+        u128_to_bigdecimal!(lower),
+        content.filter(|c| !c.trim().is_empty()),
+        author_id.map(|id| u128_to_bigdecimal!(id)),
+        channel_id.map(|id| u128_to_bigdecimal!(id)),
+        has_link,
+        pinned,
+        limit
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut ids = Vec::with_capacity(resp.len());
+    let mut reply_ids = Vec::new();
+    for x in &resp {
+        ids.push(bigdecimal_to_u128!(x.id));
+        if let Some(ref reply_to_id) = x.reply_to_id {
+            reply_ids.push(bigdecimal_to_u128!(reply_to_id));
+        }
+    }
+    let mut reactions = load_reactions(db, &ids, auth.0).await?;
+    let mut attachments = load_attachments(db, &ids).await?;
+    let reply_targets = load_reply_targets(db, &reply_ids).await?;
+
+    let mut messages = Vec::with_capacity(resp.len());
+    for (x, id) in resp.into_iter().zip(ids) {
+        let author_id = bigdecimal_to_u128!(x.author_id);
+        let channel_id = bigdecimal_to_u128!(x.channel_id);
+
+        messages.push(Message {
+            id,
+            content: x.content,
+            channel: Channel {
+                id: channel_id,
+                name: x.channel_name,
+                guild_id: Some(guild_id),
+                channel_type: ChannelType::from_i16(x.channel_type).unwrap_or(ChannelType::Text),
+                parent_id: match x.channel_parent_id {
+                    Some(parent_id) => Some(bigdecimal_to_u128!(parent_id)),
+                    None => None,
+                },
+                position: x.channel_position,
+                topic: x.channel_topic,
+                slowmode: x.channel_slowmode,
+                nsfw: x.channel_nsfw,
+                announcement: x.channel_announcement,
+                message_ttl: x.channel_message_ttl,
+            },
+            channel_id,
+            author_id,
+            author: Some(User {
+                id: author_id,
+                name: x.author_name,
+                avatar: x.avatar,
+                guilds: None,
+                flags: UserFlags::from_bits_truncate(x.author_flags),
+                discriminator: x.author_discriminator,
+                pronouns: x
+                    .author_pronouns
+                    .and_then(ferrischat_common::types::Pronouns::from_i16),
+            }),
+            edited_at: x.edited_at,
+            expires_at: x.expires_at,
+            embeds: load_embeds(x.embeds)?,
+            mentions: mention_ids_from_row(x.mentions)?,
+            mention_roles: mention_ids_from_row(x.mention_roles)?,
+            mention_channels: mention_ids_from_row(x.mention_channels)?,
+            mention_everyone: x.mention_everyone,
+            reactions: reactions.remove(&id).unwrap_or_default(),
+            attachments: attachments.remove(&id).unwrap_or_default(),
+            crossposted_from: build_message_reference(
+                x.crosspost_guild_id,
+                x.crosspost_channel_id,
+                x.crosspost_message_id,
+            )?,
+            reply_to: match x.reply_to_id {
+                Some(reply_to_id) => Some(build_reply(
+                    &reply_targets,
+                    bigdecimal_to_u128!(reply_to_id),
+                    x.reply_mention_author,
+                )),
+                None => None,
+            },
+            nonce: None,
+        });
+    }
+
+    Ok(crate::Json {
+        obj: MessageHistory { messages },
+        code: 200,
+    })
+}
@@ -1,18 +1,91 @@
+use super::attachments::{store_attachments, MessageCreateBody, PendingAttachment};
+use super::{
+    load_reply, mention_ids_to_row, message_expiry, parse_mentions, resolve_mentions,
+    update_read_states_for_new_message, validate_embeds, validate_message_ttl, Mentions,
+};
+use crate::channels::check_slowmode;
+use crate::channels::dm_channels::check_dm_recipient;
+use crate::guilds::{check_member_can_post, member_has_permissions};
+use crate::storage::delete_attachment_files;
+use crate::threads::record_thread_activity;
 use crate::ws::fire_event;
 use crate::WebServerError;
-use axum::extract::{Json, Path};
+use axum::extract::Path;
+use ferrischat_common::perms::Permissions;
 use ferrischat_common::request_json::MessageCreateJson;
-use ferrischat_common::types::{Channel, ErrorJson, Message, ModelType, User, UserFlags};
+use ferrischat_common::types::{
+    Channel, ChannelType, Embed, ErrorJson, Message, ModelType, User, UserFlags,
+};
 use ferrischat_common::ws::WsOutboundEvent;
 use ferrischat_snowflake_generator::generate_snowflake;
+use sqlx::types::Json;
 
 /// POST `/v0/channels/{channel_id}/messages`
+///
+/// Files can be attached by sending `multipart/form-data` instead of JSON:
+/// the JSON goes in a `payload_json` field, and every other field is a file.
 pub async fn create_message(
     auth: crate::Authorization,
-    json: Json<MessageCreateJson>,
+    body: MessageCreateBody,
     Path(channel_id): Path<u128>,
 ) -> Result<crate::Json<Message>, WebServerError> {
-    let MessageCreateJson { content, nonce } = json.0;
+    let MessageCreateBody { json, files } = body;
+    let db = get_db_or_fail!();
+
+    let msg_obj = send_message(db, auth.0, channel_id, json, files).await?;
+
+    Ok(crate::Json {
+        obj: msg_obj,
+        code: 201,
+    })
+}
+
+/// A message that [`validate_message`] has accepted, ready for [`deliver_message`].
+pub struct ValidatedMessage {
+    channel: Channel,
+    author: User,
+    content: String,
+    nonce: Option<String>,
+    embeds: Vec<Embed>,
+    expires_at: Option<i64>,
+    reply_to: Option<u128>,
+    mention_author: bool,
+    mentions: Mentions,
+    slowmode: bool,
+}
+
+/// Checks the author can post in the channel, then sends a message there as them.
+///
+/// This is everything behind [`create_message`], so anything else sending messages
+/// on a user's behalf goes through the same checks.
+pub async fn send_message(
+    db: &sqlx::PgPool,
+    author_id: u128,
+    channel_id: u128,
+    json: MessageCreateJson,
+    files: Vec<PendingAttachment>,
+) -> Result<Message, WebServerError> {
+    let message = validate_message(db, author_id, channel_id, json).await?;
+    deliver_message(db, message, files).await
+}
+
+/// Runs every check [`send_message`] makes, without sending anything.
+///
+/// Nothing is written here, so this can be used to check a message that'll be sent later.
+pub async fn validate_message(
+    db: &sqlx::PgPool,
+    author_id: u128,
+    channel_id: u128,
+    json: MessageCreateJson,
+) -> Result<ValidatedMessage, WebServerError> {
+    let MessageCreateJson {
+        content,
+        nonce,
+        reply_to,
+        mention_author,
+        embeds,
+        ttl,
+    } = json;
 
     if content.len() > 10240 {
         return Err(ErrorJson::new_400(
@@ -21,43 +94,74 @@ pub async fn create_message(
         .into());
     }
 
-    let bigint_channel_id = u128_to_bigdecimal!(channel_id);
-
-    let node_id = {
-        ferrischat_redis::NODE_ID
-            .get()
-            .copied()
-            .ok_or(WebServerError::MissingNodeId)?
-    };
-    let message_id = generate_snowflake::<0>(ModelType::Message as u8, node_id);
-    let bigint_message_id = u128_to_bigdecimal!(message_id);
+    let embeds = embeds.unwrap_or_default();
+    validate_embeds(&embeds).map_err(ErrorJson::new_400)?;
+    if let Some(ttl) = ttl {
+        validate_message_ttl(ttl).map_err(ErrorJson::new_400)?;
+    }
 
-    let author_id = auth.0;
+    let bigint_channel_id = u128_to_bigdecimal!(channel_id);
     let bigint_author_id = u128_to_bigdecimal!(author_id);
 
-    let db = get_db_or_fail!();
-
     let channel = sqlx::query!("SELECT * FROM channels WHERE id = $1", bigint_channel_id)
         .fetch_optional(db)
         .await?
         .ok_or_else(|| ErrorJson::new_404("channel not found".to_string()))?;
 
-    let channel_obj = Channel {
+    if channel.channel_type == ChannelType::Category as i16 {
+        return Err(ErrorJson::new_400("can't send messages in a category".to_string()).into());
+    }
+
+    let mut slowmode = false;
+    match channel.guild_id {
+        Some(ref guild_id) => {
+            check_member_can_post(db, guild_id, &bigint_author_id).await?;
+
+            // moderators can post in announcement channels, and skip slowmode
+            if channel.announcement || channel.slowmode > 0 {
+                let is_moderator = member_has_permissions(
+                    db,
+                    guild_id,
+                    &bigint_author_id,
+                    Permissions::MANAGE_MESSAGES,
+                )
+                .await?;
+                if channel.announcement && !is_moderator {
+                    return Err(ErrorJson::new_403(
+                        "only moderators can post in announcement channels".to_string(),
+                    )
+                    .into());
+                }
+                slowmode = channel.slowmode > 0 && !is_moderator;
+            }
+        }
+        None => check_dm_recipient(db, channel_id, author_id).await?,
+    }
+
+    // a TTL of 0 opts out of the channel's default
+    let expires_at = message_expiry(ttl.or(channel.message_ttl));
+
+    let bigint_guild_id = channel.guild_id.clone();
+    let channel = Channel {
         id: channel_id,
         name: channel.name,
-        guild_id: bigdecimal_to_u128!(channel.guild_id),
+        guild_id: match channel.guild_id {
+            Some(guild_id) => Some(bigdecimal_to_u128!(guild_id)),
+            None => None,
+        },
+        channel_type: ChannelType::from_i16(channel.channel_type).unwrap_or(ChannelType::Text),
+        parent_id: match channel.parent_id {
+            Some(parent_id) => Some(bigdecimal_to_u128!(parent_id)),
+            None => None,
+        },
+        position: channel.position,
+        topic: channel.topic,
+        slowmode: channel.slowmode,
+        nsfw: channel.nsfw,
+        announcement: channel.announcement,
+        message_ttl: channel.message_ttl,
     };
 
-    sqlx::query!(
-        "INSERT INTO messages VALUES ($1, $2, $3, $4)",
-        bigint_message_id,
-        content,
-        bigint_channel_id,
-        bigint_author_id
-    )
-    .execute(db)
-    .await?;
-
     let r = sqlx::query!("SELECT * FROM users WHERE id = $1", bigint_author_id)
         .fetch_one(db)
         .await?;
@@ -73,26 +177,231 @@ pub async fn create_message(
             .and_then(ferrischat_common::types::Pronouns::from_i16),
     };
 
-    let msg_obj = Message {
-        id: message_id,
-        content: Some(content),
-        channel: channel_obj,
-        channel_id,
-        author_id,
-        author: Some(author),
-        edited_at: None,
-        embeds: vec![],
+    if !embeds.is_empty() && !author.flags.contains(UserFlags::BOT_ACCOUNT) {
+        return Err(ErrorJson::new_403("only bots can send embeds".to_string()).into());
+    }
+
+    let mut mentions = parse_mentions(&content);
+
+    // replies have to stay within the channel
+    let mention_author = mention_author.unwrap_or(false);
+    if let Some(reply_to) = reply_to {
+        let target = sqlx::query!(
+            "SELECT author_id FROM messages WHERE id = $1 AND channel_id = $2",
+            u128_to_bigdecimal!(reply_to),
+            bigint_channel_id
+        )
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| {
+            ErrorJson::new_400("replies must be to a message in the same channel".to_string())
+        })?;
+        let target_author_id = bigdecimal_to_u128!(target.author_id);
+        if mention_author
+            && target_author_id != author_id
+            && !mentions.users.contains(&target_author_id)
+        {
+            mentions.users.push(target_author_id);
+        }
+    }
+    let mentions = resolve_mentions(
+        db,
+        &bigint_channel_id,
+        bigint_guild_id.as_ref(),
+        &bigint_author_id,
+        mentions,
+    )
+    .await?;
+
+    Ok(ValidatedMessage {
+        channel,
+        author,
+        content,
         nonce,
-    };
+        embeds,
+        expires_at,
+        reply_to,
+        mention_author,
+        mentions,
+        slowmode,
+    })
+}
+
+/// Sends a message [`validate_message`] accepted, along with its files.
+pub async fn deliver_message(
+    db: &sqlx::PgPool,
+    message: ValidatedMessage,
+    files: Vec<PendingAttachment>,
+) -> Result<Message, WebServerError> {
+    let ValidatedMessage {
+        channel,
+        author,
+        content,
+        nonce,
+        embeds,
+        expires_at,
+        reply_to,
+        mention_author,
+        mentions,
+        slowmode,
+    } = message;
+    let channel_id = channel.id;
+    let author_id = author.id;
+    let bigint_channel_id = u128_to_bigdecimal!(channel_id);
+    let bigint_guild_id = channel.guild_id.map(|id| u128_to_bigdecimal!(id));
+    let bigint_author_id = u128_to_bigdecimal!(author_id);
+
+    let node_id = get_node_id!();
+    let message_id = generate_snowflake::<0>(ModelType::Message as u8, node_id);
+    let bigint_message_id = u128_to_bigdecimal!(message_id);
+
+    let reply_to = load_reply(db, reply_to, mention_author).await?;
+
+    // these count the message as sent, so they wait until nothing else can reject it
+    if slowmode {
+        check_slowmode(channel_id, author_id, channel.slowmode).await?;
+    }
+    if channel.channel_type == ChannelType::Thread {
+        record_thread_activity(db, channel_id, author_id).await?;
+    }
+
+    let attachments = store_attachments(channel_id, files).await?;
+    let msg_obj = insert_message(
+        db,
+        Message {
+            id: message_id,
+            content: Some(content),
+            channel_id,
+            channel,
+            author_id,
+            author: Some(author),
+            edited_at: None,
+            expires_at,
+            embeds,
+            reactions: vec![],
+            attachments,
+            mentions: mentions.users.clone(),
+            mention_roles: mentions.roles.clone(),
+            mention_channels: mentions.channels.clone(),
+            mention_everyone: mentions.everyone,
+            crossposted_from: None,
+            reply_to,
+            nonce,
+        },
+    )
+    .await?;
+
+    update_read_states_for_new_message(
+        db,
+        &bigint_channel_id,
+        bigint_guild_id.as_ref(),
+        &bigint_author_id,
+        &bigint_message_id,
+        &mentions,
+    )
+    .await?;
+
+    tokio::spawn(crate::unfurl::unfurl_message(msg_obj.clone()));
+
+    Ok(msg_obj)
+}
+
+/// Stores a new message and fires `MessageCreate` for it.
+///
+/// This doesn't check anything: callers must make sure the author is allowed to post in the channel.
+/// Attachments must already be in storage. If the message can't be saved, their files are removed.
+pub async fn insert_message(
+    db: &sqlx::PgPool,
+    message: Message,
+) -> Result<Message, WebServerError> {
+    let saved = async {
+        let mut tx = db.begin().await?;
+        save_message(&mut tx, &message).await?;
+        tx.commit().await
+    }
+    .await;
+    if let Err(e) = saved {
+        delete_attachment_files(message.attachments.iter().map(|a| a.id).collect()).await;
+        return Err(e.into());
+    }
 
     let event = WsOutboundEvent::MessageCreate {
-        message: msg_obj.clone(),
+        message: message.clone(),
     };
 
     fire_event(&event).await?;
 
-    Ok(crate::Json {
-        obj: msg_obj,
-        code: 201,
-    })
+    Ok(message)
+}
+
+/// Writes a message and its attachments as part of `tx`.
+///
+/// No event is fired; that's up to the caller once `tx` is committed.
+pub(crate) async fn save_message(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    message: &Message,
+) -> Result<(), sqlx::Error> {
+    let (crosspost_guild_id, crosspost_channel_id, crosspost_message_id) =
+        match message.crossposted_from {
+            Some(ref r) => (
+                match r.guild_id {
+                    Some(guild_id) => Some(u128_to_bigdecimal!(guild_id)),
+                    None => None,
+                },
+                Some(u128_to_bigdecimal!(r.channel_id)),
+                Some(u128_to_bigdecimal!(r.message_id)),
+            ),
+            None => (None, None, None),
+        };
+    let (reply_to_id, reply_mention_author) = match message.reply_to {
+        Some(ref r) => (Some(u128_to_bigdecimal!(r.message_id)), r.mention_author),
+        None => (None, false),
+    };
+    let bigint_message_id = u128_to_bigdecimal!(message.id);
+
+    sqlx::query!(
+        r#"
+INSERT INTO messages (id, content, channel_id, author_id, crosspost_guild_id, crosspost_channel_id,
+                      crosspost_message_id, reply_to_id, reply_mention_author, embeds, mentions,
+                      mention_roles, mention_channels, mention_everyone, expires_at)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+"#,
+        bigint_message_id,
+        message.content,
+        u128_to_bigdecimal!(message.channel_id),
+        u128_to_bigdecimal!(message.author_id),
+        crosspost_guild_id,
+        crosspost_channel_id,
+        crosspost_message_id,
+        reply_to_id,
+        reply_mention_author,
+        Json(&message.embeds) as _,
+        &mention_ids_to_row(&message.mentions)[..],
+        &mention_ids_to_row(&message.mention_roles)[..],
+        &mention_ids_to_row(&message.mention_channels)[..],
+        message.mention_everyone,
+        message.expires_at
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    for attachment in &message.attachments {
+        sqlx::query!(
+            r#"
+INSERT INTO attachments (id, message_id, filename, size, content_type, width, height)
+VALUES ($1, $2, $3, $4, $5, $6, $7)
+"#,
+            u128_to_bigdecimal!(attachment.id),
+            bigint_message_id,
+            attachment.filename,
+            i64::try_from(attachment.size).unwrap_or(i64::MAX),
+            attachment.content_type,
+            attachment.width.and_then(|w| i32::try_from(w).ok()),
+            attachment.height.and_then(|h| i32::try_from(h).ok())
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    Ok(())
 }
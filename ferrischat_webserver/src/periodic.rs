@@ -0,0 +1,25 @@
+use crate::WebServerError;
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+
+/// Runs `task` every `period`, logging how much it got through, or why it failed.
+///
+/// A failed run is only logged: the task is tried again next period.
+/// This never returns, so it should be spawned as its own task.
+pub async fn run_periodically<F, Fut, N>(name: &str, period: Duration, mut task: F)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<N, WebServerError>>,
+    N: Default + PartialEq + Display,
+{
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+        match task().await {
+            Ok(n) if n == N::default() => {}
+            Ok(n) => debug!("{}: handled {}", name, n),
+            Err(e) => error!("{} failed: {}", name, e),
+        }
+    }
+}
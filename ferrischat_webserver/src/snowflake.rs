@@ -0,0 +1,13 @@
+/// Start of snowflake time, in milliseconds since the Unix epoch: 2020-01-01T00:00:00Z.
+///
+/// Snowflakes store their creation time relative to this in their upper 64 bits.
+/// SQL can read the same timestamp back out with the `snowflake_timestamp()` function.
+pub const FERRIS_EPOCH: i64 = 1_577_836_800_000;
+
+/// The smallest snowflake that could have been generated at a Unix timestamp, in seconds.
+pub fn snowflake_at(unix_timestamp: i64) -> u128 {
+    let ms = unix_timestamp
+        .saturating_mul(1000)
+        .saturating_sub(FERRIS_EPOCH);
+    u128::try_from(ms).unwrap_or(0) << 64
+}
@@ -0,0 +1,54 @@
+use super::Storage;
+use bytes::Bytes;
+use std::io::{Error, ErrorKind};
+use std::path::{Component, Path, PathBuf};
+
+/// Stores files in a directory on the local filesystem.
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Turns a key into a path inside the root, refusing anything that could escape it.
+    fn path_for(&self, key: &str) -> std::io::Result<PathBuf> {
+        let key = Path::new(key);
+        if key.components().all(|c| matches!(c, Component::Normal(_))) {
+            Ok(self.root.join(key))
+        } else {
+            Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid storage key {:?}", key),
+            ))
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, data: Bytes) -> std::io::Result<()> {
+        let path = self.path_for(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, data).await
+    }
+
+    async fn get(&self, key: &str) -> std::io::Result<Option<Bytes>> {
+        match tokio::fs::read(self.path_for(key)?).await {
+            Ok(data) => Ok(Some(Bytes::from(data))),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> std::io::Result<()> {
+        match tokio::fs::remove_file(self.path_for(key)?).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
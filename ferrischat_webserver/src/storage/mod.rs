@@ -0,0 +1,77 @@
+//! Where uploaded files are kept.
+//!
+//! Everything goes through the [`Storage`] trait, so a remote backend can be swapped in later
+//! without touching the routes.
+
+mod local;
+
+pub use local::*;
+
+use crate::WebServerError;
+use bytes::Bytes;
+
+pub static STORAGE: std::lazy::SyncOnceCell<Box<dyn Storage>> = std::lazy::SyncOnceCell::new();
+
+/// A place to put files, addressed by `/`-separated keys.
+#[async_trait::async_trait]
+pub trait Storage: Send + Sync {
+    /// Stores `data` under `key`, replacing anything already there.
+    async fn put(&self, key: &str, data: Bytes) -> std::io::Result<()>;
+
+    /// Fetches the file under `key`, or `None` if there isn't one.
+    async fn get(&self, key: &str) -> std::io::Result<Option<Bytes>>;
+
+    /// Removes the file under `key`. Removing a file that doesn't exist isn't an error.
+    async fn delete(&self, key: &str) -> std::io::Result<()>;
+}
+
+/// Sets up the global storage backend.
+///
+/// # Panics
+/// If storage was already set up.
+pub fn init_storage() {
+    let home = std::env::var("FERRISCHAT_HOME").unwrap_or_else(|_| "/etc/ferrischat/".to_string());
+    if STORAGE
+        .set(Box::new(LocalStorage::new(
+            std::path::Path::new(&home).join("uploads"),
+        )))
+        .is_err()
+    {
+        panic!("storage was already set up: did you call init_storage() twice?");
+    }
+}
+
+/// Fetches the global storage backend.
+pub fn get_storage() -> Result<&'static dyn Storage, WebServerError> {
+    STORAGE
+        .get()
+        .map(|s| &**s)
+        .ok_or(WebServerError::MissingStorage)
+}
+
+/// Storage key for an attachment's file.
+pub fn attachment_key(attachment_id: u128) -> String {
+    format!("attachments/{}", attachment_id)
+}
+
+/// Deletes the files behind these attachments once their rows are gone.
+///
+/// Failures are only logged: the attachments are already unreachable, so at worst this leaves files behind.
+pub async fn delete_attachment_files(attachment_ids: Vec<u128>) {
+    let storage = match get_storage() {
+        Ok(s) => s,
+        Err(_) => {
+            error!(
+                "storage is missing: {} attachment files were left behind",
+                attachment_ids.len()
+            );
+            return;
+        }
+    };
+
+    for id in attachment_ids {
+        if let Err(e) = storage.delete(&attachment_key(id)).await {
+            error!(attachment_id = %id, "failed to delete attachment file: {}", e);
+        }
+    }
+}
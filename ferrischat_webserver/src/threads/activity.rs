@@ -0,0 +1,59 @@
+use super::{add_thread_member, load_thread};
+use crate::guilds::member_has_permissions;
+use crate::ws::fire_event;
+use crate::WebServerError;
+use ferrischat_common::perms::Permissions;
+use ferrischat_common::types::{ErrorJson, Thread};
+use ferrischat_common::ws::WsOutboundEvent;
+
+/// Checks a user can post in a thread, and records that they did.
+///
+/// Locked threads only accept messages from moderators.
+/// Posting in an archived thread unarchives it, and makes the author a member of the thread.
+pub async fn record_thread_activity(
+    db: &sqlx::PgPool,
+    thread_id: u128,
+    user_id: u128,
+) -> Result<(), WebServerError> {
+    let old = load_thread(db, thread_id)
+        .await?
+        .ok_or_else(|| ErrorJson::new_404(format!("Unknown thread with ID {}", thread_id)))?;
+
+    if old.locked
+        && !member_has_permissions(
+            db,
+            &u128_to_bigdecimal!(old.guild_id),
+            &u128_to_bigdecimal!(user_id),
+            Permissions::MANAGE_CHANNELS,
+        )
+        .await?
+    {
+        return Err(ErrorJson::new_403("this thread is locked".to_string()).into());
+    }
+
+    sqlx::query!(
+        "UPDATE threads SET archived = false, last_activity_at = now() AT TIME ZONE 'UTC' WHERE channel_id = $1",
+        u128_to_bigdecimal!(thread_id)
+    )
+    .execute(db)
+    .await?;
+
+    let thread = if old.archived {
+        let new = Thread {
+            archived: false,
+            ..old.clone()
+        };
+        let event = WsOutboundEvent::ThreadUpdate {
+            old,
+            new: new.clone(),
+        };
+        fire_event(&event).await?;
+        new
+    } else {
+        old
+    };
+
+    add_thread_member(db, &thread, user_id).await?;
+
+    Ok(())
+}
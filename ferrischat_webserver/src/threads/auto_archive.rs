@@ -0,0 +1,74 @@
+use crate::periodic::run_periodically;
+use crate::ws::fire_event;
+use crate::WebServerError;
+use ferrischat_common::types::Thread;
+use ferrischat_common::ws::WsOutboundEvent;
+use std::time::Duration;
+
+/// How often to look for threads that need archiving.
+const AUTO_ARCHIVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Archives threads that have been inactive for longer than their auto-archive duration.
+///
+/// This never returns, so it should be spawned as its own task.
+pub async fn auto_archive_threads() {
+    run_periodically(
+        "archiving inactive threads",
+        AUTO_ARCHIVE_INTERVAL,
+        archive_inactive_threads,
+    )
+    .await;
+}
+
+async fn archive_inactive_threads() -> Result<usize, WebServerError> {
+    let db = get_db_or_fail!();
+
+    // the update is atomic, so with several webservers running each thread is only archived once
+    let resp = sqlx::query!(
+        r#"
+UPDATE threads t
+SET archived = true
+FROM channels c
+WHERE c.id = t.channel_id
+  AND NOT t.archived
+  AND t.last_activity_at + make_interval(mins => t.auto_archive_duration) < now() AT TIME ZONE 'UTC'
+RETURNING t.*,
+          c.name,
+          c.guild_id AS "guild_id!",
+          c.parent_id AS "parent_id!"
+"#
+    )
+    .fetch_all(db)
+    .await?;
+
+    let n = resp.len();
+    for t in resp {
+        let new = Thread {
+            id: bigdecimal_to_u128!(t.channel_id),
+            guild_id: bigdecimal_to_u128!(t.guild_id),
+            parent_id: bigdecimal_to_u128!(t.parent_id),
+            name: t.name,
+            owner_id: match t.owner_id {
+                Some(owner_id) => Some(bigdecimal_to_u128!(owner_id)),
+                None => None,
+            },
+            starter_message_id: match t.starter_message_id {
+                Some(starter_message_id) => Some(bigdecimal_to_u128!(starter_message_id)),
+                None => None,
+            },
+            archived: true,
+            locked: t.locked,
+            auto_archive_duration: t.auto_archive_duration,
+        };
+        let event = WsOutboundEvent::ThreadUpdate {
+            old: Thread {
+                archived: false,
+                ..new.clone()
+            },
+            new,
+        };
+        fire_event(&event).await?;
+    }
+
+    Ok(n)
+}
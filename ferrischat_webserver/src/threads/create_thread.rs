@@ -0,0 +1,179 @@
+use super::{load_thread, AUTO_ARCHIVE_DURATIONS};
+use crate::guilds::check_member_can_post;
+use crate::ws::fire_event;
+use crate::WebServerError;
+use axum::extract::{Json, Path};
+use ferrischat_common::request_json::ThreadCreateJson;
+use ferrischat_common::types::{ChannelType, ErrorJson, ModelType, Thread};
+use ferrischat_common::ws::WsOutboundEvent;
+use ferrischat_snowflake_generator::generate_snowflake;
+
+/// POST `/v0/channels/{channel_id}/threads`
+pub async fn create_thread(
+    Path(channel_id): Path<u128>,
+    auth: crate::Authorization,
+    Json(thread_info): Json<ThreadCreateJson>,
+) -> Result<crate::Json<Thread>, WebServerError> {
+    if thread_info.name.is_none() {
+        return Err(ErrorJson::new_400("threads need a name".to_string()).into());
+    }
+
+    create_thread_in(channel_id, None, auth.0, thread_info).await
+}
+
+/// POST `/v0/channels/{channel_id}/messages/{message_id}/threads`
+///
+/// If no name is given, the thread is named after the start of the message.
+pub async fn create_thread_from_message(
+    Path((channel_id, message_id)): Path<(u128, u128)>,
+    auth: crate::Authorization,
+    Json(thread_info): Json<ThreadCreateJson>,
+) -> Result<crate::Json<Thread>, WebServerError> {
+    create_thread_in(channel_id, Some(message_id), auth.0, thread_info).await
+}
+
+async fn create_thread_in(
+    channel_id: u128,
+    starter_message_id: Option<u128>,
+    user_id: u128,
+    ThreadCreateJson {
+        name,
+        auto_archive_duration,
+    }: ThreadCreateJson,
+) -> Result<crate::Json<Thread>, WebServerError> {
+    let db = get_db_or_fail!();
+    let bigint_channel_id = u128_to_bigdecimal!(channel_id);
+    let bigint_user_id = u128_to_bigdecimal!(user_id);
+
+    let auto_archive_duration = auto_archive_duration.unwrap_or(1440);
+    if !AUTO_ARCHIVE_DURATIONS.contains(&auto_archive_duration) {
+        return Err(ErrorJson::new_400(format!(
+            "auto_archive_duration must be one of {:?}",
+            AUTO_ARCHIVE_DURATIONS
+        ))
+        .into());
+    }
+
+    let parent = sqlx::query!(
+        "SELECT guild_id, channel_type FROM channels WHERE id = $1",
+        bigint_channel_id
+    )
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| ErrorJson::new_404(format!("Unknown channel with ID {}", channel_id)))?;
+    let guild_id = match (parent.guild_id, parent.channel_type) {
+        (Some(guild_id), t) if t == ChannelType::Text as i16 => guild_id,
+        _ => {
+            return Err(ErrorJson::new_400(
+                "threads can only be created in guild text channels".to_string(),
+            )
+            .into())
+        }
+    };
+
+    check_member_can_post(db, &guild_id, &bigint_user_id).await?;
+
+    let bigint_starter_message_id = match starter_message_id {
+        Some(message_id) => Some(u128_to_bigdecimal!(message_id)),
+        None => None,
+    };
+    let name = match (name, &bigint_starter_message_id) {
+        (Some(name), _) => name,
+        (None, Some(bigint_message_id)) => {
+            let content = sqlx::query!(
+                "SELECT content FROM messages WHERE id = $1 AND channel_id = $2",
+                bigint_message_id,
+                bigint_channel_id
+            )
+            .fetch_optional(db)
+            .await?
+            .ok_or_else(|| ErrorJson::new_404("Unknown message".to_string()))?
+            .content
+            .unwrap_or_default();
+            Some(content.chars().take(100).collect::<String>())
+                .filter(|n| !n.trim().is_empty())
+                .unwrap_or_else(|| "thread".to_string())
+        }
+        (None, None) => unreachable!("standalone threads always have a name"),
+    };
+    if name.trim().is_empty() || name.chars().count() > 100 {
+        return Err(ErrorJson::new_400(
+            "thread names must be 1 to 100 characters long".to_string(),
+        )
+        .into());
+    }
+
+    if let Some(ref bigint_message_id) = bigint_starter_message_id {
+        let message = sqlx::query!(
+            r#"
+SELECT EXISTS(SELECT * FROM threads WHERE starter_message_id = m.id) AS "has_thread!"
+FROM messages m
+WHERE m.id = $1
+  AND m.channel_id = $2
+"#,
+            bigint_message_id,
+            bigint_channel_id
+        )
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ErrorJson::new_404("Unknown message".to_string()))?;
+        if message.has_thread {
+            return Err(ErrorJson::new_409(
+                "a thread was already started from this message".to_string(),
+            )
+            .into());
+        }
+    }
+
+    let node_id = get_node_id!();
+    let thread_id = generate_snowflake::<0>(ModelType::Channel as u8, node_id);
+    let bigint_thread_id = u128_to_bigdecimal!(thread_id);
+
+    let mut tx = db.begin().await?;
+
+    sqlx::query!(
+        "INSERT INTO channels (id, name, guild_id, channel_type, parent_id) VALUES ($1, $2, $3, $4, $5)",
+        bigint_thread_id,
+        name,
+        guild_id,
+        ChannelType::Thread as i16,
+        bigint_channel_id
+    )
+    .execute(&mut tx)
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO threads (channel_id, owner_id, starter_message_id, auto_archive_duration) VALUES ($1, $2, $3, $4)",
+        bigint_thread_id,
+        bigint_user_id,
+        bigint_starter_message_id,
+        auto_archive_duration
+    )
+    .execute(&mut tx)
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO thread_members (thread_id, user_id) VALUES ($1, $2)",
+        bigint_thread_id,
+        bigint_user_id
+    )
+    .execute(&mut tx)
+    .await?;
+
+    tx.commit().await?;
+
+    let thread = load_thread(db, thread_id)
+        .await?
+        .ok_or_else(|| ErrorJson::new_404("thread just disappeared".to_string()))?;
+
+    let event = WsOutboundEvent::ThreadCreate {
+        thread: thread.clone(),
+    };
+
+    fire_event(&event).await?;
+
+    Ok(crate::Json {
+        obj: thread,
+        code: 201,
+    })
+}
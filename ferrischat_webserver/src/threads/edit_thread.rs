@@ -0,0 +1,122 @@
+use super::{load_thread, AUTO_ARCHIVE_DURATIONS};
+use crate::guilds::member_has_permissions;
+use crate::ws::fire_event;
+use crate::WebServerError;
+use axum::extract::{Json, Path};
+use ferrischat_common::perms::Permissions;
+use ferrischat_common::request_json::ThreadUpdateJson;
+use ferrischat_common::types::{ErrorJson, Thread};
+use ferrischat_common::ws::WsOutboundEvent;
+
+/// PATCH `/v0/threads/{thread_id}`
+///
+/// The thread's owner can rename, archive and unarchive it.
+/// Locking a thread, or touching one that's locked, needs the manage channels permission.
+pub async fn edit_thread(
+    Path(thread_id): Path<u128>,
+    auth: crate::Authorization,
+    Json(ThreadUpdateJson {
+        name,
+        archived,
+        locked,
+        auto_archive_duration,
+    }): Json<ThreadUpdateJson>,
+) -> Result<crate::Json<Thread>, WebServerError> {
+    let db = get_db_or_fail!();
+    let bigint_thread_id = u128_to_bigdecimal!(thread_id);
+
+    if name
+        .as_ref()
+        .map_or(false, |n| n.trim().is_empty() || n.chars().count() > 100)
+    {
+        return Err(ErrorJson::new_400(
+            "thread names must be 1 to 100 characters long".to_string(),
+        )
+        .into());
+    }
+    if auto_archive_duration.map_or(false, |d| !AUTO_ARCHIVE_DURATIONS.contains(&d)) {
+        return Err(ErrorJson::new_400(format!(
+            "auto_archive_duration must be one of {:?}",
+            AUTO_ARCHIVE_DURATIONS
+        ))
+        .into());
+    }
+
+    let old = load_thread(db, thread_id)
+        .await?
+        .ok_or_else(|| ErrorJson::new_404(format!("Unknown thread with ID {}", thread_id)))?;
+
+    let is_moderator = member_has_permissions(
+        db,
+        &u128_to_bigdecimal!(old.guild_id),
+        &u128_to_bigdecimal!(auth.0),
+        Permissions::MANAGE_CHANNELS,
+    )
+    .await?;
+    if !is_moderator {
+        if old.owner_id != Some(auth.0) {
+            return Err(ErrorJson::new_403(
+                "only the thread's owner or a moderator can edit it".to_string(),
+            )
+            .into());
+        }
+        if old.locked || locked.is_some() {
+            return Err(ErrorJson::new_403(
+                "only moderators can lock threads or edit locked ones".to_string(),
+            )
+            .into());
+        }
+    }
+
+    let mut tx = db.begin().await?;
+
+    if let Some(ref name) = name {
+        sqlx::query!(
+            "UPDATE channels SET name = $1 WHERE id = $2",
+            name,
+            bigint_thread_id
+        )
+        .execute(&mut tx)
+        .await?;
+    }
+
+    // unarchiving counts as activity, so the thread doesn't get archived again straight away
+    sqlx::query!(
+        r#"
+UPDATE threads
+SET archived              = coalesce($2, archived),
+    locked                = coalesce($3, locked),
+    auto_archive_duration = coalesce($4, auto_archive_duration),
+    last_activity_at      = CASE
+                                WHEN archived AND NOT coalesce($2, archived)
+                                    THEN now() AT TIME ZONE 'UTC'
+                                ELSE last_activity_at
+                            END
+WHERE channel_id = $1
+"#,
+        bigint_thread_id,
+        archived,
+        locked,
+        auto_archive_duration
+    )
+    .execute(&mut tx)
+    .await?;
+
+    tx.commit().await?;
+
+    let new = load_thread(db, thread_id)
+        .await?
+        .ok_or_else(|| ErrorJson::new_404(format!("Unknown thread with ID {}", thread_id)))?;
+
+    let event = WsOutboundEvent::ThreadUpdate {
+        old,
+        new: new.clone(),
+    };
+
+    fire_event(&event).await?;
+
+    Ok(crate::Json {
+        obj: new,
+        code: 200,
+    })
+}
@@ -0,0 +1,70 @@
+use crate::WebServerError;
+use axum::extract::{Path, Query};
+use ferrischat_common::request_json::GetThreadsUrlParams;
+use ferrischat_common::types::{ErrorJson, Thread};
+
+/// GET `/v0/channels/{channel_id}/threads`
+///
+/// Only active threads are returned unless `?archived=true` is passed,
+/// in which case only archived ones are. Most recently active threads come first.
+pub async fn get_threads(
+    Path(channel_id): Path<u128>,
+    Query(GetThreadsUrlParams { archived }): Query<GetThreadsUrlParams>,
+    _: crate::Authorization,
+) -> Result<crate::Json<Vec<Thread>>, WebServerError> {
+    let db = get_db_or_fail!();
+    let bigint_channel_id = u128_to_bigdecimal!(channel_id);
+
+    let guild_id = sqlx::query!(
+        "SELECT guild_id FROM channels WHERE id = $1",
+        bigint_channel_id
+    )
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| ErrorJson::new_404(format!("Unknown channel with ID {}", channel_id)))?
+    .guild_id
+    .ok_or_else(|| ErrorJson::new_400("DM channels can't have threads".to_string()))?;
+    let guild_id = bigdecimal_to_u128!(guild_id);
+
+    let resp = sqlx::query!(
+        r#"
+SELECT t.*,
+       c.name
+FROM threads t
+    INNER JOIN channels c ON c.id = t.channel_id
+WHERE c.parent_id = $1
+  AND t.archived = $2
+ORDER BY t.last_activity_at DESC
+"#,
+        bigint_channel_id,
+        archived.unwrap_or(false)
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut threads = Vec::with_capacity(resp.len());
+    for t in resp {
+        threads.push(Thread {
+            id: bigdecimal_to_u128!(t.channel_id),
+            guild_id,
+            parent_id: channel_id,
+            name: t.name,
+            owner_id: match t.owner_id {
+                Some(owner_id) => Some(bigdecimal_to_u128!(owner_id)),
+                None => None,
+            },
+            starter_message_id: match t.starter_message_id {
+                Some(starter_message_id) => Some(bigdecimal_to_u128!(starter_message_id)),
+                None => None,
+            },
+            archived: t.archived,
+            locked: t.locked,
+            auto_archive_duration: t.auto_archive_duration,
+        });
+    }
+
+    Ok(crate::Json {
+        obj: threads,
+        code: 200,
+    })
+}
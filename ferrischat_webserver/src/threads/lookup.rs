@@ -0,0 +1,50 @@
+use crate::WebServerError;
+use ferrischat_common::types::Thread;
+
+/// Lengths of inactivity, in minutes, that threads can be set to archive after.
+pub const AUTO_ARCHIVE_DURATIONS: [i32; 4] = [60, 1440, 4320, 10080];
+
+/// Fetches a thread.
+///
+/// Returns `None` if there's no thread with this ID: other kinds of channels don't count.
+pub async fn load_thread(
+    db: &sqlx::PgPool,
+    thread_id: u128,
+) -> Result<Option<Thread>, WebServerError> {
+    let t = match sqlx::query!(
+        r#"
+SELECT t.*,
+       c.name,
+       c.guild_id AS "guild_id!",
+       c.parent_id AS "parent_id!"
+FROM threads t
+    INNER JOIN channels c ON c.id = t.channel_id
+WHERE t.channel_id = $1
+"#,
+        u128_to_bigdecimal!(thread_id)
+    )
+    .fetch_optional(db)
+    .await?
+    {
+        Some(t) => t,
+        None => return Ok(None),
+    };
+
+    Ok(Some(Thread {
+        id: thread_id,
+        guild_id: bigdecimal_to_u128!(t.guild_id),
+        parent_id: bigdecimal_to_u128!(t.parent_id),
+        name: t.name,
+        owner_id: match t.owner_id {
+            Some(owner_id) => Some(bigdecimal_to_u128!(owner_id)),
+            None => None,
+        },
+        starter_message_id: match t.starter_message_id {
+            Some(starter_message_id) => Some(bigdecimal_to_u128!(starter_message_id)),
+            None => None,
+        },
+        archived: t.archived,
+        locked: t.locked,
+        auto_archive_duration: t.auto_archive_duration,
+    }))
+}
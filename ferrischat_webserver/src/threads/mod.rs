@@ -0,0 +1,47 @@
+mod activity;
+mod auto_archive;
+mod create_thread;
+mod edit_thread;
+mod get_threads;
+mod lookup;
+mod thread_members;
+
+pub use activity::*;
+pub use auto_archive::*;
+pub use create_thread::*;
+pub use edit_thread::*;
+pub use get_threads::*;
+pub use lookup::*;
+pub use thread_members::*;
+
+use axum::routing::{get, patch, post, put};
+use axum::Router;
+
+pub fn generate_threads_routes() -> axum::Router {
+    debug!("generating routes for threads");
+    Router::new()
+        // POST   /channels/:channel_id/threads
+        // GET    /channels/:channel_id/threads
+        .route(
+            expand_version!("channels/:channel_id/threads"),
+            post(create_thread).get(get_threads),
+        )
+        // POST   /channels/:channel_id/messages/:message_id/threads
+        .route(
+            expand_version!("channels/:channel_id/messages/:message_id/threads"),
+            post(create_thread_from_message),
+        )
+        // PATCH  /threads/:thread_id
+        .route(expand_version!("threads/:thread_id"), patch(edit_thread))
+        // GET    /threads/:thread_id/members
+        .route(
+            expand_version!("threads/:thread_id/members"),
+            get(get_thread_members),
+        )
+        // PUT    /threads/:thread_id/members/:user_id
+        // DELETE /threads/:thread_id/members/:user_id
+        .route(
+            expand_version!("threads/:thread_id/members/:user_id"),
+            put(add_thread_member_route).delete(remove_thread_member),
+        )
+}
@@ -0,0 +1,191 @@
+use super::load_thread;
+use crate::guilds::member_has_permissions;
+use crate::ws::fire_event;
+use crate::WebServerError;
+use axum::extract::Path;
+use ferrischat_common::perms::Permissions;
+use ferrischat_common::types::{ErrorJson, Thread, ThreadMember};
+use ferrischat_common::ws::WsOutboundEvent;
+
+/// Adds a user to a thread, firing `ThreadMembersUpdate` if they weren't already in it.
+///
+/// Returns `None` if the user was already a member.
+pub async fn add_thread_member(
+    db: &sqlx::PgPool,
+    thread: &Thread,
+    user_id: u128,
+) -> Result<Option<ThreadMember>, WebServerError> {
+    let member = match sqlx::query!(
+        "INSERT INTO thread_members (thread_id, user_id) VALUES ($1, $2) ON CONFLICT DO NOTHING RETURNING joined_at",
+        u128_to_bigdecimal!(thread.id),
+        u128_to_bigdecimal!(user_id)
+    )
+    .fetch_optional(db)
+    .await?
+    {
+        Some(m) => ThreadMember {
+            thread_id: thread.id,
+            user_id,
+            joined_at: m.joined_at,
+        },
+        None => return Ok(None),
+    };
+
+    let event = WsOutboundEvent::ThreadMembersUpdate {
+        thread: thread.clone(),
+        added_members: vec![member.clone()],
+        removed_member_ids: vec![],
+    };
+
+    fire_event(&event).await?;
+
+    Ok(Some(member))
+}
+
+/// GET `/v0/threads/{thread_id}/members`
+pub async fn get_thread_members(
+    Path(thread_id): Path<u128>,
+    _: crate::Authorization,
+) -> Result<crate::Json<Vec<ThreadMember>>, WebServerError> {
+    let db = get_db_or_fail!();
+
+    if load_thread(db, thread_id).await?.is_none() {
+        return Err(ErrorJson::new_404(format!("Unknown thread with ID {}", thread_id)).into());
+    }
+
+    let resp = sqlx::query!(
+        "SELECT * FROM thread_members WHERE thread_id = $1 ORDER BY joined_at",
+        u128_to_bigdecimal!(thread_id)
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut members = Vec::with_capacity(resp.len());
+    for m in resp {
+        members.push(ThreadMember {
+            thread_id,
+            user_id: bigdecimal_to_u128!(m.user_id),
+            joined_at: m.joined_at,
+        });
+    }
+
+    Ok(crate::Json {
+        obj: members,
+        code: 200,
+    })
+}
+
+/// PUT `/v0/threads/{thread_id}/members/{user_id}`
+///
+/// Anyone in the guild can join a thread. Adding somebody else requires being in the thread yourself.
+pub async fn add_thread_member_route(
+    Path((thread_id, user_id)): Path<(u128, u128)>,
+    auth: crate::Authorization,
+) -> Result<crate::Json<ThreadMember>, WebServerError> {
+    let db = get_db_or_fail!();
+
+    let thread = load_thread(db, thread_id)
+        .await?
+        .ok_or_else(|| ErrorJson::new_404(format!("Unknown thread with ID {}", thread_id)))?;
+    let bigint_guild_id = u128_to_bigdecimal!(thread.guild_id);
+
+    if thread.archived {
+        return Err(ErrorJson::new_400("this thread is archived".to_string()).into());
+    }
+
+    if user_id != auth.0
+        && !sqlx::query!(
+            r#"SELECT EXISTS(SELECT * FROM thread_members WHERE thread_id = $1 AND user_id = $2) AS "exists!""#,
+            u128_to_bigdecimal!(thread_id),
+            u128_to_bigdecimal!(auth.0)
+        )
+        .fetch_one(db)
+        .await?
+        .exists
+    {
+        return Err(ErrorJson::new_403(
+            "you must be in a thread to add other users to it".to_string(),
+        )
+        .into());
+    }
+
+    if !sqlx::query!(
+        r#"SELECT EXISTS(SELECT * FROM members WHERE guild_id = $1 AND user_id = $2) AS "exists!""#,
+        bigint_guild_id,
+        u128_to_bigdecimal!(user_id)
+    )
+    .fetch_one(db)
+    .await?
+    .exists
+    {
+        return Err(ErrorJson::new_404(format!(
+            "Unknown member with ID {} in {}",
+            user_id, thread.guild_id
+        ))
+        .into());
+    }
+
+    match add_thread_member(db, &thread, user_id).await? {
+        Some(member) => Ok(crate::Json {
+            obj: member,
+            code: 201,
+        }),
+        None => Err(ErrorJson::new_409("this user is already in the thread".to_string()).into()),
+    }
+}
+
+/// DELETE `/v0/threads/{thread_id}/members/{user_id}`
+///
+/// Users can always leave threads. Removing anyone else needs the thread's owner or a moderator.
+pub async fn remove_thread_member(
+    Path((thread_id, user_id)): Path<(u128, u128)>,
+    auth: crate::Authorization,
+) -> Result<http::StatusCode, WebServerError> {
+    let db = get_db_or_fail!();
+
+    let thread = load_thread(db, thread_id)
+        .await?
+        .ok_or_else(|| ErrorJson::new_404(format!("Unknown thread with ID {}", thread_id)))?;
+
+    if user_id != auth.0
+        && thread.owner_id != Some(auth.0)
+        && !member_has_permissions(
+            db,
+            &u128_to_bigdecimal!(thread.guild_id),
+            &u128_to_bigdecimal!(auth.0),
+            Permissions::MANAGE_CHANNELS,
+        )
+        .await?
+    {
+        return Err(ErrorJson::new_403(
+            "only the thread's owner or a moderator can remove other users".to_string(),
+        )
+        .into());
+    }
+
+    if sqlx::query!(
+        "DELETE FROM thread_members WHERE thread_id = $1 AND user_id = $2 RETURNING user_id",
+        u128_to_bigdecimal!(thread_id),
+        u128_to_bigdecimal!(user_id)
+    )
+    .fetch_optional(db)
+    .await?
+    .is_none()
+    {
+        return Err(ErrorJson::new_404(format!(
+            "Unknown thread member with ID {} in {}",
+            user_id, thread_id
+        ))
+        .into());
+    }
+
+    let event = WsOutboundEvent::ThreadMembersUpdate {
+        thread,
+        added_members: vec![],
+        removed_member_ids: vec![user_id],
+    };
+
+    fire_event(&event).await?;
+
+    Ok(http::StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,64 @@
+use crate::WebServerError;
+use ferrischat_common::types::Embed;
+use ferrischat_redis::REDIS_MANAGER;
+use reqwest::Url;
+
+/// How long a failed unfurl is remembered for, in seconds, so broken links aren't fetched over and over.
+pub const FAILED_UNFURL_TTL: u64 = 600;
+
+/// Cache key for a URL's preview. Keys are grouped by domain so a whole site can be flushed at once.
+fn cache_key(url: &Url) -> String {
+    let digest = ring::digest::digest(&ring::digest::SHA256, url.as_str().as_bytes());
+    let hash: String = digest
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("unfurl:{}:{}", url.host_str().unwrap_or_default(), hash)
+}
+
+/// Looks up a URL's preview.
+///
+/// The outer `None` means it isn't cached. The inner one means the URL was tried and had nothing to show.
+pub async fn get_cached_unfurl(url: &Url) -> Result<Option<Option<Embed>>, WebServerError> {
+    let mut redis = REDIS_MANAGER
+        .get()
+        .ok_or(WebServerError::MissingRedis)?
+        .get()
+        .await?;
+
+    let cached = ferrischat_redis::redis::cmd("GET")
+        .arg(cache_key(url))
+        .query_async::<_, Option<Vec<u8>>>(&mut redis)
+        .await?;
+    match cached {
+        Some(mut json) => Ok(Some(simd_json::from_slice(&mut json)?)),
+        None => Ok(None),
+    }
+}
+
+/// Remembers a URL's preview, or that it didn't have one.
+pub async fn cache_unfurl(
+    url: &Url,
+    embed: &Option<Embed>,
+    ttl: u64,
+) -> Result<(), WebServerError> {
+    let mut redis = REDIS_MANAGER
+        .get()
+        .ok_or(WebServerError::MissingRedis)?
+        .get()
+        .await?;
+
+    ferrischat_redis::redis::cmd("SET")
+        .arg(cache_key(url))
+        .arg(simd_json::to_string(embed)?)
+        .arg("EX")
+        .arg(if embed.is_some() {
+            ttl
+        } else {
+            FAILED_UNFURL_TTL.min(ttl)
+        })
+        .query_async::<_, ()>(&mut redis)
+        .await?;
+    Ok(())
+}
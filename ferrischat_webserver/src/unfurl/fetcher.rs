@@ -0,0 +1,111 @@
+use super::resolve_public;
+use bytes::Bytes;
+use http::header::{ACCEPT, CONTENT_TYPE, LOCATION};
+use reqwest::redirect::Policy;
+use reqwest::Url;
+use std::time::Duration;
+
+/// Most redirects followed before giving up on a page.
+pub const MAX_REDIRECTS: usize = 3;
+const USER_AGENT: &str = concat!(
+    "Mozilla/5.0 (compatible; FerrisChat/",
+    env!("CARGO_PKG_VERSION"),
+    "; +https://ferris.chat)"
+);
+
+/// A page fetched for unfurling.
+pub struct FetchedPage {
+    /// Where the page ended up, after any redirects.
+    pub url: Url,
+    pub content_type: String,
+    /// The start of the body, cut off at the size limit.
+    pub body: Bytes,
+}
+
+/// Fetches pages to build link previews out of.
+///
+/// Implementations are responsible for refusing to fetch anything that isn't on the public internet.
+#[async_trait::async_trait]
+pub trait PageFetcher: Send + Sync {
+    async fn fetch(&self, url: &Url) -> Result<FetchedPage, String>;
+}
+
+/// Fetches pages over HTTP, refusing to connect to anything that isn't public.
+pub struct HttpFetcher {
+    pub timeout: Duration,
+    /// Most bytes of a body that are read. Anything after that is dropped.
+    pub max_size: usize,
+}
+
+impl HttpFetcher {
+    async fn fetch_following_redirects(&self, url: &Url) -> Result<FetchedPage, String> {
+        let mut url = url.clone();
+        for _ in 0..=MAX_REDIRECTS {
+            // every hop is checked again, and pinned to the address that was checked
+            let addr = resolve_public(&url).await?;
+            // a proxy would make the connection somewhere other than the checked address
+            let mut builder = reqwest::Client::builder()
+                .no_proxy()
+                .redirect(Policy::none())
+                .user_agent(USER_AGENT);
+            if let Some(domain) = url.domain() {
+                builder = builder.resolve(domain, addr);
+            }
+            let client = builder.build().map_err(|e| e.to_string())?;
+
+            let mut resp = client
+                .get(url.clone())
+                .header(ACCEPT, "text/html, application/json;q=0.9")
+                .send()
+                .await
+                .map_err(|e| e.to_string())?;
+
+            if resp.status().is_redirection() {
+                let location = resp
+                    .headers()
+                    .get(LOCATION)
+                    .and_then(|l| l.to_str().ok())
+                    .ok_or_else(|| "redirect without a location".to_string())?;
+                url = url.join(location).map_err(|e| e.to_string())?;
+                continue;
+            }
+            if !resp.status().is_success() {
+                return Err(format!("{} returned {}", url, resp.status()));
+            }
+
+            let content_type = resp
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default()
+                .to_string();
+
+            // metadata is near the top of a page, so a cut off one is still useful
+            let mut body = Vec::new();
+            while let Some(chunk) = resp.chunk().await.map_err(|e| e.to_string())? {
+                let remaining = self.max_size - body.len();
+                if chunk.len() >= remaining {
+                    body.extend_from_slice(&chunk[..remaining]);
+                    break;
+                }
+                body.extend_from_slice(&chunk);
+            }
+
+            return Ok(FetchedPage {
+                url,
+                content_type,
+                body: body.into(),
+            });
+        }
+        Err(format!("more than {} redirects", MAX_REDIRECTS))
+    }
+}
+
+#[async_trait::async_trait]
+impl PageFetcher for HttpFetcher {
+    async fn fetch(&self, url: &Url) -> Result<FetchedPage, String> {
+        tokio::time::timeout(self.timeout, self.fetch_following_redirects(url))
+            .await
+            .map_err(|_| format!("timed out fetching {}", url))?
+    }
+}
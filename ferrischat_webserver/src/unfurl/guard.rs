@@ -0,0 +1,155 @@
+use reqwest::Url;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use url::Host;
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // "this network"
+        || a == 0
+        // carrier-grade NAT
+        || (a == 100 && (64..128).contains(&b))
+        // IETF protocol assignments
+        || (a == 192 && b == 0 && c == 0)
+        // benchmarking
+        || (a == 198 && (b == 18 || b == 19))
+        // reserved
+        || a >= 240)
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    let segments = ip.segments();
+    // mapped IPv4 addresses reach the same place as the address they map
+    if let [0, 0, 0, 0, 0, 0xffff, hi, lo] = segments {
+        return is_public_ipv4(Ipv4Addr::from((u32::from(hi) << 16) | u32::from(lo)));
+    }
+    !(ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // unique local
+        || (segments[0] & 0xfe00) == 0xfc00
+        // link local
+        || (segments[0] & 0xffc0) == 0xfe80
+        // documentation
+        || (segments[0] == 0x2001 && segments[1] == 0x0db8)
+        // NAT64, which can reach anything IPv4 can
+        || (segments[0] == 0x0064 && segments[1] == 0xff9b))
+}
+
+/// Whether an address is on the public internet, rather than loopback, a private range or anything else
+/// the server shouldn't be tricked into connecting to.
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => is_public_ipv6(ip),
+    }
+}
+
+/// Resolves the host of a URL, making sure every address it resolves to is public.
+///
+/// Connections should be made to the address this returns, so the host can't resolve somewhere else the second time.
+pub async fn resolve_public(url: &Url) -> Result<SocketAddr, String> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("unsupported scheme {}", url.scheme()));
+    }
+    let port = url
+        .port_or_known_default()
+        .ok_or_else(|| "URL has no port".to_string())?;
+
+    let addrs: Vec<SocketAddr> = match url.host() {
+        Some(Host::Ipv4(ip)) => vec![SocketAddr::new(IpAddr::V4(ip), port)],
+        Some(Host::Ipv6(ip)) => vec![SocketAddr::new(IpAddr::V6(ip), port)],
+        Some(Host::Domain(domain)) => tokio::net::lookup_host((domain, port))
+            .await
+            .map_err(|e| format!("failed to resolve {}: {}", domain, e))?
+            .collect(),
+        None => return Err("URL has no host".to_string()),
+    };
+
+    if addrs.iter().any(|addr| !is_public_ip(addr.ip())) {
+        return Err(format!("{} resolves to a non-public address", url));
+    }
+    addrs
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("{} doesn't resolve to anything", url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_public_ip;
+    use std::net::IpAddr;
+
+    fn ip(addr: &str) -> IpAddr {
+        addr.parse().unwrap()
+    }
+
+    #[test]
+    fn public_addresses_are_allowed() {
+        for addr in ["1.1.1.1", "93.184.216.34", "2606:4700:4700::1111"] {
+            assert!(is_public_ip(ip(addr)), "{} should be public", addr);
+        }
+    }
+
+    #[test]
+    fn private_ipv4_ranges_are_refused() {
+        for addr in [
+            "10.0.0.1",
+            "172.16.0.1",
+            "192.168.1.1",
+            "127.0.0.1",
+            "169.254.169.254",
+            "0.0.0.0",
+            "255.255.255.255",
+            "224.0.0.1",
+            "192.0.0.8",
+            "198.18.0.1",
+            "240.0.0.1",
+        ] {
+            assert!(!is_public_ip(ip(addr)), "{} should not be public", addr);
+        }
+    }
+
+    #[test]
+    fn carrier_grade_nat_is_refused() {
+        assert!(!is_public_ip(ip("100.64.0.1")));
+        assert!(!is_public_ip(ip("100.127.255.254")));
+        // either side of the range is fine
+        assert!(is_public_ip(ip("100.63.255.255")));
+        assert!(is_public_ip(ip("100.128.0.1")));
+    }
+
+    #[test]
+    fn private_ipv6_ranges_are_refused() {
+        for addr in [
+            "::1",
+            "::",
+            "fc00::1",
+            "fd12:3456::1",
+            "fe80::1",
+            "ff02::1",
+            "2001:db8::1",
+        ] {
+            assert!(!is_public_ip(ip(addr)), "{} should not be public", addr);
+        }
+    }
+
+    #[test]
+    fn nat64_is_refused() {
+        assert!(!is_public_ip(ip("64:ff9b::a00:1")));
+        assert!(!is_public_ip(ip("64:ff9b::101:101")));
+    }
+
+    #[test]
+    fn mapped_ipv4_is_checked_as_ipv4() {
+        assert!(!is_public_ip(ip("::ffff:127.0.0.1")));
+        assert!(!is_public_ip(ip("::ffff:10.0.0.1")));
+        assert!(is_public_ip(ip("::ffff:1.1.1.1")));
+    }
+}
@@ -0,0 +1,383 @@
+//! Link previews for URLs in messages.
+//!
+//! Pages are fetched in the background after a message is sent. Whatever previews come out of them are
+//! appended to the message's embeds, and announced with a `MessageUpdate`.
+
+mod cache;
+mod fetcher;
+mod guard;
+mod parse;
+
+pub use cache::*;
+pub use fetcher::*;
+pub use guard::*;
+pub use parse::*;
+
+use crate::messages::{
+    load_embeds, MAX_EMBEDS_PER_MESSAGE, MAX_EMBED_TITLE_LENGTH, MAX_EMBED_URL_LENGTH,
+};
+use crate::ws::fire_event;
+use crate::WebServerError;
+use ferrischat_common::types::{Embed, EmbedFooter, EmbedImage, Message};
+use ferrischat_common::ws::WsOutboundEvent;
+use reqwest::Url;
+use sqlx::types::Json;
+use std::time::Duration;
+
+/// Longest description shown in a preview, in characters.
+pub const PREVIEW_DESCRIPTION_LENGTH: usize = 350;
+
+pub static FETCHER: std::lazy::SyncOnceCell<Box<dyn PageFetcher>> = std::lazy::SyncOnceCell::new();
+
+/// Sets up the fetcher used for link previews.
+///
+/// # Panics
+/// If the fetcher was already set up.
+pub fn init_unfurler() {
+    let (timeout, max_size) = ferrischat_config::GLOBAL_CONFIG
+        .get()
+        .map_or((5000, 1024 * 1024), |cfg| {
+            (cfg.unfurl.timeout, cfg.unfurl.max_size)
+        });
+    if FETCHER
+        .set(Box::new(HttpFetcher {
+            timeout: Duration::from_millis(timeout),
+            max_size,
+        }))
+        .is_err()
+    {
+        panic!("the unfurler was already set up: did you call init_unfurler() twice?");
+    }
+}
+
+/// Finds the links in a message worth previewing, up to `max` of them.
+///
+/// Wrapping a link in `<>` keeps it from being previewed.
+pub fn extract_urls(content: &str, max: usize) -> Vec<Url> {
+    let mut urls: Vec<Url> = Vec::new();
+    for word in content.split_whitespace() {
+        if urls.len() >= max {
+            break;
+        }
+        if word.starts_with('<') {
+            continue;
+        }
+        let word = word.trim_end_matches(|c| {
+            matches!(
+                c,
+                '.' | ',' | ')' | '!' | '?' | ';' | ':' | '"' | '\'' | '>'
+            )
+        });
+        let start = match word.find("https://").or_else(|| word.find("http://")) {
+            Some(start) => start,
+            None => continue,
+        };
+        if let Ok(url) = Url::parse(&word[start..]) {
+            if !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+    }
+    urls
+}
+
+fn truncate(text: String, max: usize) -> String {
+    if text.chars().count() <= max {
+        text
+    } else {
+        let mut text: String = text.chars().take(max - 1).collect();
+        text.push('…');
+        text
+    }
+}
+
+/// Resolves a link a page points at, keeping it only if it's a reasonable http(s) URL.
+fn resolve_link(base: &Url, link: &str) -> Option<String> {
+    base.join(link)
+        .ok()
+        .filter(|url| matches!(url.scheme(), "http" | "https"))
+        .map(String::from)
+        .filter(|url| url.len() <= MAX_EMBED_URL_LENGTH)
+}
+
+async fn fetch_oembed(fetcher: &dyn PageFetcher, url: &Url) -> Option<OEmbed> {
+    let page = fetcher.fetch(url).await.ok()?;
+    simd_json::from_slice(&mut page.body.to_vec()).ok()
+}
+
+/// Fetches a page and builds a preview of it, if it has anything to show.
+pub async fn fetch_preview(fetcher: &dyn PageFetcher, url: &Url) -> Result<Option<Embed>, String> {
+    let page = fetcher.fetch(url).await?;
+    if !page.content_type.starts_with("text/html") {
+        return Ok(None);
+    }
+
+    let meta = parse_html(&String::from_utf8_lossy(&page.body));
+    let oembed = match meta
+        .oembed_url
+        .as_deref()
+        .and_then(|oembed_url| page.url.join(oembed_url).ok())
+    {
+        Some(oembed_url) => fetch_oembed(fetcher, &oembed_url).await,
+        None => None,
+    };
+
+    let (oembed_title, oembed_site, oembed_image) = match oembed {
+        Some(o) => (o.title, o.provider_name.or(o.author_name), o.thumbnail_url),
+        None => (None, None, None),
+    };
+    let title = meta.title.or(oembed_title);
+    if title.is_none() && meta.description.is_none() {
+        return Ok(None);
+    }
+
+    Ok(Some(Embed {
+        title: title.map(|t| truncate(t, MAX_EMBED_TITLE_LENGTH)),
+        description: meta
+            .description
+            .map(|d| truncate(d, PREVIEW_DESCRIPTION_LENGTH)),
+        url: meta
+            .url
+            .and_then(|u| resolve_link(&page.url, &u))
+            .or_else(|| Some(page.url.to_string())),
+        color: meta.color,
+        fields: vec![],
+        footer: meta.site_name.or(oembed_site).map(|text| EmbedFooter {
+            text: truncate(text, MAX_EMBED_TITLE_LENGTH),
+            icon_url: None,
+        }),
+        image: None,
+        thumbnail: meta
+            .image
+            .or(oembed_image)
+            .and_then(|i| resolve_link(&page.url, &i))
+            .map(|url| EmbedImage { url }),
+    }))
+}
+
+/// Previews the links in a freshly sent message. Meant to be spawned off once the message is stored.
+pub async fn unfurl_message(message: Message) {
+    let message_id = message.id;
+    if let Err(e) = try_unfurl_message(message).await {
+        error!(%message_id, "failed to unfurl links: {}", e);
+    }
+}
+
+async fn try_unfurl_message(message: Message) -> Result<(), WebServerError> {
+    let config = ferrischat_config::GLOBAL_CONFIG
+        .get()
+        .map(|cfg| cfg.unfurl.clone())
+        .unwrap_or_default();
+    let fetcher = match FETCHER.get() {
+        Some(fetcher) if config.enabled => &**fetcher,
+        _ => return Ok(()),
+    };
+
+    let room = MAX_EMBEDS_PER_MESSAGE.saturating_sub(message.embeds.len());
+    let urls = match message.content {
+        Some(ref content) => extract_urls(content, config.max_urls_per_message.min(room)),
+        None => return Ok(()),
+    };
+
+    let mut embeds = Vec::with_capacity(urls.len());
+    for url in urls {
+        let embed = match get_cached_unfurl(&url).await? {
+            Some(embed) => embed,
+            None => {
+                let embed = fetch_preview(fetcher, &url).await.unwrap_or_else(|e| {
+                    debug!(%url, "failed to unfurl link: {}", e);
+                    None
+                });
+                cache_unfurl(&url, &embed, config.cache_ttl).await?;
+                embed
+            }
+        };
+        embeds.extend(embed);
+    }
+    if embeds.is_empty() {
+        return Ok(());
+    }
+
+    let db = get_db_or_fail!();
+    let updated = match sqlx::query!(
+        "UPDATE messages SET embeds = embeds || $1 WHERE id = $2 RETURNING content, edited_at, embeds",
+        Json(&embeds) as _,
+        u128_to_bigdecimal!(message.id)
+    )
+    .fetch_optional(db)
+    .await?
+    {
+        Some(updated) => updated,
+        // it was deleted while the links were being fetched
+        None => return Ok(()),
+    };
+
+    let mut new = message.clone();
+    new.content = updated.content;
+    new.edited_at = updated.edited_at;
+    new.embeds = load_embeds(updated.embeds)?;
+
+    let event = WsOutboundEvent::MessageUpdate { old: message, new };
+    fire_event(&event).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract_urls, fetch_preview, FetchedPage, PageFetcher};
+    use reqwest::Url;
+    use std::collections::HashMap;
+
+    #[test]
+    fn finds_links_in_text() {
+        let urls = extract_urls(
+            "look at https://example.com/a and http://example.org/b?c=d#e too",
+            5,
+        );
+        assert_eq!(
+            urls.iter().map(Url::as_str).collect::<Vec<_>>(),
+            ["https://example.com/a", "http://example.org/b?c=d#e"]
+        );
+    }
+
+    #[test]
+    fn skips_wrapped_links() {
+        let urls = extract_urls("<https://example.com/hidden> https://example.com/shown", 5);
+        assert_eq!(
+            urls.iter().map(Url::as_str).collect::<Vec<_>>(),
+            ["https://example.com/shown"]
+        );
+    }
+
+    #[test]
+    fn strips_trailing_punctuation() {
+        let urls = extract_urls(
+            "(see https://example.com/a), \"https://example.com/b\". https://example.com/c?!",
+            5,
+        );
+        assert_eq!(
+            urls.iter().map(Url::as_str).collect::<Vec<_>>(),
+            [
+                "https://example.com/a",
+                "https://example.com/b",
+                "https://example.com/c"
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_duplicates_and_stops_at_max() {
+        let urls = extract_urls(
+            "https://example.com/a https://example.com/a https://example.com/b https://example.com/c",
+            2,
+        );
+        assert_eq!(
+            urls.iter().map(Url::as_str).collect::<Vec<_>>(),
+            ["https://example.com/a", "https://example.com/b"]
+        );
+        assert!(extract_urls("ftp://example.com nothing here", 5).is_empty());
+    }
+
+    /// Serves canned pages instead of going over the network.
+    struct StubFetcher {
+        pages: HashMap<&'static str, (&'static str, &'static str)>,
+    }
+
+    #[async_trait::async_trait]
+    impl PageFetcher for StubFetcher {
+        async fn fetch(&self, url: &Url) -> Result<FetchedPage, String> {
+            let (content_type, body) = self
+                .pages
+                .get(url.as_str())
+                .ok_or_else(|| format!("{} not found", url))?;
+            Ok(FetchedPage {
+                url: url.clone(),
+                content_type: content_type.to_string(),
+                body: body.as_bytes().to_vec().into(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn previews_opengraph_pages() {
+        let fetcher = StubFetcher {
+            pages: HashMap::from([(
+                "https://example.com/page",
+                (
+                    "text/html; charset=utf-8",
+                    r#"<head>
+<meta property="og:title" content="A page">
+<meta property="og:description" content="About things">
+<meta property="og:site_name" content="Example">
+<meta property="og:image" content="/image.png">
+</head>"#,
+                ),
+            )]),
+        };
+        let embed = fetch_preview(&fetcher, &Url::parse("https://example.com/page").unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(embed.title.as_deref(), Some("A page"));
+        assert_eq!(embed.description.as_deref(), Some("About things"));
+        assert_eq!(embed.url.as_deref(), Some("https://example.com/page"));
+        assert_eq!(embed.footer.map(|f| f.text).as_deref(), Some("Example"));
+        assert_eq!(
+            embed.thumbnail.map(|t| t.url).as_deref(),
+            Some("https://example.com/image.png")
+        );
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_oembed() {
+        let fetcher = StubFetcher {
+            pages: HashMap::from([
+                (
+                    "https://example.com/video",
+                    (
+                        "text/html",
+                        r#"<head><link rel="alternate" type="application/json+oembed" href="/oembed?id=1"></head>"#,
+                    ),
+                ),
+                (
+                    "https://example.com/oembed?id=1",
+                    (
+                        "application/json",
+                        r#"{"title": "A video", "provider_name": "ExampleTube", "thumbnail_url": "https://example.com/thumb.jpg"}"#,
+                    ),
+                ),
+            ]),
+        };
+        let embed = fetch_preview(&fetcher, &Url::parse("https://example.com/video").unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(embed.title.as_deref(), Some("A video"));
+        assert_eq!(embed.footer.map(|f| f.text).as_deref(), Some("ExampleTube"));
+        assert_eq!(
+            embed.thumbnail.map(|t| t.url).as_deref(),
+            Some("https://example.com/thumb.jpg")
+        );
+    }
+
+    #[tokio::test]
+    async fn skips_pages_with_nothing_to_show() {
+        let fetcher = StubFetcher {
+            pages: HashMap::from([
+                ("https://example.com/empty", ("text/html", "<head></head>")),
+                ("https://example.com/file", ("image/png", "not a page")),
+            ]),
+        };
+        for url in ["https://example.com/empty", "https://example.com/file"] {
+            let preview = fetch_preview(&fetcher, &Url::parse(url).unwrap())
+                .await
+                .unwrap();
+            assert!(preview.is_none(), "{} shouldn't have a preview", url);
+        }
+        assert!(fetch_preview(
+            &fetcher,
+            &Url::parse("https://example.com/missing").unwrap()
+        )
+        .await
+        .is_err());
+    }
+}
@@ -0,0 +1,223 @@
+use serde::Deserialize;
+
+/// What a page says about itself, through OpenGraph tags and the like.
+#[derive(Default)]
+pub struct PageMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub url: Option<String>,
+    pub site_name: Option<String>,
+    pub image: Option<String>,
+    pub color: Option<u32>,
+    /// Where the page's oEmbed data is, if it has any.
+    pub oembed_url: Option<String>,
+}
+
+/// The parts of an oEmbed response that make it into a preview.
+#[derive(Deserialize)]
+pub struct OEmbed {
+    pub title: Option<String>,
+    pub author_name: Option<String>,
+    pub provider_name: Option<String>,
+    pub thumbnail_url: Option<String>,
+}
+
+/// Decodes the handful of entities that show up in metadata.
+fn decode_entities(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// Splits the inside of a tag into lowercased attribute names and their values.
+fn parse_attributes(tag: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    let mut rest = tag;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == '/');
+        if rest.is_empty() {
+            return attributes;
+        }
+
+        let name_end = rest
+            .find(|c: char| c.is_ascii_whitespace() || c == '=' || c == '/')
+            .unwrap_or(rest.len());
+        let name = rest[..name_end].to_ascii_lowercase();
+        rest = rest[name_end..].trim_start();
+
+        let value = if let Some(after_eq) = rest.strip_prefix('=') {
+            let after_eq = after_eq.trim_start();
+            let (value, remaining) = match after_eq.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let inner = &after_eq[1..];
+                    let end = inner.find(quote).unwrap_or(inner.len());
+                    (&inner[..end], inner.get(end + 1..).unwrap_or_default())
+                }
+                _ => {
+                    let end = after_eq
+                        .find(|c: char| c.is_ascii_whitespace())
+                        .unwrap_or(after_eq.len());
+                    (&after_eq[..end], &after_eq[end..])
+                }
+            };
+            rest = remaining;
+            decode_entities(value)
+        } else {
+            String::new()
+        };
+
+        if !name.is_empty() {
+            attributes.push((name, value));
+        }
+    }
+}
+
+fn attribute<'a>(attributes: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, v)| v.as_str())
+}
+
+fn parse_color(color: &str) -> Option<u32> {
+    let hex = color.trim().strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
+
+/// Pulls metadata out of the `<head>` of a page.
+///
+/// This only looks at `<title>`, `<meta>` and `<link>` tags, so it doesn't need a real HTML parser.
+/// OpenGraph tags win over their plain HTML equivalents.
+pub fn parse_html(html: &str) -> PageMetadata {
+    let mut meta = PageMetadata::default();
+    let mut html_title = None;
+    let mut html_description = None;
+
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let end = match rest.find('>') {
+            Some(end) => end,
+            None => break,
+        };
+        let tag = &rest[..end];
+        rest = &rest[end + 1..];
+
+        let name_end = tag
+            .find(|c: char| c.is_ascii_whitespace() || c == '/')
+            .unwrap_or(tag.len());
+        let name = tag[..name_end].to_ascii_lowercase();
+        match name.as_str() {
+            "meta" => {
+                let attributes = parse_attributes(&tag[name_end..]);
+                let key = attribute(&attributes, "property")
+                    .or_else(|| attribute(&attributes, "name"))
+                    .map(str::to_ascii_lowercase);
+                let content = match attribute(&attributes, "content") {
+                    Some(content) if !content.trim().is_empty() => content.trim().to_string(),
+                    _ => continue,
+                };
+                match key.as_deref() {
+                    Some("og:title") => meta.title = Some(content),
+                    Some("og:description") => meta.description = Some(content),
+                    Some("og:url") => meta.url = Some(content),
+                    Some("og:site_name") => meta.site_name = Some(content),
+                    Some("og:image" | "og:image:url") if meta.image.is_none() => {
+                        meta.image = Some(content);
+                    }
+                    Some("description") => html_description = Some(content),
+                    Some("theme-color") => meta.color = parse_color(&content),
+                    _ => {}
+                }
+            }
+            "link" => {
+                let attributes = parse_attributes(&tag[name_end..]);
+                if attribute(&attributes, "type")
+                    .map_or(false, |t| t.eq_ignore_ascii_case("application/json+oembed"))
+                {
+                    meta.oembed_url = attribute(&attributes, "href").map(ToString::to_string);
+                }
+            }
+            "title" => {
+                if let Some(close) = rest.to_ascii_lowercase().find("</title") {
+                    let title = decode_entities(rest[..close].trim());
+                    if !title.is_empty() {
+                        html_title = Some(title);
+                    }
+                }
+            }
+            // everything useful is in the head
+            "/head" | "body" => break,
+            _ => {}
+        }
+    }
+
+    meta.title = meta.title.or(html_title);
+    meta.description = meta.description.or(html_description);
+    meta
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_html;
+
+    #[test]
+    fn opengraph_wins_over_plain_html() {
+        let meta = parse_html(
+            r#"<html><head>
+<title>Plain title</title>
+<meta name="description" content="Plain description">
+<meta property="og:title" content="OpenGraph title">
+<meta property="og:description" content="OpenGraph description">
+</head></html>"#,
+        );
+        assert_eq!(meta.title.as_deref(), Some("OpenGraph title"));
+        assert_eq!(meta.description.as_deref(), Some("OpenGraph description"));
+    }
+
+    #[test]
+    fn falls_back_to_plain_html() {
+        let meta = parse_html(
+            "<HEAD><TITLE> Fish &amp; Chips </TITLE><META NAME=description CONTENT='Tasty'></HEAD>",
+        );
+        assert_eq!(meta.title.as_deref(), Some("Fish & Chips"));
+        assert_eq!(meta.description.as_deref(), Some("Tasty"));
+    }
+
+    #[test]
+    fn reads_the_rest_of_the_metadata() {
+        let meta = parse_html(
+            r##"<head>
+<meta property="og:url" content="https://example.com/page">
+<meta property="og:site_name" content="Example">
+<meta property="og:image" content="/first.png">
+<meta property="og:image" content="/second.png">
+<meta name="theme-color" content="#ff8800">
+<link rel="alternate" type="application/json+oembed" href="/oembed?url=page" />
+</head>"##,
+        );
+        assert_eq!(meta.url.as_deref(), Some("https://example.com/page"));
+        assert_eq!(meta.site_name.as_deref(), Some("Example"));
+        assert_eq!(meta.image.as_deref(), Some("/first.png"));
+        assert_eq!(meta.color, Some(0xff8800));
+        assert_eq!(meta.oembed_url.as_deref(), Some("/oembed?url=page"));
+    }
+
+    #[test]
+    fn ignores_the_body_and_empty_tags() {
+        let meta = parse_html(
+            r#"<head><meta property="og:title" content="  "></head>
+<body><meta property="og:title" content="In the body"><title>Also in the body</title></body>"#,
+        );
+        assert!(meta.title.is_none());
+        assert!(meta.description.is_none());
+    }
+}
@@ -1,3 +1,6 @@
+use crate::avatars::{delete_image_files, ImageKind};
+use crate::storage::delete_attachment_files;
+use crate::users::load_user_attachment_ids;
 use crate::WebServerError;
 use axum::extract::Path;
 use ferrischat_common::types::ErrorJson;
@@ -24,9 +27,23 @@ pub async fn delete_bot(
         return Err(ErrorJson::new_403("you are not the owner of this bot".to_string()).into());
     }
 
-    sqlx::query!("DELETE FROM users WHERE id = $1", bigint_user_id)
-        .execute(db)
-        .await?;
+    let mut tx = db.begin().await?;
+
+    let attachment_ids = load_user_attachment_ids(&mut tx, &bigint_user_id).await?;
+    let bot = sqlx::query!(
+        "DELETE FROM users WHERE id = $1 RETURNING avatar",
+        bigint_user_id
+    )
+    .fetch_optional(&mut tx)
+    .await?
+    .ok_or_else(|| ErrorJson::new_404(format!("Unknown bot with ID {}", bot_id)))?;
+
+    tx.commit().await?;
+
+    delete_attachment_files(attachment_ids).await;
+    if let Some(avatar) = bot.avatar {
+        delete_image_files(ImageKind::UserAvatar, user_id, &avatar).await;
+    }
 
     Ok(http::StatusCode::NO_CONTENT)
 }
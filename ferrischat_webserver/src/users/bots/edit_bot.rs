@@ -1,3 +1,4 @@
+use crate::avatars::{delete_replaced_image, upload_image, ImageKind};
 use crate::WebServerError;
 use axum::extract::Path;
 use axum::Json;
@@ -7,6 +8,8 @@ use sqlx::types::BigDecimal;
 
 /// PATCH `/v0/users/me/bots/{bot_id}`
 /// Edits the bot with the attached payload
+///
+/// `avatar` is a base64 encoded image, or empty to remove it.
 pub async fn edit_bot(
     Path((_, bot_id)): Path<(u128, u128)>,
     Json(BotUpdateJson {
@@ -44,6 +47,11 @@ pub async fn edit_bot(
     }
 
     if let Some(avatar) = avatar {
+        let old_avatar = sqlx::query!("SELECT avatar FROM users WHERE id = $1", bigint_bot_id)
+            .fetch_optional(db)
+            .await?
+            .and_then(|x| x.avatar);
+        let avatar = upload_image(ImageKind::UserAvatar, bot_id, avatar).await?;
         sqlx::query!(
             "UPDATE users SET avatar = $1 WHERE id = $2",
             avatar,
@@ -51,6 +59,7 @@ pub async fn edit_bot(
         )
         .execute(db)
         .await?;
+        delete_replaced_image(ImageKind::UserAvatar, bot_id, old_avatar, &avatar).await;
     }
 
     let user = sqlx::query!("SELECT * FROM users WHERE id = $1", bigint_bot_id)
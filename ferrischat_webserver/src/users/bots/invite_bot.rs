@@ -44,13 +44,16 @@ pub async fn invite_bot(
         return Err(ErrorJson::new_409("bot has already joined this guild".to_string()).into());
     };
 
-    sqlx::query!(
-        "INSERT INTO members VALUES ($1, $2)",
+    // bots don't join through invites: the guild owner adding them is the inviter
+    let joined_at = sqlx::query!(
+        "INSERT INTO members (user_id, guild_id, inviter_id) VALUES ($1, $2, $3) RETURNING joined_at",
         bigint_bot_id,
-        bigint_guild_id
+        bigint_guild_id,
+        bigint_user_id
     )
-    .execute(db)
-    .await?;
+    .fetch_one(db)
+    .await?
+    .joined_at;
 
     let member_obj = Member {
         user_id: Some(bot_id),
@@ -72,6 +75,10 @@ pub async fn invite_bot(
         }),
         guild_id: Some(guild_id),
         guild: None,
+        joined_at: Some(joined_at),
+        invite_code: None,
+        inviter_id: Some(auth.0),
+        pending: Some(false),
     };
 
     let event = WsOutboundEvent::MemberCreate {
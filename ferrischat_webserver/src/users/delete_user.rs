@@ -1,6 +1,9 @@
+use crate::avatars::{delete_image_files, ImageKind};
+use crate::storage::delete_attachment_files;
 use crate::WebServerError;
 use axum::extract::Path;
 use ferrischat_common::types::ErrorJson;
+use sqlx::types::BigDecimal;
 
 /// DELETE `/v0/users/{user_id}`
 /// Deletes the authenticated user
@@ -15,14 +18,54 @@ pub async fn delete_user(
     let bigint_user_id = u128_to_bigdecimal!(user_id);
     let db = get_db_or_fail!();
 
+    let mut tx = db.begin().await?;
+
+    let attachment_ids = load_user_attachment_ids(&mut tx, &bigint_user_id).await?;
+
     // Drop the user.
-    sqlx::query!(
-        "DELETE FROM users WHERE id = $1 RETURNING (id)",
+    let user = sqlx::query!(
+        "DELETE FROM users WHERE id = $1 RETURNING avatar",
         bigint_user_id,
     )
-    .fetch_optional(db)
+    .fetch_optional(&mut tx)
     .await?
     .ok_or_else(|| ErrorJson::new_404("account not found".to_string()))?;
 
+    tx.commit().await?;
+
+    delete_attachment_files(attachment_ids).await;
+    if let Some(avatar) = user.avatar {
+        delete_image_files(ImageKind::UserAvatar, user_id, &avatar).await;
+    }
+
     Ok(http::StatusCode::NO_CONTENT)
 }
+
+/// Finds the attachments that go when a user is deleted:
+/// everything they sent, and everything sent in guilds they own.
+///
+/// The rows go with the user, but the files have to be removed by hand once that's committed.
+pub async fn load_user_attachment_ids(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    bigint_user_id: &BigDecimal,
+) -> Result<Vec<u128>, WebServerError> {
+    let attachments = sqlx::query!(
+        r#"
+SELECT a.id
+FROM attachments a
+    INNER JOIN messages m ON m.id = a.message_id
+    INNER JOIN channels c ON c.id = m.channel_id
+WHERE m.author_id = $1
+   OR c.guild_id IN (SELECT id FROM guilds WHERE owner_id = $1)
+"#,
+        bigint_user_id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut attachment_ids = Vec::with_capacity(attachments.len());
+    for x in attachments {
+        attachment_ids.push(bigdecimal_to_u128!(x.id));
+    }
+    Ok(attachment_ids)
+}
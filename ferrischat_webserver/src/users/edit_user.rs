@@ -1,3 +1,4 @@
+use crate::avatars::{delete_replaced_image, upload_image, ImageKind};
 use crate::WebServerError;
 use axum::extract::Json;
 use ferrischat_common::request_json::UserUpdateJson;
@@ -5,6 +6,8 @@ use ferrischat_common::types::{ErrorJson, User, UserFlags};
 
 /// PATCH `/v0/users/me`
 /// Modifies the authenticated user
+///
+/// `avatar` is a base64 encoded image, or empty to remove it.
 pub async fn edit_user(
     Json(UserUpdateJson {
         username,
@@ -32,6 +35,11 @@ pub async fn edit_user(
     }
 
     if let Some(avatar) = avatar {
+        let old_avatar = sqlx::query!("SELECT avatar FROM users WHERE id = $1", bigint_user_id)
+            .fetch_optional(db)
+            .await?
+            .and_then(|x| x.avatar);
+        let avatar = upload_image(ImageKind::UserAvatar, user_id, avatar).await?;
         sqlx::query!(
             "UPDATE users SET avatar = $1 WHERE id = $2",
             avatar,
@@ -39,6 +47,7 @@ pub async fn edit_user(
         )
         .execute(db)
         .await?;
+        delete_replaced_image(ImageKind::UserAvatar, user_id, old_avatar, &avatar).await;
     }
 
     if let Some(email) = email {
@@ -1,5 +1,7 @@
 use crate::WebServerError;
-use ferrischat_common::types::{Channel, ErrorJson, Guild, GuildFlags, Member, User, UserFlags};
+use ferrischat_common::types::{
+    Channel, ChannelType, ErrorJson, Guild, GuildFlags, Member, User, UserFlags,
+};
 use num_traits::cast::ToPrimitive;
 
 /// GET `/v0/users/me`
@@ -85,7 +87,7 @@ pub async fn get_me(
                         name: x.name.clone(),
                         channels: Some(
                             sqlx::query!(
-                                "SELECT * FROM channels WHERE guild_id = $1",
+                                "SELECT * FROM channels WHERE guild_id = $1 AND channel_type <> 4 ORDER BY position, id",
                                 x.id.clone()
                             )
                             .fetch_all(db)
@@ -100,12 +102,20 @@ pub async fn get_me(
                                         .0
                                         .to_u128()?,
                                     name: x.name.clone(),
-                                    guild_id: x
-                                        .guild_id
-                                        .with_scale(0)
-                                        .into_bigint_and_exponent()
-                                        .0
-                                        .to_u128()?,
+                                    guild_id: x.guild_id.as_ref().and_then(|id| {
+                                        id.with_scale(0).into_bigint_and_exponent().0.to_u128()
+                                    }),
+                                    channel_type: ChannelType::from_i16(x.channel_type)
+                                        .unwrap_or(ChannelType::Text),
+                                    parent_id: x.parent_id.as_ref().and_then(|id| {
+                                        id.with_scale(0).into_bigint_and_exponent().0.to_u128()
+                                    }),
+                                    position: x.position,
+                                    topic: x.topic.clone(),
+                                    slowmode: x.slowmode,
+                                    nsfw: x.nsfw,
+                                    announcement: x.announcement,
+                                    message_ttl: x.message_ttl,
                                 })
                             })
                             .collect(),
@@ -157,6 +167,12 @@ pub async fn get_me(
                                             .0
                                             .to_u128(),
                                         guild: None,
+                                        joined_at: Some(x.joined_at),
+                                        invite_code: x.invite_code,
+                                        inviter_id: x.inviter_id.and_then(|id| {
+                                            id.with_scale(0).into_bigint_and_exponent().0.to_u128()
+                                        }),
+                                        pending: Some(x.pending),
                                     };
 
                                     members.push(member);
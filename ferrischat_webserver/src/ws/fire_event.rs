@@ -1,25 +1,44 @@
 use crate::WebServerError;
-use ferrischat_common::types::{Channel, Guild, Invite, Member, Message, Role};
+use ferrischat_common::types::{Channel, DMChannel, Guild, Invite, Member, Message, Role, Thread};
 use ferrischat_common::ws::WsOutboundEvent;
 use ferrischat_redis::redis::AsyncCommands;
 
+/// Where an event should be published to.
+enum EventTarget {
+    /// A single event name, which everyone subscribed to it receives.
+    Name(String),
+    /// Every recipient of a DM channel. They're looked up when the event is fired.
+    DmChannel(u128),
+    /// Only these users.
+    Users(Vec<u128>),
+}
+
 #[inline]
-fn get_event_name(event: &WsOutboundEvent) -> String {
-    match event {
+#[allow(clippy::too_many_lines)]
+fn get_event_target(event: &WsOutboundEvent) -> EventTarget {
+    EventTarget::Name(match event {
         /*****************
          * Message Events *
          *****************/
         WsOutboundEvent::MessageDelete {
             message:
                 Message {
-                    channel: Channel { guild_id, .. },
+                    channel:
+                        Channel {
+                            guild_id: Some(guild_id),
+                            ..
+                        },
                     ..
                 },
         }
         | WsOutboundEvent::MessageUpdate {
             old:
                 Message {
-                    channel: Channel { guild_id, .. },
+                    channel:
+                        Channel {
+                            guild_id: Some(guild_id),
+                            ..
+                        },
                     ..
                 },
             ..
@@ -27,24 +46,171 @@ fn get_event_name(event: &WsOutboundEvent) -> String {
         | WsOutboundEvent::MessageCreate {
             message:
                 Message {
-                    channel: Channel { guild_id, .. },
+                    channel:
+                        Channel {
+                            guild_id: Some(guild_id),
+                            ..
+                        },
+                    ..
+                },
+        }
+        | WsOutboundEvent::MessageDeleteBulk {
+            channel:
+                Channel {
+                    guild_id: Some(guild_id),
                     ..
                 },
+            ..
+        }
+        | WsOutboundEvent::ReactionAdd {
+            channel:
+                Channel {
+                    guild_id: Some(guild_id),
+                    ..
+                },
+            ..
+        }
+        | WsOutboundEvent::ReactionRemove {
+            channel:
+                Channel {
+                    guild_id: Some(guild_id),
+                    ..
+                },
+            ..
         } => format!("message_{}", *guild_id),
+        WsOutboundEvent::MessageDelete {
+            message:
+                Message {
+                    channel:
+                        Channel {
+                            id, guild_id: None, ..
+                        },
+                    ..
+                },
+        }
+        | WsOutboundEvent::MessageUpdate {
+            old:
+                Message {
+                    channel:
+                        Channel {
+                            id, guild_id: None, ..
+                        },
+                    ..
+                },
+            ..
+        }
+        | WsOutboundEvent::MessageCreate {
+            message:
+                Message {
+                    channel:
+                        Channel {
+                            id, guild_id: None, ..
+                        },
+                    ..
+                },
+        }
+        | WsOutboundEvent::MessageDeleteBulk {
+            channel: Channel {
+                id, guild_id: None, ..
+            },
+            ..
+        }
+        | WsOutboundEvent::ReactionAdd {
+            channel: Channel {
+                id, guild_id: None, ..
+            },
+            ..
+        }
+        | WsOutboundEvent::ReactionRemove {
+            channel: Channel {
+                id, guild_id: None, ..
+            },
+            ..
+        } => return EventTarget::DmChannel(*id),
 
         /*****************
          * Channel Events *
          *****************/
         WsOutboundEvent::ChannelCreate {
-            channel: Channel { guild_id, .. },
+            channel:
+                Channel {
+                    guild_id: Some(guild_id),
+                    ..
+                },
         }
         | WsOutboundEvent::ChannelUpdate {
-            old: Channel { guild_id, .. },
+            old:
+                Channel {
+                    guild_id: Some(guild_id),
+                    ..
+                },
             ..
         }
         | WsOutboundEvent::ChannelDelete {
-            channel: Channel { guild_id, .. },
+            channel:
+                Channel {
+                    guild_id: Some(guild_id),
+                    ..
+                },
+        }
+        | WsOutboundEvent::ChannelPinsUpdate {
+            channel:
+                Channel {
+                    guild_id: Some(guild_id),
+                    ..
+                },
+            ..
         } => format!("channel_{}", *guild_id),
+        WsOutboundEvent::ChannelCreate {
+            channel: Channel {
+                id, guild_id: None, ..
+            },
+        }
+        | WsOutboundEvent::ChannelUpdate {
+            old: Channel {
+                id, guild_id: None, ..
+            },
+            ..
+        }
+        | WsOutboundEvent::ChannelDelete {
+            channel: Channel {
+                id, guild_id: None, ..
+            },
+        }
+        | WsOutboundEvent::ChannelPinsUpdate {
+            channel: Channel {
+                id, guild_id: None, ..
+            },
+            ..
+        } => return EventTarget::DmChannel(*id),
+
+        /********************
+         * DM Channel Events *
+         ********************/
+        WsOutboundEvent::DmChannelCreate { channel }
+        | WsOutboundEvent::DmChannelDelete { channel } => {
+            return EventTarget::Users(channel.recipients.iter().map(|u| u.id).collect())
+        }
+        // users who were just added or removed need to hear about it too
+        WsOutboundEvent::DmChannelUpdate {
+            old: DMChannel {
+                recipients: old, ..
+            },
+            new: DMChannel {
+                recipients: new, ..
+            },
+        } => {
+            let mut users = old.iter().chain(new).map(|u| u.id).collect::<Vec<_>>();
+            users.sort_unstable();
+            users.dedup();
+            return EventTarget::Users(users);
+        }
+
+        /*********************
+         * Read State Events *
+         *********************/
+        // only the user's own sessions care about what they've read
+        WsOutboundEvent::MessageAck { user_id, .. } => return EventTarget::Users(vec![*user_id]),
 
         /***************
          * Guild Events *
@@ -129,13 +295,48 @@ fn get_event_name(event: &WsOutboundEvent) -> String {
          * Typing Events *
          ****************/
         WsOutboundEvent::TypingStart {
-            channel: Channel { guild_id, .. },
+            channel:
+                Channel {
+                    guild_id: Some(guild_id),
+                    ..
+                },
             ..
         }
         | WsOutboundEvent::TypingEnd {
-            channel: Channel { guild_id, .. },
+            channel:
+                Channel {
+                    guild_id: Some(guild_id),
+                    ..
+                },
             ..
         } => format!("typing_{}", guild_id),
+        WsOutboundEvent::TypingStart {
+            channel: Channel {
+                id, guild_id: None, ..
+            },
+            ..
+        }
+        | WsOutboundEvent::TypingEnd {
+            channel: Channel {
+                id, guild_id: None, ..
+            },
+            ..
+        } => return EventTarget::DmChannel(*id),
+
+        /****************
+         * Thread Events *
+         ****************/
+        WsOutboundEvent::ThreadCreate {
+            thread: Thread { guild_id, .. },
+        }
+        | WsOutboundEvent::ThreadUpdate {
+            old: Thread { guild_id, .. },
+            ..
+        }
+        | WsOutboundEvent::ThreadMembersUpdate {
+            thread: Thread { guild_id, .. },
+            ..
+        } => format!("thread_{}", guild_id),
 
         /*********************
          * Member Role Events *
@@ -150,20 +351,43 @@ fn get_event_name(event: &WsOutboundEvent) -> String {
         } => format!("member_role_{}", guild_id),
 
         _ => panic!("called `fire_event` with an unsupported event type"),
-    }
+    })
 }
 
+/// Publishes an event to Redis for the gateway to pick up.
+///
+/// DM events are published once per recipient as `dm_{user_id}`,
+/// so they never reach anyone outside of the channel.
 pub async fn fire_event(event: &WsOutboundEvent) -> Result<(), WebServerError> {
-    let event_name = get_event_name(event);
+    let event_names = match get_event_target(event) {
+        EventTarget::Name(name) => vec![name],
+        EventTarget::DmChannel(channel_id) => {
+            let recipients = sqlx::query!(
+                "SELECT user_id FROM channel_recipients WHERE channel_id = $1",
+                u128_to_bigdecimal!(channel_id)
+            )
+            .fetch_all(get_db_or_fail!())
+            .await?;
+            let mut names = Vec::with_capacity(recipients.len());
+            for x in recipients {
+                names.push(format!("dm_{}", bigdecimal_to_u128!(x.user_id)));
+            }
+            names
+        }
+        EventTarget::Users(users) => users.into_iter().map(|id| format!("dm_{}", id)).collect(),
+    };
     let message = simd_json::to_vec(event)?;
 
-    ferrischat_redis::REDIS_MANAGER
+    let mut redis = ferrischat_redis::REDIS_MANAGER
         .get()
         .ok_or(WebServerError::MissingRedis)?
         .get()
-        .await?
-        .publish::<_, _, Option<u32>>(event_name, message)
-        .await
-        .map_err(WebServerError::from)
-        .map(|_| ())
+        .await?;
+    for event_name in event_names {
+        redis
+            .publish::<_, _, Option<u32>>(event_name, message.as_slice())
+            .await?;
+    }
+
+    Ok(())
 }
@@ -0,0 +1,11 @@
+use ferrischat_common::ws::WsOutboundEvent;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+
+pub async fn handle_dm_tx<'a>(
+    _msg: &WsOutboundEvent,
+    user_id: u128,
+    recipient_id: u128,
+) -> Result<bool, CloseFrame<'a>> {
+    // DM events are published once for every recipient, so only pass on this user's copy
+    Ok(user_id == recipient_id)
+}
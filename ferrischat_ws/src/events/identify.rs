@@ -113,6 +113,12 @@ pub async fn handle_identify_rx<'a>(
                                 }),
                                 guild_id: Some(id),
                                 guild: None,
+                                joined_at: Some(x.joined_at),
+                                invite_code: x.invite_code.clone(),
+                                inviter_id: x.inviter_id.as_ref().and_then(|id| {
+                                    id.with_scale(0).into_bigint_and_exponent().0.to_u128()
+                                }),
+                                pending: Some(x.pending),
                             })
                         })
                         .collect(),
@@ -120,9 +126,12 @@ pub async fn handle_identify_rx<'a>(
             };
 
             let channels = {
-                let resp = sqlx::query!("SELECT * FROM channels WHERE guild_id = $1", x.id.clone())
-                    .fetch_all(db)
-                    .await?;
+                let resp = sqlx::query!(
+                    "SELECT * FROM channels WHERE guild_id = $1 AND channel_type <> 4 ORDER BY position, id",
+                    x.id.clone()
+                )
+                .fetch_all(db)
+                .await?;
 
                 Some(
                     resp.iter()
@@ -136,7 +145,24 @@ pub async fn handle_identify_rx<'a>(
                                     .0
                                     .to_u128()?,
                                 name: x.name.clone(),
-                                guild_id: id,
+                                guild_id: Some(id),
+                                channel_type: ferrischat_common::types::ChannelType::from_i16(
+                                    x.channel_type,
+                                )
+                                .unwrap_or(ferrischat_common::types::ChannelType::Text),
+                                parent_id: x.parent_id.as_ref().and_then(|parent_id| {
+                                    parent_id
+                                        .with_scale(0)
+                                        .into_bigint_and_exponent()
+                                        .0
+                                        .to_u128()
+                                }),
+                                position: x.position,
+                                topic: x.topic.clone(),
+                                slowmode: x.slowmode,
+                                nsfw: x.nsfw,
+                                announcement: x.announcement,
+                                message_ttl: x.message_ttl,
                             })
                         })
                         .collect(),
@@ -177,8 +203,32 @@ pub async fn handle_identify_rx<'a>(
         }
     };
 
+    let read_states = sqlx::query!(
+        "SELECT * FROM read_states WHERE user_id = $1",
+        bigdecimal_user_id
+    )
+    .fetch_all(db)
+    .await?
+    .iter()
+    .filter_map(|x| {
+        Some(ferrischat_common::types::ReadState {
+            channel_id: x
+                .channel_id
+                .with_scale(0)
+                .into_bigint_and_exponent()
+                .0
+                .to_u128()?,
+            last_message_id: x
+                .last_message_id
+                .as_ref()
+                .and_then(|id| id.with_scale(0).into_bigint_and_exponent().0.to_u128()),
+            mention_count: x.mention_count,
+        })
+    })
+    .collect();
+
     inter_tx
-        .send(WsOutboundEvent::IdentifyAccepted { user })
+        .send(WsOutboundEvent::IdentifyAccepted { user, read_states })
         .await
         .as_ref()?;
 
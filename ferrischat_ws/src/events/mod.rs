@@ -1,4 +1,5 @@
 mod channel;
+mod dm;
 mod guild;
 mod identify;
 mod invite;
@@ -6,8 +7,10 @@ mod member;
 mod message;
 mod ping;
 mod pong;
+mod thread;
 
 pub use channel::*;
+pub use dm::*;
 pub use guild::*;
 pub use identify::*;
 pub use invite::*;
@@ -15,3 +18,4 @@ pub use member::*;
 pub use message::*;
 pub use ping::*;
 pub use pong::*;
+pub use thread::*;
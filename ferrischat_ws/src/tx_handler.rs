@@ -137,6 +137,8 @@ pub async fn tx_handler(
                     "guild" => handle_guild_tx(db, &outbound_message, uid, obj_id).await,
                     "member" => handle_member_tx(db, &outbound_message, uid, obj_id).await,
                     "invite" => handle_invite_tx(db, &outbound_message, uid, obj_id).await,
+                    "dm" => handle_dm_tx(&outbound_message, uid, obj_id).await,
+                    "thread" => handle_thread_tx(db, &outbound_message, uid, obj_id).await,
                     t => {
                         warn!("unknown event type {}", t);
                         continue;
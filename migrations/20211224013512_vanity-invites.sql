@@ -0,0 +1,7 @@
+-- Add migration script here
+ALTER TABLE invites
+    ADD COLUMN vanity BOOLEAN NOT NULL DEFAULT false;
+
+-- a guild can only claim a single vanity code
+CREATE UNIQUE INDEX invites_vanity_guild_uindex
+    ON invites (guild_id) WHERE vanity;
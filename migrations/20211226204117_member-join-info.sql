@@ -0,0 +1,28 @@
+-- Add migration script here
+ALTER TABLE members
+    ADD COLUMN joined_at TIMESTAMP WITHOUT TIME ZONE,
+    ADD COLUMN invite_code TEXT,
+    ADD COLUMN inviter_id numeric(39) REFERENCES users ON DELETE SET NULL;
+
+-- snowflakes store their creation time as milliseconds since 2020-01-01 in the upper 64 bits.
+-- owners joined when they created the guild: everyone else gets the time of their first
+-- message in the guild, falling back to the guild's creation time if they never sent one
+UPDATE members m
+SET joined_at = to_timestamp(
+        (floor(coalesce(
+                   (SELECT min(msg.id)
+                    FROM messages msg
+                             INNER JOIN channels c ON msg.channel_id = c.id
+                    WHERE c.guild_id = m.guild_id
+                      AND msg.author_id = m.user_id
+                      AND m.user_id != g.owner_id),
+                   g.id
+                   ) / 18446744073709551616) + 1577836800000) / 1000
+    ) AT TIME ZONE 'UTC'
+FROM guilds g
+WHERE g.id = m.guild_id;
+
+ALTER TABLE members ALTER COLUMN joined_at SET DEFAULT (now() AT TIME ZONE 'UTC');
+ALTER TABLE members ALTER COLUMN joined_at SET NOT NULL;
+
+CREATE INDEX members_invite_code_idx ON members (guild_id, invite_code);
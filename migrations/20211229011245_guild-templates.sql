@@ -0,0 +1,26 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS guild_templates
+(
+    code            TEXT PRIMARY KEY                                NOT NULL,
+    name            VARCHAR(100)                                    NOT NULL,
+    source_guild_id numeric(39) REFERENCES guilds ON DELETE SET NULL,
+    creator_id      numeric(39) REFERENCES users ON DELETE CASCADE  NOT NULL,
+    created_at      BIGINT                                          NOT NULL
+);
+CREATE TABLE IF NOT EXISTS guild_template_channels
+(
+    template_code TEXT REFERENCES guild_templates ON DELETE CASCADE NOT NULL,
+    position      INT                                               NOT NULL,
+    name          VARCHAR(100)                                      NOT NULL
+);
+CREATE TABLE IF NOT EXISTS guild_template_roles
+(
+    template_code TEXT REFERENCES guild_templates ON DELETE CASCADE NOT NULL,
+    name          VARCHAR(256)                                      NOT NULL,
+    color         INT,
+    position      SMALLINT                                          NOT NULL DEFAULT 0,
+    permissions   bytea                                             NOT NULL DEFAULT ''
+);
+
+CREATE INDEX guild_template_channels_idx ON guild_template_channels (template_code);
+CREATE INDEX guild_template_roles_idx ON guild_template_roles (template_code);
@@ -0,0 +1,15 @@
+-- Add migration script here
+ALTER TABLE guilds
+    ADD COLUMN description  VARCHAR(300),
+    ADD COLUMN tags         TEXT[]  NOT NULL DEFAULT '{}',
+    ADD COLUMN discoverable BOOLEAN NOT NULL DEFAULT false;
+
+-- this expression must match the one used in the discovery search query exactly,
+-- otherwise Postgres won't use the index
+CREATE INDEX guilds_discovery_search_idx
+    ON guilds USING GIN (to_tsvector('english', name || ' ' || coalesce(description, '')))
+    WHERE discoverable;
+CREATE INDEX guilds_discovery_tags_idx
+    ON guilds USING GIN (tags)
+    WHERE discoverable;
+CREATE INDEX members_guild_id_idx ON members (guild_id);
@@ -0,0 +1,11 @@
+-- Add migration script here
+ALTER TABLE guilds
+    ADD COLUMN verification_level SMALLINT NOT NULL DEFAULT 0,
+    -- both in minutes
+    ADD COLUMN min_account_age    INT      NOT NULL DEFAULT 5,
+    ADD COLUMN min_membership_age INT      NOT NULL DEFAULT 10,
+    -- new members must accept these before posting, if set
+    ADD COLUMN rules              TEXT;
+
+ALTER TABLE members
+    ADD COLUMN pending BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,15 @@
+-- Add migration script here
+ALTER TABLE channels
+    -- 0 is a text channel, 1 is a category
+    ADD COLUMN channel_type SMALLINT NOT NULL DEFAULT 0,
+    ADD COLUMN parent_id    numeric(39) REFERENCES channels ON DELETE SET NULL,
+    ADD COLUMN position     INT      NOT NULL DEFAULT 0;
+
+-- keep existing channels in the order they were created in
+UPDATE channels c
+SET position = o.position
+FROM (SELECT id, row_number() OVER (PARTITION BY guild_id ORDER BY id) - 1 AS position
+      FROM channels) o
+WHERE o.id = c.id;
+
+CREATE INDEX channels_guild_position_idx ON channels (guild_id, position);
@@ -0,0 +1,8 @@
+-- Add migration script here
+ALTER TABLE channels
+    ADD COLUMN topic        VARCHAR(1024),
+    -- seconds members have to wait between messages, 0 if disabled
+    ADD COLUMN slowmode     INT     NOT NULL DEFAULT 0,
+    ADD COLUMN nsfw         BOOLEAN NOT NULL DEFAULT false,
+    -- only moderators can post in announcement channels
+    ADD COLUMN announcement BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,19 @@
+-- Add migration script here
+-- DM channels live alongside guild channels, but don't belong to a guild
+ALTER TABLE channels
+    ALTER COLUMN guild_id DROP NOT NULL,
+    -- the creator of a group DM, who can remove other recipients from it
+    ADD COLUMN owner_id numeric(39) REFERENCES users ON DELETE SET NULL;
+
+-- channel types 2 and 3 are 1:1 and group DMs
+ALTER TABLE channels
+    ADD CONSTRAINT channels_dm_guild_check CHECK ((guild_id IS NULL) = (channel_type IN (2, 3)));
+
+CREATE TABLE IF NOT EXISTS channel_recipients
+(
+    channel_id numeric(39) REFERENCES channels ON DELETE CASCADE NOT NULL,
+    user_id    numeric(39) REFERENCES users ON DELETE CASCADE    NOT NULL,
+    PRIMARY KEY (channel_id, user_id)
+);
+
+CREATE INDEX channel_recipients_user_id_idx ON channel_recipients (user_id);
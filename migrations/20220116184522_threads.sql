@@ -0,0 +1,26 @@
+-- Add migration script here
+-- threads are channels of type 4, with their parent channel in channels.parent_id
+CREATE TABLE IF NOT EXISTS threads
+(
+    channel_id            numeric(39) PRIMARY KEY REFERENCES channels ON DELETE CASCADE,
+    owner_id              numeric(39) REFERENCES users ON DELETE SET NULL,
+    -- only one thread can be started from each message
+    starter_message_id    numeric(39) UNIQUE REFERENCES messages ON DELETE SET NULL,
+    archived              BOOLEAN                     NOT NULL DEFAULT false,
+    locked                BOOLEAN                     NOT NULL DEFAULT false,
+    -- in minutes
+    auto_archive_duration INT                         NOT NULL DEFAULT 1440,
+    last_activity_at      TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (now() AT TIME ZONE 'UTC')
+);
+
+CREATE INDEX threads_active_idx ON threads (last_activity_at) WHERE NOT archived;
+
+CREATE TABLE IF NOT EXISTS thread_members
+(
+    thread_id numeric(39) REFERENCES threads ON DELETE CASCADE NOT NULL,
+    user_id   numeric(39) REFERENCES users ON DELETE CASCADE   NOT NULL,
+    joined_at TIMESTAMP WITHOUT TIME ZONE                      NOT NULL DEFAULT (now() AT TIME ZONE 'UTC'),
+    PRIMARY KEY (thread_id, user_id)
+);
+
+CREATE INDEX thread_members_user_id_idx ON thread_members (user_id);
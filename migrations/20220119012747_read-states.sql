@@ -0,0 +1,10 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS read_states
+(
+    user_id         numeric(39) REFERENCES users ON DELETE CASCADE    NOT NULL,
+    channel_id      numeric(39) REFERENCES channels ON DELETE CASCADE NOT NULL,
+    -- not a foreign key: the message may well be deleted after it's been read
+    last_message_id numeric(39),
+    mention_count   INT                                               NOT NULL DEFAULT 0,
+    PRIMARY KEY (user_id, channel_id)
+);
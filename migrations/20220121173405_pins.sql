@@ -0,0 +1,10 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS pins
+(
+    message_id numeric(39) PRIMARY KEY REFERENCES messages ON DELETE CASCADE,
+    channel_id numeric(39) REFERENCES channels ON DELETE CASCADE NOT NULL,
+    pinned_by  numeric(39) REFERENCES users ON DELETE SET NULL,
+    pinned_at  TIMESTAMP WITHOUT TIME ZONE                        NOT NULL DEFAULT (now() AT TIME ZONE 'UTC')
+);
+
+CREATE INDEX pins_channel_id_idx ON pins (channel_id, pinned_at);
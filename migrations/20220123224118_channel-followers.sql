@@ -0,0 +1,19 @@
+-- Add migration script here
+-- where a crossposted message was copied from
+-- these aren't foreign keys so the attribution survives the original being deleted
+ALTER TABLE messages
+    ADD COLUMN crosspost_guild_id   numeric(39),
+    ADD COLUMN crosspost_channel_id numeric(39),
+    ADD COLUMN crosspost_message_id numeric(39);
+
+CREATE INDEX messages_crosspost_message_id_idx ON messages (crosspost_message_id) WHERE crosspost_message_id IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS channel_followers
+(
+    source_channel_id numeric(39) REFERENCES channels ON DELETE CASCADE NOT NULL,
+    target_channel_id numeric(39) REFERENCES channels ON DELETE CASCADE NOT NULL,
+    created_by        numeric(39) REFERENCES users ON DELETE SET NULL,
+    PRIMARY KEY (source_channel_id, target_channel_id)
+);
+
+CREATE INDEX channel_followers_target_channel_id_idx ON channel_followers (target_channel_id);
@@ -0,0 +1,3 @@
+-- Add migration script here
+-- message history pages through a channel by ID in both directions
+CREATE INDEX messages_channel_id_id_idx ON messages (channel_id, id);
@@ -0,0 +1,11 @@
+-- Add migration script here
+-- replaces messages.reactions, which was dropped in 20210822034529_message_type_change
+CREATE TABLE IF NOT EXISTS reactions
+(
+    message_id numeric(39) REFERENCES messages ON DELETE CASCADE NOT NULL,
+    user_id    numeric(39) REFERENCES users ON DELETE CASCADE    NOT NULL,
+    -- a Unicode emoji for now: custom emoji will be stored as `name:id`
+    emoji      VARCHAR(64)                                       NOT NULL,
+    created_at TIMESTAMP WITHOUT TIME ZONE                       NOT NULL DEFAULT (now() AT TIME ZONE 'UTC'),
+    PRIMARY KEY (message_id, emoji, user_id)
+);
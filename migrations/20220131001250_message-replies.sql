@@ -0,0 +1,5 @@
+-- Add migration script here
+-- not a foreign key: replies stay replies once the original is deleted, and show it as such
+ALTER TABLE messages
+    ADD COLUMN reply_to_id          numeric(39),
+    ADD COLUMN reply_mention_author BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,15 @@
+-- Add migration script here
+-- the files themselves live in storage, under `attachments/{id}`
+CREATE TABLE IF NOT EXISTS attachments
+(
+    id           numeric(39) PRIMARY KEY                           NOT NULL,
+    message_id   numeric(39) REFERENCES messages ON DELETE CASCADE NOT NULL,
+    filename     VARCHAR(255)                                      NOT NULL,
+    size         BIGINT                                            NOT NULL,
+    content_type VARCHAR(255)                                      NOT NULL,
+    -- only set for images
+    width        INT,
+    height       INT
+);
+
+CREATE INDEX IF NOT EXISTS attachments_message_id_idx ON attachments (message_id);
@@ -0,0 +1,10 @@
+-- Add migration script here
+-- avatars used to be arbitrary URLs set by clients, and now hold the hash of an uploaded image.
+-- there's nothing to serve for the old ones, so they're dropped
+UPDATE users
+SET avatar = NULL
+WHERE avatar !~ '^[0-9a-f]{32}$';
+
+UPDATE guilds
+SET avatar = NULL
+WHERE avatar !~ '^[0-9a-f]{32}$';
@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE messages
+    ADD COLUMN embeds JSONB NOT NULL DEFAULT '[]';
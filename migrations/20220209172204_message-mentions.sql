@@ -0,0 +1,7 @@
+-- Add migration script here
+-- only mentions that were checked when the message was sent or edited end up here
+ALTER TABLE messages
+    ADD COLUMN mentions         numeric(39)[] NOT NULL DEFAULT '{}',
+    ADD COLUMN mention_roles    numeric(39)[] NOT NULL DEFAULT '{}',
+    ADD COLUMN mention_channels numeric(39)[] NOT NULL DEFAULT '{}',
+    ADD COLUMN mention_everyone BOOLEAN       NOT NULL DEFAULT false;
@@ -0,0 +1,6 @@
+-- Add migration script here
+-- generated, so it's kept up to date on every insert and edit without any help from the server
+ALTER TABLE messages
+    ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (to_tsvector('english', coalesce(content, ''))) STORED;
+
+CREATE INDEX messages_search_vector_idx ON messages USING GIN (search_vector);
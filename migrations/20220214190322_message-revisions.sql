@@ -0,0 +1,17 @@
+-- Add migration script here
+ALTER TABLE guilds
+    ADD COLUMN message_revisions          boolean NOT NULL DEFAULT false,
+    -- in days, or forever if null
+    ADD COLUMN message_revision_retention integer;
+
+CREATE TABLE IF NOT EXISTS message_revisions
+(
+    id         bigserial PRIMARY KEY,
+    message_id numeric(39) REFERENCES messages ON DELETE CASCADE NOT NULL,
+    content    text,
+    embeds     jsonb                                            NOT NULL DEFAULT '[]',
+    revised_at TIMESTAMP WITHOUT TIME ZONE                      NOT NULL DEFAULT (now() AT TIME ZONE 'UTC')
+);
+
+CREATE INDEX message_revisions_message_id_idx ON message_revisions (message_id, id);
+CREATE INDEX message_revisions_revised_at_idx ON message_revisions (revised_at);
@@ -0,0 +1,16 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS scheduled_messages
+(
+    id             numeric(39) PRIMARY KEY,
+    channel_id     numeric(39) REFERENCES channels ON DELETE CASCADE NOT NULL,
+    author_id      numeric(39) REFERENCES users ON DELETE CASCADE    NOT NULL,
+    content        text                                              NOT NULL,
+    embeds         jsonb                                             NOT NULL DEFAULT '[]',
+    reply_to_id    numeric(39),
+    mention_author boolean                                           NOT NULL DEFAULT false,
+    -- unix timestamp, in seconds
+    send_at        bigint                                            NOT NULL
+);
+
+CREATE INDEX scheduled_messages_send_at_idx ON scheduled_messages (send_at);
+CREATE INDEX scheduled_messages_author_id_idx ON scheduled_messages (author_id, channel_id);
@@ -0,0 +1,10 @@
+-- Add migration script here
+-- unix timestamp, in seconds
+ALTER TABLE messages
+    ADD COLUMN expires_at bigint;
+
+-- in seconds, or never if null
+ALTER TABLE channels
+    ADD COLUMN message_ttl integer;
+
+CREATE INDEX messages_expires_at_idx ON messages (expires_at) WHERE expires_at IS NOT NULL;
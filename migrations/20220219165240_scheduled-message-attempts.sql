@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE scheduled_messages
+    ADD COLUMN attempts integer NOT NULL DEFAULT 0;
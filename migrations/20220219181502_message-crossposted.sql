@@ -0,0 +1,7 @@
+-- Add migration script here
+ALTER TABLE messages
+    ADD COLUMN crossposted boolean NOT NULL DEFAULT false;
+
+UPDATE messages m
+SET crossposted = true
+WHERE EXISTS(SELECT * FROM messages c WHERE c.crosspost_message_id = m.id);
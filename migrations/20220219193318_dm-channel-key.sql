@@ -0,0 +1,19 @@
+-- Add migration script here
+-- `{lower user ID}:{higher user ID}` for 1:1 DMs, so there's only ever one per pair of users
+ALTER TABLE channels
+    ADD COLUMN dm_key text;
+
+-- if duplicates were already made, the oldest one keeps the key
+UPDATE channels c
+SET dm_key = k.dm_key
+FROM (SELECT r.channel_id,
+             min(r.user_id)::text || ':' || max(r.user_id)::text AS dm_key,
+             row_number() OVER (PARTITION BY min(r.user_id), max(r.user_id) ORDER BY r.channel_id) AS n
+      FROM channel_recipients r
+          INNER JOIN channels ch ON ch.id = r.channel_id
+      WHERE ch.channel_type = 2
+      GROUP BY r.channel_id) AS k
+WHERE c.id = k.channel_id
+  AND k.n = 1;
+
+CREATE UNIQUE INDEX channels_dm_key_idx ON channels (dm_key);
@@ -0,0 +1,5 @@
+-- Add migration script here
+ALTER TABLE guild_template_channels
+    ADD COLUMN channel_type    smallint NOT NULL DEFAULT 0,
+    -- the position of this channel's category within the same template
+    ADD COLUMN parent_position INT;
@@ -0,0 +1,11 @@
+-- Add migration script here
+-- snowflakes store their creation time as milliseconds since 2020-01-01 in the upper 64 bits.
+-- this gives it back as milliseconds since the Unix epoch
+CREATE OR REPLACE FUNCTION snowflake_timestamp(id numeric) RETURNS numeric
+    LANGUAGE sql
+    IMMUTABLE
+    RETURNS NULL ON NULL INPUT
+AS
+$$
+SELECT floor(id / 18446744073709551616) + 1577836800000
+$$;